@@ -0,0 +1,105 @@
+// Knot hashing, as introduced on day 10 and reused on day 14. A circular list of marks is twisted
+// by reversing runs of it, and the result is condensed into a 128-bit hash.
+
+const SUFFIX: [usize; 5] = [17, 31, 73, 47, 23];
+const ROUNDS: usize = 64;
+const BLOCK_SIZE: usize = 16;
+
+pub struct KnotHasher {
+    marks: Vec<u8>,
+    // Where the next run starts, as an index into marks
+    position: usize,
+    skip: usize,
+}
+
+impl KnotHasher {
+    // Starts with the marks 0 to size - 1 in order, which all have to fit in a u8
+    pub fn new(size: usize) -> Self {
+        assert!(
+            (1..=256).contains(&size),
+            "Expected between 1 and 256 marks but was asked for {}",
+            size
+        );
+
+        Self {
+            marks: (0..=u8::MAX).take(size).collect(),
+            position: 0,
+            skip: 0,
+        }
+    }
+
+    // Reverses a run of each length in turn, skipping further ahead after each one
+    pub fn round(&mut self, lengths: &[usize]) {
+        let size = self.marks.len();
+        for &length in lengths {
+            assert!(
+                length <= size,
+                "Expected lengths of at most {} but found {}",
+                size,
+                length
+            );
+
+            for offset in 0..length / 2 {
+                self.marks.swap(
+                    (self.position + offset) % size,
+                    (self.position + length - 1 - offset) % size,
+                );
+            }
+
+            self.position = (self.position + length + self.skip) % size;
+            self.skip += 1;
+        }
+    }
+
+    pub fn marks(&self) -> &[u8] {
+        &self.marks
+    }
+}
+
+// The full hash of the input bytes, with the standard suffix, rounds and condensing
+pub fn hash(input: &[u8]) -> [u8; 16] {
+    let lengths: Vec<usize> = input
+        .iter()
+        .map(|byte| usize::from(*byte))
+        .chain(SUFFIX.iter().copied())
+        .collect();
+
+    let mut hasher = KnotHasher::new(256);
+    for _ in 0..ROUNDS {
+        hasher.round(&lengths);
+    }
+
+    let mut dense = [0; 16];
+    for (byte, block) in dense.iter_mut().zip(hasher.marks().chunks(BLOCK_SIZE)) {
+        *byte = block.iter().fold(0, |accumulator, mark| accumulator ^ mark);
+    }
+    dense
+}
+
+pub fn to_hex(hash: &[u8]) -> String {
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, to_hex, KnotHasher};
+
+    #[test]
+    fn single_round() {
+        let mut hasher = KnotHasher::new(5);
+        hasher.round(&[3, 4, 1, 5]);
+        assert_eq!(hasher.marks(), [3, 4, 2, 1, 0]);
+    }
+
+    #[test]
+    fn full_hash() {
+        for (input, expected) in [
+            ("", "a2582a3a0e66e6e86e3812dcb672a272"),
+            ("AoC 2017", "33efeb34ea91902bb2f59c9920caa6cd"),
+            ("1,2,3", "3efbe78a8d82f29979031a4aa0b16a9d"),
+            ("1,2,4", "63960835bcdc130f0b66d7ff4f6a5a8e"),
+        ] {
+            assert_eq!(to_hex(&hash(input.as_bytes())), expected);
+        }
+    }
+}
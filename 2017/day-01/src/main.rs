@@ -1,19 +1,78 @@
+#![deny(clippy::all, clippy::pedantic)]
+
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+fn parse_digits(line: &str) -> Vec<u8> {
+    line.bytes()
+        .map(|byte| {
+            assert!(
+                byte.is_ascii_digit(),
+                "Unexpected character '{}'",
+                char::from(byte)
+            );
+            byte - b'0'
+        })
+        .collect()
+}
+
+// Sums the digits that match the digit offset places further around the circular list
+fn solve_captcha(digits: &[u8], offset: usize) -> u32 {
+    digits
+        .iter()
+        .enumerate()
+        .filter(|(index, digit)| **digit == digits[(index + offset) % digits.len()])
+        .map(|(_, digit)| u32::from(*digit))
+        .sum()
+}
+
 fn main() {
-    let digits: Vec<u8> = b"1212"
-        .into_iter()
-        .map(|c| c - 48)
-        .collect();
-    let length = digits.len();
-    let mut sum = 0;
-    for i in 0..length {
-        /*
-        if digits[i] == digits[(i + 1) % length] {
-            sum += i32::from(digits[i]);
+    let args = Args::parse();
+
+    let mut line = String::new();
+    open_input(args.filename.as_deref())
+        .read_line(&mut line)
+        .expect("Failed to read line");
+    let digits = parse_digits(line.trim());
+
+    let offset = if args.part == 1 { 1 } else { digits.len() / 2 };
+    println!("Captcha: {}", solve_captcha(&digits, offset));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_digits, solve_captcha};
+
+    #[test]
+    fn next_digit() {
+        for (digits, sum) in [("1122", 3), ("1111", 4), ("1234", 0), ("91212129", 9)] {
+            assert_eq!(solve_captcha(&parse_digits(digits), 1), sum);
         }
-        */
-        if digits[i] == digits[(i + length / 2) % length] {
-            sum += i32::from(digits[i]);
+    }
+
+    #[test]
+    fn halfway_around() {
+        for (digits, sum) in [
+            ("1212", 6),
+            ("1221", 0),
+            ("123425", 4),
+            ("123123", 12),
+            ("12131415", 4),
+        ] {
+            let digits = parse_digits(digits);
+            assert_eq!(solve_captcha(&digits, digits.len() / 2), sum);
         }
     }
-    println!("{}", sum);
 }
@@ -1,49 +1,81 @@
-use std::io;
-
-fn checksum<I>(stream: I) -> i32
-where
-    I: Iterator<Item = i32>,
-{
-    let min_max = stream.fold((i32::max_value(), i32::min_value()), |acc, x| {
-        (std::cmp::min(acc.0, x), std::cmp::max(acc.1, x))
-    });
-    min_max.1 - min_max.0
+#![deny(clippy::all, clippy::pedantic)]
+
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+fn parse_row(line: &str) -> Vec<i32> {
+    line.split_whitespace()
+        .map(|text| {
+            text.parse()
+                .unwrap_or_else(|_| panic!("Failed to parse {} as i32", text))
+        })
+        .collect()
+}
+
+fn difference(row: &[i32]) -> i32 {
+    let min = row.iter().min().expect("Expected a non-empty row");
+    let max = row.iter().max().expect("Expected a non-empty row");
+    max - min
 }
 
-fn divisible(line: &str) -> i32 {
-    let mut seen: Vec<i32> = vec![];
-    for text in line.split_whitespace() {
-        let number: i32 = text.parse().expect("Expected an integer");
-        for s in seen.as_slice() {
-            if *s > number && s % number == 0 {
-                return s / number;
-            } else if number > *s && number % s == 0 {
-                return number / s;
+// The quotient of the only two numbers in the row where one evenly divides the other
+fn quotient(row: &[i32]) -> i32 {
+    for (index, first) in row.iter().enumerate() {
+        for second in &row[index + 1..] {
+            let (larger, smaller) = (first.max(second), first.min(second));
+            if *smaller != 0 && larger % smaller == 0 {
+                return larger / smaller;
             }
         }
-        seen.push(number);
     }
-    0
+    panic!("Failed to find evenly divisible numbers in {:?}", row);
 }
 
 fn main() {
-    let mut input = String::new();
-    let mut sum = 0;
-    while io::stdin().read_line(&mut input).is_ok() {
-        {
-            let trimmed = input.trim();
-            if trimmed.is_empty() {
-                break;
-            }
+    let args = Args::parse();
 
-            let stream = trimmed
-                .split_whitespace()
-                .map(|t| t.parse::<i32>().expect("Expected an integer"));
-            sum += checksum(stream);
+    let row_value = if args.part == 1 { difference } else { quotient };
 
-            // sum += divisible(trimmed);
+    let mut checksum = 0;
+    for line in open_input(args.filename.as_deref()).lines() {
+        let line = line.expect("Failed to read line");
+        if line.trim().is_empty() {
+            continue;
         }
-        input.clear();
+
+        checksum += row_value(&parse_row(&line));
+    }
+
+    println!("Checksum: {checksum}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{difference, parse_row, quotient};
+
+    fn checksum(rows: &[&str], row_value: fn(&[i32]) -> i32) -> i32 {
+        rows.iter().map(|row| row_value(&parse_row(row))).sum()
+    }
+
+    #[test]
+    fn differences() {
+        assert_eq!(checksum(&["5 1 9 5", "7 5 3", "2 4 6 8"], difference), 18);
+    }
+
+    #[test]
+    fn quotients() {
+        assert_eq!(checksum(&["5 9 2 8", "9 4 7 3", "3 8 6 5"], quotient), 9);
     }
-    println!("{}", sum);
 }
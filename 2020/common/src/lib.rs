@@ -1,18 +1,29 @@
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
 };
 
+// Opens the named file, or stdin if there is no filename or the filename is "-"
+pub fn open_input(filename: Option<&str>) -> Box<dyn BufRead> {
+    match filename {
+        Some(filename) if filename != "-" => {
+            let file =
+                File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
+            Box::new(BufReader::new(file))
+        }
+        _ => Box::new(io::stdin().lock()),
+    }
+}
+
 pub struct LineReader {
-    reader: BufReader<File>,
+    reader: Box<dyn BufRead>,
 }
 
 impl LineReader {
-    pub fn new(filename: &str) -> Self {
-        let file =
-            File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-        let reader = BufReader::new(file);
-        Self { reader }
+    pub fn new(filename: Option<&str>) -> Self {
+        Self {
+            reader: open_input(filename),
+        }
     }
 
     pub fn read_with<F>(&mut self, mut f: F) -> bool
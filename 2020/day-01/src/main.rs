@@ -3,98 +3,386 @@
 
 extern crate test;
 
-use clap::{crate_name, App, Arg};
+use std::collections::HashMap;
+
+use clap::{builder::RangedU64ValueParser, Parser};
 use common::LineReader;
 
-fn sum_product2(sorted: &[i32], target: i32) -> Option<i32> {
-    let mut candidate_index = sorted.len() - 1;
-    for number in sorted {
-        while number + sorted[candidate_index] > target {
-            if candidate_index == 0 {
-                return None;
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// How many entries to sum, instead of 2 for part 1 and 3 for part 2
+    #[arg(short, long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    count: Option<usize>,
+
+    /// Sum to look for
+    #[arg(short, long, default_value_t = 2020)]
+    target: i64,
+
+    /// Allow the same entry to be used more than once
+    #[arg(long)]
+    reuse: bool,
+
+    /// Print every combination reaching the target, not just the product of the first
+    #[arg(long)]
+    all: bool,
+}
+
+// Below this many entries, searching recursively down to pairs is quicker than building the
+// tables for meeting in the middle
+const MEET_IN_THE_MIDDLE_COUNT: usize = 4;
+
+// Finds combinations of entries summing to a target. Entries are kept as sorted distinct values
+// with how many times each appears, and combinations as non-decreasing lists of value indices, so
+// equal entries never produce the same combination twice.
+struct KSum {
+    values: Vec<i64>,
+    counts: Vec<usize>,
+    reuse: bool,
+}
+
+impl KSum {
+    fn new(entries: &[i32], reuse: bool) -> Self {
+        let mut sorted = entries.to_vec();
+        sorted.sort_unstable();
+
+        let mut values = Vec::new();
+        let mut counts: Vec<usize> = Vec::new();
+        for entry in sorted {
+            if values.last() == Some(&i64::from(entry)) {
+                *counts.last_mut().unwrap() += 1;
+            } else {
+                values.push(i64::from(entry));
+                counts.push(1);
             }
+        }
 
-            candidate_index -= 1;
+        Self {
+            values,
+            counts,
+            reuse,
         }
+    }
 
-        if number + sorted[candidate_index] == target {
-            return Some(number * sorted[candidate_index]);
+    // How many more times the value can go in the combination
+    fn available(&self, index: usize, combination: &[usize]) -> usize {
+        if self.reuse {
+            return usize::MAX;
         }
+        let used = combination.iter().filter(|used| **used == index).count();
+        self.counts[index] - used
     }
 
-    None
-}
+    // Every combination of count entries summing to target, sorted, with the entries of each in
+    // increasing order
+    fn find(&self, count: usize, target: i64) -> Vec<Vec<i64>> {
+        let mut found = if count < MEET_IN_THE_MIDDLE_COUNT {
+            let mut found = Vec::new();
+            self.search(count, 0, target, &mut Vec::new(), &mut found);
+            found
+        } else {
+            self.meet_in_the_middle(count, target)
+        };
+
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|combination| {
+                combination
+                    .iter()
+                    .map(|index| self.values[*index])
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Fixes entries one at a time from start onwards until only a pair is left, which is found
+    // by walking in from both ends of the sorted values
+    fn search(
+        &self,
+        count: usize,
+        start: usize,
+        target: i64,
+        combination: &mut Vec<usize>,
+        found: &mut Vec<Vec<usize>>,
+    ) {
+        let mut push = |extra: &[usize]| {
+            let mut complete = combination.clone();
+            complete.extend_from_slice(extra);
+            found.push(complete);
+        };
 
-fn sum_product3(sorted: &[i32], target: i32) -> Option<i32> {
-    let mut end = sorted.len() - 1;
-    for number in sorted {
-        while number + sorted[end] > target {
-            end -= 1;
+        match count {
+            0 => {
+                if target == 0 {
+                    push(&[]);
+                }
+            }
+            1 => {
+                if let Ok(offset) = self.values[start..].binary_search(&target) {
+                    if self.available(start + offset, combination) > 0 {
+                        push(&[start + offset]);
+                    }
+                }
+            }
+            2 => {
+                if self.values.len() <= start {
+                    return;
+                }
+                let (mut low, mut high) = (start, self.values.len() - 1);
+                while low <= high {
+                    let sum = self.values[low] + self.values[high];
+                    let needed = if low == high { 2 } else { 1 };
+                    if sum == target && self.available(low, combination) >= needed {
+                        push(&[low, high]);
+                    }
+                    // Values are distinct, so a match can't be repeated by moving just one end
+                    if sum <= target {
+                        low += 1;
+                    } else if high == low {
+                        break;
+                    } else {
+                        high -= 1;
+                    }
+                }
+            }
+            _ => {
+                for index in start..self.values.len() {
+                    if self.available(index, combination) > 0 {
+                        combination.push(index);
+                        self.search(
+                            count - 1,
+                            index,
+                            target - self.values[index],
+                            combination,
+                            found,
+                        );
+                        combination.pop();
+                    }
+                }
+            }
         }
+    }
 
-        if let Some(product2) = sum_product2(&sorted[0..end], target - number) {
-            return Some(product2 * number);
+    // Every valid combination of the given size, as non-decreasing value indices
+    fn combinations(&self, size: usize) -> Vec<Vec<usize>> {
+        let mut combinations = vec![Vec::new()];
+        for _ in 0..size {
+            let mut longer = Vec::new();
+            for combination in &combinations {
+                let start = combination.last().copied().unwrap_or(0);
+                for index in start..self.values.len() {
+                    if self.available(index, combination) > 0 {
+                        let mut extended = combination.clone();
+                        extended.push(index);
+                        longer.push(extended);
+                    }
+                }
+            }
+            combinations = longer;
         }
+        combinations
     }
 
-    None
+    // Splits each combination into a lower half and an upper half, looking up the upper halves by
+    // sum. The halves can only share the value where they meet, so that's the only one whose
+    // count needs checking.
+    fn meet_in_the_middle(&self, count: usize, target: i64) -> Vec<Vec<usize>> {
+        let lower_size = count / 2;
+
+        let mut upper_by_sum: HashMap<i64, Vec<Vec<usize>>> = HashMap::new();
+        for upper in self.combinations(count - lower_size) {
+            let sum = upper.iter().map(|index| self.values[*index]).sum();
+            upper_by_sum.entry(sum).or_default().push(upper);
+        }
+
+        let mut found = Vec::new();
+        for lower in self.combinations(lower_size) {
+            let sum: i64 = lower.iter().map(|index| self.values[*index]).sum();
+            let Some(uppers) = upper_by_sum.get(&(target - sum)) else {
+                continue;
+            };
+            let meeting = *lower.last().expect("Failed to find lower half");
+            for upper in uppers {
+                if upper[0] < meeting {
+                    continue;
+                }
+                let shared = upper.iter().take_while(|index| **index == meeting).count();
+                if shared == 0 || self.available(meeting, &lower) >= shared {
+                    let mut combination = lower.clone();
+                    combination.extend_from_slice(upper);
+                    found.push(combination);
+                }
+            }
+        }
+        found
+    }
+}
+
+fn product(combination: &[i64]) -> i64 {
+    combination
+        .iter()
+        .try_fold(1_i64, |product, entry| product.checked_mul(*entry))
+        .expect("Failed to fit product in i64")
 }
 
-fn read_array(filename: &str) -> Vec<i32> {
+fn read_array(filename: Option<&str>) -> Vec<i32> {
     let mut reader = LineReader::new(filename);
     let mut array = Vec::<i32>::new();
     reader.read_with(|line| {
         array.push(
             line.parse()
                 .unwrap_or_else(|_| panic!("Failed to parse {}", line)),
-        )
+        );
     });
 
     array
 }
 
 fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<FILE>"))
-        .arg(
-            Arg::from_usage("-n, --entries <ENTRIES> 'Number of entries to consider'")
-                .possible_value("2")
-                .possible_value("3"),
-        )
-        .get_matches();
-
-    let mut array = read_array(args.value_of("FILE").unwrap());
-    array.sort_unstable();
-    let result = match args.value_of("entries").unwrap() {
-        "2" => sum_product2(&array, 2020),
-        "3" => sum_product3(&array, 2020),
-        _ => unreachable!("Impossible argument value"),
-    };
-
-    println!("Result: {}", result.expect("Failed to find sum product"));
+    let args = Args::parse();
+
+    let count = args.count.unwrap_or(usize::from(args.part) + 1);
+
+    let array = read_array(args.filename.as_deref());
+    let combinations = KSum::new(&array, args.reuse).find(count, args.target);
+
+    if args.all {
+        for combination in &combinations {
+            let entries: Vec<_> = combination.iter().map(ToString::to_string).collect();
+            println!(
+                "{} = {} (product {})",
+                entries.join(" + "),
+                args.target,
+                product(combination)
+            );
+        }
+        return;
+    }
+
+    let first = combinations.first().expect("Failed to find sum product");
+    println!("Result: {}", product(first));
 }
 
 #[cfg(test)]
 mod tests {
     use test::Bencher;
 
+    use super::{product, KSum};
+
+    const EXAMPLE: [i32; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    #[test]
+    fn example() {
+        let k_sum = KSum::new(&EXAMPLE, false);
+        assert_eq!(k_sum.find(2, 2020), [[299, 1721]]);
+        assert_eq!(product(&k_sum.find(2, 2020)[0]), 514_579);
+        assert_eq!(k_sum.find(3, 2020), [[366, 675, 979]]);
+        assert_eq!(product(&k_sum.find(3, 2020)[0]), 241_861_950);
+    }
+
+    #[test]
+    fn repeated_entries() {
+        // A single 1010 can't pair with itself unless reuse is allowed
+        assert!(KSum::new(&[1010, 5], false).find(2, 2020).is_empty());
+        assert_eq!(KSum::new(&[1010, 5], true).find(2, 2020), [[1010, 1010]]);
+
+        // Two equal entries make one combination, not two
+        let k_sum = KSum::new(&[1, 1, 2, 3, 3], false);
+        assert_eq!(k_sum.find(2, 4), [[1, 3]]);
+        // Only one 2, so 1 + 2 + 2 doesn't count
+        assert_eq!(k_sum.find(3, 5), [[1, 1, 3]]);
+    }
+
+    // Checks every combination of count entries, brute force
+    fn brute_force(entries: &[i32], count: usize, target: i64, reuse: bool) -> Vec<Vec<i64>> {
+        fn extend(
+            entries: &[i32],
+            start: usize,
+            count: usize,
+            target: i64,
+            reuse: bool,
+            combination: &mut Vec<i64>,
+            found: &mut Vec<Vec<i64>>,
+        ) {
+            if count == 0 {
+                if target == 0 {
+                    found.push(combination.clone());
+                }
+                return;
+            }
+            for index in start..entries.len() {
+                let entry = i64::from(entries[index]);
+                combination.push(entry);
+                let next = if reuse { index } else { index + 1 };
+                extend(
+                    entries,
+                    next,
+                    count - 1,
+                    target - entry,
+                    reuse,
+                    combination,
+                    found,
+                );
+                combination.pop();
+            }
+        }
+
+        let mut sorted = entries.to_vec();
+        sorted.sort_unstable();
+        let mut found = Vec::new();
+        extend(
+            &sorted,
+            0,
+            count,
+            target,
+            reuse,
+            &mut Vec::new(),
+            &mut found,
+        );
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    #[test]
+    fn meet_in_the_middle() {
+        let entries = [-4, -1, 0, 2, 2, 3, 5, 5, 5, 7, 8, 11];
+        for reuse in [false, true] {
+            let k_sum = KSum::new(&entries, reuse);
+            for count in 1..=6 {
+                for target in [-3, 0, 10, 17] {
+                    assert_eq!(
+                        k_sum.find(count, target),
+                        brute_force(&entries, count, target, reuse),
+                        "count {count}, target {target}, reuse {reuse}"
+                    );
+                }
+            }
+        }
+    }
+
     #[bench]
     fn sum_product2(bencher: &mut Bencher) {
-        let array = super::read_array("input.txt");
+        let array = super::read_array(Some("input.txt"));
         bencher.iter(|| {
-            let mut array = array.clone();
-            array.sort_unstable();
-            assert_eq!(super::sum_product2(&array, 2020).unwrap(), 1019904);
-        })
+            let combinations = KSum::new(&array, false).find(2, 2020);
+            assert_eq!(product(&combinations[0]), 1_019_904);
+        });
     }
 
     #[bench]
     fn sum_product3(bencher: &mut Bencher) {
-        let array = super::read_array("input.txt");
+        let array = super::read_array(Some("input.txt"));
         bencher.iter(|| {
-            let mut array = array.clone();
-            array.sort_unstable();
-            assert_eq!(super::sum_product3(&array, 2020).unwrap(), 176647680);
-        })
+            let combinations = KSum::new(&array, false).find(3, 2020);
+            assert_eq!(product(&combinations[0]), 176_647_680);
+        });
     }
 }
@@ -1,52 +1,56 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-#[macro_use]
-extern crate lazy_static;
+use std::{collections::HashSet, io::BufRead, sync::LazyLock};
 
+use clap::{builder::PossibleValuesParser, Parser};
+use common::open_input;
 use regex::{Captures, Regex};
 
-lazy_static! {
-    static ref PARSE_LINE: Regex =
-        Regex::new(r"(\d+)-(\d+) (.): (.*)").expect("Failed to compile regular expression");
-}
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
 
-#[derive(Clone, Copy)]
-enum PolicyType {
-    Range,
-    Position,
-}
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
 
-trait Policy {
-    fn allows(&self, password: &str) -> bool;
+    /// Policy to check passwords against, instead of the one for the selected part
+    #[arg(long, value_parser = policy_names())]
+    policy: Option<String>,
+
+    /// Print which policies accept and reject each password
+    #[arg(long)]
+    report: bool,
 }
 
-struct PositionPolicy {
-    first: usize,
-    second: usize,
+static PARSE_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d+)-(\d+) (.): (.*)").expect("Failed to compile regular expression")
+});
+
+// The two numbers and the character before each password, which each policy interprets in its
+// own way
+struct Rule {
+    low: usize,
+    high: usize,
     character: u8,
 }
 
-impl PositionPolicy {
+impl Rule {
     fn new(captures: &Captures) -> Self {
         Self {
-            first: captures
+            low: captures
                 .get(1)
-                .expect("Failed to parse first")
+                .expect("Failed to parse low")
                 .as_str()
                 .parse::<usize>()
-                .expect("Failed to parse first as usize"),
-            second: captures
+                .expect("Failed to parse low as usize"),
+            high: captures
                 .get(2)
-                .expect("Failed to parse second")
+                .expect("Failed to parse high")
                 .as_str()
                 .parse::<usize>()
-                .expect("Failed to parse second as usize"),
+                .expect("Failed to parse high as usize"),
             character: captures
                 .get(3)
                 .expect("Failed to parse character")
@@ -56,45 +60,60 @@ impl PositionPolicy {
     }
 }
 
+trait Policy {
+    fn new(rule: &Rule) -> Self
+    where
+        Self: Sized;
+
+    fn allows(&self, password: &str) -> bool;
+}
+
+// Exactly one of the two 1-based positions holds the character
+struct PositionPolicy {
+    first: usize,
+    second: usize,
+    character: u8,
+}
+
+impl PositionPolicy {
+    fn matches(&self, password: &str, position: usize) -> bool {
+        position
+            .checked_sub(1)
+            .and_then(|index| password.as_bytes().get(index))
+            == Some(&self.character)
+    }
+}
+
 impl Policy for PositionPolicy {
+    fn new(rule: &Rule) -> Self {
+        Self {
+            first: rule.low,
+            second: rule.high,
+            character: rule.character,
+        }
+    }
+
     fn allows(&self, password: &str) -> bool {
-        let first_matches = password.as_bytes()[self.first - 1] == self.character;
-        let second_matches = password.as_bytes()[self.second - 1] == self.character;
-        first_matches ^ second_matches
+        self.matches(password, self.first) ^ self.matches(password, self.second)
     }
 }
 
+// The character appears between min and max times
 struct RangePolicy {
     min: usize,
     max: usize,
     character: u8,
 }
 
-impl RangePolicy {
-    fn new(captures: &Captures) -> Self {
+impl Policy for RangePolicy {
+    fn new(rule: &Rule) -> Self {
         Self {
-            min: captures
-                .get(1)
-                .expect("Failed to parse min")
-                .as_str()
-                .parse::<usize>()
-                .expect("Failed to parse min as usize"),
-            max: captures
-                .get(2)
-                .expect("Failed to parse max")
-                .as_str()
-                .parse::<usize>()
-                .expect("Failed to parse max as usize"),
-            character: captures
-                .get(3)
-                .expect("Failed to parse character")
-                .as_str()
-                .as_bytes()[0],
+            min: rule.low,
+            max: rule.high,
+            character: rule.character,
         }
     }
-}
 
-impl Policy for RangePolicy {
     fn allows(&self, password: &str) -> bool {
         let mut count = 0_usize;
         for c in password.as_bytes() {
@@ -109,39 +128,129 @@ impl Policy for RangePolicy {
     }
 }
 
-fn password_is_valid(line: &str, policy_type: PolicyType) -> bool {
-    let captures = PARSE_LINE
-        .captures(&line)
-        .unwrap_or_else(|| panic!("Failed to match [{}]", line));
+// The password uses between min and max different characters, whatever the rule's character is
+struct DistinctPolicy {
+    min: usize,
+    max: usize,
+}
 
-    let policy = {
-        match policy_type {
-            PolicyType::Position => Box::new(PositionPolicy::new(&captures)) as Box<dyn Policy>,
-            PolicyType::Range => Box::new(RangePolicy::new(&captures)) as Box<dyn Policy>,
+impl Policy for DistinctPolicy {
+    fn new(rule: &Rule) -> Self {
+        Self {
+            min: rule.low,
+            max: rule.high,
         }
-    };
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        let distinct = password.bytes().collect::<HashSet<_>>().len();
+        (self.min..=self.max).contains(&distinct)
+    }
+}
+
+// No character appears more than max times in a row, whatever the rule's character is. The
+// rule's lower number isn't used.
+struct RunPolicy {
+    max: usize,
+}
+
+impl Policy for RunPolicy {
+    fn new(rule: &Rule) -> Self {
+        Self { max: rule.high }
+    }
+
+    fn allows(&self, password: &str) -> bool {
+        let mut run = 0_usize;
+        let mut previous = None;
+        for c in password.bytes() {
+            run = if previous == Some(c) { run + 1 } else { 1 };
+            if run > self.max {
+                return false;
+            }
+            previous = Some(c);
+        }
+        true
+    }
+}
+
+fn build<P: Policy + 'static>(rule: &Rule) -> Box<dyn Policy> {
+    Box::new(P::new(rule))
+}
+
+struct Registration {
+    name: &'static str,
+    build: fn(&Rule) -> Box<dyn Policy>,
+}
+
+// Every policy that can be selected by name. New policies only need an entry here.
+static POLICIES: [Registration; 4] = [
+    Registration {
+        name: "range",
+        build: build::<RangePolicy>,
+    },
+    Registration {
+        name: "position",
+        build: build::<PositionPolicy>,
+    },
+    Registration {
+        name: "distinct",
+        build: build::<DistinctPolicy>,
+    },
+    Registration {
+        name: "run",
+        build: build::<RunPolicy>,
+    },
+];
+
+fn policy_names() -> PossibleValuesParser {
+    PossibleValuesParser::new(POLICIES.iter().map(|policy| policy.name))
+}
+
+fn find_policy(name: &str) -> &'static Registration {
+    POLICIES
+        .iter()
+        .find(|policy| policy.name == name)
+        .unwrap_or_else(|| panic!("Unknown policy [{}]", name))
+}
+
+fn parse_line(line: &str) -> (Rule, &str) {
+    let captures = PARSE_LINE
+        .captures(line)
+        .unwrap_or_else(|| panic!("Failed to match [{}]", line));
     let password = captures.get(4).expect("Failed to parse password").as_str();
+    (Rule::new(&captures), password)
+}
+
+fn password_is_valid(line: &str, policy: &Registration) -> bool {
+    let (rule, password) = parse_line(line);
+    (policy.build)(&rule).allows(password)
+}
 
-    policy.allows(password)
+// Names of the policies accepting and rejecting the password on the line, in registry order
+fn judge(line: &str) -> (Vec<&'static str>, Vec<&'static str>) {
+    let (rule, password) = parse_line(line);
+    let (accepted, rejected): (Vec<_>, Vec<_>) = POLICIES
+        .iter()
+        .partition(|policy| (policy.build)(&rule).allows(password));
+    (
+        accepted.iter().map(|policy| policy.name).collect(),
+        rejected.iter().map(|policy| policy.name).collect(),
+    )
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        return;
-    }
+    let args = Args::parse();
 
-    let policy_type = match args[2].as_str() {
-        "position" => PolicyType::Position,
-        "range" => PolicyType::Range,
-        _ => panic!("Unexpected policy type {}", args[2].as_str()),
-    };
+    let policy = find_policy(args.policy.as_deref().unwrap_or(if args.part == 1 {
+        "range"
+    } else {
+        "position"
+    }));
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut line = String::new();
+    let mut line_number = 0;
     let mut valid_password_count = 0;
     loop {
         let bytes = reader
@@ -150,13 +259,83 @@ fn main() {
         if bytes == 0 {
             break;
         }
+        line_number += 1;
+
+        if line.trim().is_empty() {
+            line.clear();
+            continue;
+        }
 
-        if password_is_valid(&line, policy_type) {
+        if args.report {
+            let (accepted, rejected) = judge(line.trim_end());
+            println!(
+                "Line {}: accepted by [{}], rejected by [{}]",
+                line_number,
+                accepted.join(", "),
+                rejected.join(", ")
+            );
+        }
+
+        if password_is_valid(&line, policy) {
             valid_password_count += 1;
         }
 
         line.clear();
     }
 
-    println!("{} valid passwords", valid_password_count);
+    println!("{valid_password_count} valid passwords");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_policy, judge, password_is_valid};
+
+    const EXAMPLE: [&str; 3] = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+
+    fn count_valid(lines: &[&str], name: &str) -> usize {
+        let policy = find_policy(name);
+        lines
+            .iter()
+            .filter(|line| password_is_valid(line, policy))
+            .count()
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(count_valid(&EXAMPLE, "range"), 2);
+        assert_eq!(count_valid(&EXAMPLE, "position"), 1);
+    }
+
+    #[test]
+    fn positions_past_end() {
+        assert_eq!(
+            count_valid(&["2-9 a: ab", "1-9 a: ab", "0-1 a: ab"], "position"),
+            2
+        );
+    }
+
+    #[test]
+    fn extra_policies() {
+        let lines = [
+            "2-3 x: aab",
+            "2-3 x: abcd",
+            "1-2 x: axxxb",
+            "1-3 x: axxxb",
+            "1-2 x: aaab",
+        ];
+        assert_eq!(count_valid(&lines, "distinct"), 3);
+        assert_eq!(count_valid(&lines, "run"), 3);
+    }
+
+    #[test]
+    fn report() {
+        assert_eq!(
+            judge(EXAMPLE[0]),
+            (vec!["range", "position", "run"], vec!["distinct"])
+        );
+        assert_eq!(
+            judge(EXAMPLE[2]),
+            (vec!["range", "run"], vec!["position", "distinct"])
+        );
+    }
 }
@@ -1,10 +1,19 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 struct PathFollower {
     right: usize,
@@ -26,7 +35,7 @@ impl PathFollower {
     }
 
     fn add_line(&mut self, line: &[u8]) {
-        if self.row % self.down != 0 {
+        if !self.row.is_multiple_of(self.down) {
             self.row += 1;
             return;
         }
@@ -45,21 +54,21 @@ impl PathFollower {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut followers = Vec::new();
-    followers.push(PathFollower::new(1, 1));
-    followers.push(PathFollower::new(3, 1));
-    followers.push(PathFollower::new(5, 1));
-    followers.push(PathFollower::new(7, 1));
-    followers.push(PathFollower::new(1, 2));
+    let mut followers = if args.part == 1 {
+        vec![PathFollower::new(3, 1)]
+    } else {
+        vec![
+            PathFollower::new(1, 1),
+            PathFollower::new(3, 1),
+            PathFollower::new(5, 1),
+            PathFollower::new(7, 1),
+            PathFollower::new(1, 2),
+        ]
+    };
 
     let mut line = String::new();
     loop {
@@ -1,203 +1,469 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-#[macro_use]
-extern crate bitflags;
-
-bitflags! {
-    struct Fields: u8 {
-        const BIRTH_YEAR = 1_u8 << 0;
-        const ISSUE_YEAR = 1_u8 << 1;
-        const EXPIRATION_YEAR = 1_u8 << 2;
-        const HEIGHT = 1_u8 << 3;
-        const HAIR_COLOR = 1_u8 << 4;
-        const EYE_COLOR = 1_u8 << 5;
-        const PASSPORT_ID = 1_u8 << 6;
-        const REQUIRED = 0b0111_1111;
-    }
+use std::{collections::HashSet, fmt, fs, ops::RangeInclusive, str::FromStr};
+
+use clap::Parser;
+use common::LineReader;
+use regex::Regex;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Schema file to validate records against instead of the passport schema
+    #[arg(long)]
+    schema: Option<String>,
+
+    /// Explain which fields of each invalid record failed and why
+    #[arg(long)]
+    report: bool,
 }
 
-struct PassportParser {
-    validate_values: bool,
-    fields: Fields,
+const PASSPORT_SCHEMA: &str = include_str!("../passport.schema");
+
+fn parse_range(range: &str) -> Result<RangeInclusive<i64>, String> {
+    let (min, max) = range
+        .split_once("..=")
+        .ok_or_else(|| format!("Expected MIN..=MAX but found [{range}]"))?;
+    let parse = |bound: &str| {
+        bound
+            .parse::<i64>()
+            .map_err(|_| format!("Failed to parse [{bound}] as i64"))
+    };
+    Ok(parse(min)?..=parse(max)?)
 }
 
-fn number_is_valid(value: &str, min: i32, max: i32) -> bool {
-    match value.parse::<i32>() {
-        Ok(number) => number >= min && number <= max,
-        Err(_) => false,
-    }
+fn describe_range(range: &RangeInclusive<i64>) -> String {
+    format!("{} to {}", range.start(), range.end())
 }
 
-impl PassportParser {
-    fn new(validate_values: bool) -> Self {
-        Self {
-            validate_values,
-            fields: Fields::empty(),
-        }
-    }
+enum Rule {
+    Any,
+    Range(RangeInclusive<i64>),
+    Units(Vec<(String, RangeInclusive<i64>)>),
+    // The compiled pattern, and the pattern as written in the schema
+    Pattern(Regex, String),
+    OneOf(Vec<String>),
+}
 
-    fn birth_year_if_valid(&self, value: &str) -> Fields {
-        if !self.validate_values || number_is_valid(value, 1920, 2002) {
-            Fields::BIRTH_YEAR
-        } else {
-            Fields::empty()
+impl Rule {
+    fn parse(kind: &str, arguments: &str) -> Result<Self, String> {
+        match kind {
+            "range" => Ok(Self::Range(parse_range(arguments)?)),
+            "units" => {
+                let units = arguments
+                    .split_whitespace()
+                    .map(|unit| {
+                        let (name, range) = unit
+                            .split_once(':')
+                            .ok_or_else(|| format!("Expected UNIT:MIN..=MAX but found [{unit}]"))?;
+                        Ok((name.to_string(), parse_range(range)?))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                if units.is_empty() {
+                    return Err("Expected at least one unit".to_string());
+                }
+                Ok(Self::Units(units))
+            }
+            // Patterns always have to match the whole value
+            "regex" => Regex::new(&format!("^(?:{arguments})$"))
+                .map(|regex| Self::Pattern(regex, arguments.to_string()))
+                .map_err(|error| format!("Failed to compile [{arguments}]: {error}")),
+            "oneof" => {
+                let values: Vec<_> = arguments.split_whitespace().map(String::from).collect();
+                if values.is_empty() {
+                    return Err("Expected at least one value".to_string());
+                }
+                Ok(Self::OneOf(values))
+            }
+            _ => Err(format!("Unexpected rule [{kind}]")),
         }
     }
 
-    fn issue_year_if_valid(&self, value: &str) -> Fields {
-        if !self.validate_values || number_is_valid(value, 2010, 2020) {
-            Fields::ISSUE_YEAR
-        } else {
-            Fields::empty()
+    // Returns why the value breaks the rule, if it does
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Self::Any => Ok(()),
+            Self::Range(range) => match value.parse::<i64>() {
+                Ok(number) if range.contains(&number) => Ok(()),
+                Ok(_) => Err(format!("is not in the range {}", describe_range(range))),
+                Err(_) => Err("is not a number".to_string()),
+            },
+            Self::Units(units) => {
+                let (unit, range) = units
+                    .iter()
+                    .find(|(unit, _)| value.ends_with(unit.as_str()))
+                    .ok_or_else(|| {
+                        let names: Vec<_> = units.iter().map(|(unit, _)| unit.as_str()).collect();
+                        format!("is missing a unit (expected one of {})", names.join(", "))
+                    })?;
+                match value[..value.len() - unit.len()].parse::<i64>() {
+                    Ok(number) if range.contains(&number) => Ok(()),
+                    Ok(_) => Err(format!(
+                        "is not in the range {} for {}",
+                        describe_range(range),
+                        unit
+                    )),
+                    Err(_) => Err(format!("is not a number of {unit}")),
+                }
+            }
+            Self::Pattern(regex, pattern) => {
+                if regex.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(format!("does not match {pattern}"))
+                }
+            }
+            Self::OneOf(values) => {
+                if values.iter().any(|allowed| allowed == value) {
+                    Ok(())
+                } else {
+                    Err(format!("is not one of {}", values.join(", ")))
+                }
+            }
         }
     }
+}
 
-    fn expiration_year_if_valid(&self, value: &str) -> Fields {
-        if !self.validate_values || number_is_valid(value, 2020, 2030) {
-            Fields::EXPIRATION_YEAR
-        } else {
-            Fields::empty()
-        }
-    }
+struct FieldSpec {
+    name: String,
+    required: bool,
+    rule: Rule,
+}
+
+impl FromStr for FieldSpec {
+    type Err = String;
 
-    fn height_if_valid(&self, value: &str) -> Fields {
-        let bytes = value.as_bytes();
-        if !self.validate_values
-            || match &bytes[bytes.len() - 2..] {
-                b"cm" => number_is_valid(
-                    value.strip_suffix("cm").expect("Failed to strip cm suffix"),
-                    150,
-                    193,
-                ),
-                b"in" => number_is_valid(
-                    value.strip_suffix("in").expect("Failed to strip in suffix"),
-                    59,
-                    76,
-                ),
-                _ => false,
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut split = line.splitn(4, ' ');
+        let name = split.next().expect("Failed to get field name").to_string();
+        let required = match split.next() {
+            Some("required") => true,
+            Some("optional") => false,
+            other => {
+                return Err(format!(
+                    "Expected required or optional for field {name} but found {other:?}"
+                ))
             }
-        {
-            Fields::HEIGHT
-        } else {
-            Fields::empty()
-        }
+        };
+        let rule = match split.next() {
+            Some(kind) => Rule::parse(kind, split.next().unwrap_or("").trim())
+                .map_err(|error| format!("Field {name}: {error}"))?,
+            None => Rule::Any,
+        };
+        Ok(Self {
+            name,
+            required,
+            rule,
+        })
     }
+}
 
-    fn hair_color_if_valid(&self, value: &str) -> Fields {
-        let bytes = value.as_bytes();
-        if !self.validate_values
-            || bytes.len() == 7
-                && bytes[0] == b'#'
-                && bytes[1..]
-                    .iter()
-                    .all(|c| *c >= b'0' && *c <= b'9' || *c >= b'a' && *c <= b'f')
-        {
-            Fields::HAIR_COLOR
-        } else {
-            Fields::empty()
+#[derive(Debug, PartialEq)]
+enum Problem {
+    Missing(String),
+    Duplicate(String),
+    Unexpected(String),
+    Invalid {
+        field: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "{field}: missing"),
+            Self::Duplicate(field) => write!(f, "{field}: appears more than once"),
+            Self::Unexpected(field) => write!(f, "{field}: not in the schema"),
+            Self::Invalid {
+                field,
+                value,
+                reason,
+            } => write!(f, "{field}: [{value}] {reason}"),
         }
     }
+}
 
-    fn eye_color_if_valid(&self, value: &str) -> Fields {
-        if !self.validate_values {
-            return Fields::EYE_COLOR;
-        }
+// Records are whitespace-separated name:value pairs, which may span several lines
+struct Schema {
+    fields: Vec<FieldSpec>,
+}
 
-        match value {
-            "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => Fields::EYE_COLOR,
-            _ => Fields::empty(),
-        }
+impl Schema {
+    fn passport() -> Self {
+        PASSPORT_SCHEMA
+            .parse()
+            .expect("Failed to parse passport schema")
     }
 
-    fn passport_id_if_valid(&self, value: &str) -> Fields {
-        let bytes = value.as_bytes();
-        if !self.validate_values
-            || bytes.len() == 9 && bytes.iter().all(|b| *b >= b'0' && *b <= b'9')
-        {
-            Fields::PASSPORT_ID
-        } else {
-            Fields::empty()
+    // Lists everything wrong with the record, only checking for presence unless check_values
+    fn validate(&self, record: &[(String, String)], check_values: bool) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut seen = HashSet::new();
+        for (name, value) in record {
+            if !seen.insert(name.as_str()) {
+                problems.push(Problem::Duplicate(name.clone()));
+                continue;
+            }
+
+            match self.fields.iter().find(|spec| spec.name == *name) {
+                None => problems.push(Problem::Unexpected(name.clone())),
+                Some(spec) if check_values => {
+                    if let Err(reason) = spec.rule.check(value) {
+                        problems.push(Problem::Invalid {
+                            field: name.clone(),
+                            value: value.clone(),
+                            reason,
+                        });
+                    }
+                }
+                Some(_) => (),
+            }
         }
-    }
 
-    fn parse_fields(&self, line: &str) -> Fields {
-        let mut fields = Fields::empty();
-        for token in line.trim().split_ascii_whitespace() {
-            let split: Vec<&str> = token.split(':').collect();
-            assert!(
-                split.len() == 2,
-                "Expected two fields when splitting [{}]", token
-            );
-
-            fields |= match *split.get(0).expect("Failed to get field name from split") {
-                "byr" => self.birth_year_if_valid(split[1]),
-                "iyr" => self.issue_year_if_valid(split[1]),
-                "eyr" => self.expiration_year_if_valid(split[1]),
-                "hgt" => self.height_if_valid(split[1]),
-                "hcl" => self.hair_color_if_valid(split[1]),
-                "ecl" => self.eye_color_if_valid(split[1]),
-                "pid" => self.passport_id_if_valid(split[1]),
-                "cid" => Fields::empty(),
-                _ => panic!("Unexpected field {}", split[0]),
+        for spec in &self.fields {
+            if spec.required && !seen.contains(spec.name.as_str()) {
+                problems.push(Problem::Missing(spec.name.clone()));
             }
         }
 
-        fields
+        problems
     }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(schema: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<FieldSpec> = Vec::new();
+        for line in schema.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
 
-    fn add_line(&mut self, line: &str) -> Option<Fields> {
-        if line.trim().is_empty() {
-            let result = Some(self.fields);
-            self.fields = Fields::empty();
-            return result;
+            let spec: FieldSpec = line.parse()?;
+            if fields.iter().any(|other| other.name == spec.name) {
+                return Err(format!("Field {} appears twice", spec.name));
+            }
+            fields.push(spec);
         }
+        Ok(Self { fields })
+    }
+}
 
-        self.fields |= self.parse_fields(line);
-        None
+fn parse_pairs(line: &str, record: &mut Vec<(String, String)>) {
+    for token in line.split_ascii_whitespace() {
+        let (name, value) = token
+            .split_once(':')
+            .unwrap_or_else(|| panic!("Expected name:value but found [{}]", token));
+        record.push((name.to_string(), value.to_string()));
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 || args.len() > 3 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let schema = match &args.schema {
+        Some(filename) => fs::read_to_string(filename)
+            .unwrap_or_else(|_| panic!("Failed to read schema {}", filename))
+            .parse()
+            .unwrap_or_else(|error| panic!("Failed to parse schema {}: {}", filename, error)),
+        None => Schema::passport(),
+    };
 
-    let validate_values = args.len() == 3 && args[2] == "validate";
-    let mut parser = PassportParser::new(validate_values);
-    let mut valid_passports = 0_usize;
+    let mut reader = LineReader::new(args.filename.as_deref());
+
+    let check_values = args.part == 2;
+    let mut records = 0_usize;
+    let mut valid_records = 0_usize;
 
-    let mut line = String::new();
     loop {
-        let bytes = reader
-            .read_line(&mut line)
-            .unwrap_or_else(|_| panic!("Failed to read line"));
-        if bytes == 0 {
-            break;
-        }
+        let mut record = Vec::new();
+        let more = reader.read_with(|line| parse_pairs(line, &mut record));
 
-        if let Some(fields) = parser.add_line(&line) {
-            if fields == Fields::REQUIRED {
-                valid_passports += 1;
+        if !record.is_empty() {
+            records += 1;
+            let problems = schema.validate(&record, check_values);
+            if problems.is_empty() {
+                valid_records += 1;
+            } else if args.report {
+                println!("Record {records} is invalid:");
+                for problem in problems {
+                    println!("  {problem}");
+                }
             }
         }
 
-        line.clear();
+        if !more {
+            break;
+        }
+    }
+
+    if args.schema.is_some() {
+        println!("Valid records: {valid_records}");
+    } else {
+        println!("Valid passports: {valid_records}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pairs, Problem, Schema};
+
+    const EXAMPLE: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in";
+
+    const INVALID: &str = "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007";
+
+    const VALID: &str = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+
+    fn validate_all(schema: &Schema, input: &str, check_values: bool) -> Vec<Vec<Problem>> {
+        input
+            .split("\n\n")
+            .map(|block| {
+                let mut record = Vec::new();
+                for line in block.lines() {
+                    parse_pairs(line, &mut record);
+                }
+                schema.validate(&record, check_values)
+            })
+            .collect()
     }
 
-    if parser.add_line("").expect("Failed to find last record") == Fields::REQUIRED {
-        valid_passports += 1;
+    fn count_valid(schema: &Schema, input: &str, check_values: bool) -> usize {
+        validate_all(schema, input, check_values)
+            .iter()
+            .filter(|problems| problems.is_empty())
+            .count()
     }
 
-    println!("Valid passports: {}", valid_passports);
+    #[test]
+    fn required_fields() {
+        let schema = Schema::passport();
+        let problems = validate_all(&schema, EXAMPLE, false);
+        assert_eq!(problems[1], [Problem::Missing("hgt".to_string())]);
+        assert_eq!(count_valid(&schema, EXAMPLE, false), 2);
+    }
+
+    #[test]
+    fn field_values() {
+        let schema = Schema::passport();
+        assert_eq!(count_valid(&schema, INVALID, true), 0);
+        assert_eq!(count_valid(&schema, VALID, true), 4);
+    }
+
+    #[test]
+    fn report() {
+        let schema = Schema::passport();
+        let reasons: Vec<_> = validate_all(&schema, INVALID, true)[0]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                "eyr: [1972] is not in the range 2020 to 2030",
+                "hgt: [170] is missing a unit (expected one of cm, in)",
+                "pid: [186cm] does not match [0-9]{9}",
+            ]
+        );
+
+        let reasons: Vec<_> = validate_all(&schema, "ecl:zzz hgt:59cm byr:1990 byr:x", true)[0]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                "ecl: [zzz] is not one of amb, blu, brn, gry, grn, hzl, oth",
+                "hgt: [59cm] is not in the range 150 to 193 for cm",
+                "byr: appears more than once",
+                "iyr: missing",
+                "eyr: missing",
+                "hcl: missing",
+                "pid: missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_schema() {
+        let schema: Schema = "# Temperature readings
+sensor required regex [A-Z]+-[0-9]+
+temp required units C:-40..=60 F:-40..=140
+note optional"
+            .parse()
+            .unwrap();
+        let problems = validate_all(
+            &schema,
+            "sensor:AB-12 temp:-5C\n\nsensor:ab temp:200F\n\ntemp:20K extra:1",
+            true,
+        );
+        assert!(problems[0].is_empty());
+        assert_eq!(problems[1].len(), 2);
+        assert_eq!(
+            problems[2],
+            [
+                Problem::Invalid {
+                    field: "temp".to_string(),
+                    value: "20K".to_string(),
+                    reason: "is missing a unit (expected one of C, F)".to_string()
+                },
+                Problem::Unexpected("extra".to_string()),
+                Problem::Missing("sensor".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        assert!("byr".parse::<Schema>().is_err());
+        assert!("byr sometimes".parse::<Schema>().is_err());
+        assert!("byr required range 1..2".parse::<Schema>().is_err());
+        assert!("byr required between 1..=2".parse::<Schema>().is_err());
+        assert!("hcl required regex (".parse::<Schema>().is_err());
+        assert!("ecl required oneof".parse::<Schema>().is_err());
+        assert!("byr required\nbyr optional".parse::<Schema>().is_err());
+    }
 }
@@ -1,57 +1,214 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    cmp::max,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{fmt, fmt::Write as _, io::BufRead, ops::RangeInclusive};
 
 use bit_set::BitSet;
+use clap::Parser;
+use common::open_input;
 
-fn parse_row(line: &[u8]) -> usize {
-    let mut row = 0;
-    let mut factor = 64;
-    for c in line {
-        if *c == b'B' {
-            row += factor;
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Rows on the plane, which must be a power of two
+    #[arg(long, default_value_t = 128, value_parser = parse_power_of_two)]
+    rows: usize,
+
+    /// Seats in each row, which must be a power of two
+    #[arg(long, default_value_t = 8, value_parser = parse_power_of_two)]
+    columns: usize,
+
+    /// Print a map of the occupied seats and the gaps between them
+    #[arg(long)]
+    map: bool,
+}
+
+fn parse_power_of_two(text: &str) -> Result<usize, String> {
+    let count: usize = text
+        .parse()
+        .map_err(|_| format!("Failed to parse {text} as usize"))?;
+    if count.is_power_of_two() {
+        Ok(count)
+    } else {
+        Err(format!("{count} is not a power of two"))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PassError {
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    InvalidCharacter {
+        position: usize,
+        character: char,
+        expected: [char; 2],
+    },
+}
+
+impl fmt::Display for PassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongLength { expected, found } => {
+                write!(f, "expected {expected} characters but found {found}")
+            }
+            Self::InvalidCharacter {
+                position,
+                character,
+                expected,
+            } => write!(
+                f,
+                "expected {} or {} at position {} but found '{}'",
+                expected[0], expected[1], position, character
+            ),
         }
-        factor /= 2;
     }
-    row
 }
 
-fn parse_column(line: &[u8]) -> usize {
-    let mut column = 0;
-    let mut factor = 4;
-    for c in line {
-        if *c == b'R' {
-            column += factor;
+// Rows are picked by F (front, lower half) and B (back, upper half), then seats within the row by
+// L (left, lower half) and R (right, upper half). Seat IDs are row * columns + column.
+struct Plane {
+    rows: usize,
+    columns: usize,
+}
+
+impl Plane {
+    fn new(rows: usize, columns: usize) -> Self {
+        assert!(
+            rows.is_power_of_two() && columns.is_power_of_two(),
+            "Expected power of two rows and columns, but found {} and {}",
+            rows,
+            columns
+        );
+        Self { rows, columns }
+    }
+
+    fn row_length(&self) -> usize {
+        self.rows.trailing_zeros() as usize
+    }
+
+    fn column_length(&self) -> usize {
+        self.columns.trailing_zeros() as usize
+    }
+
+    fn seat_count(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    fn decode(&self, pass: &str) -> Result<usize, PassError> {
+        let expected = self.row_length() + self.column_length();
+        let found = pass.chars().count();
+        if found != expected {
+            return Err(PassError::WrongLength { expected, found });
+        }
+
+        let mut seat = 0;
+        for (position, character) in pass.chars().enumerate() {
+            let [low, high] = if position < self.row_length() {
+                ['F', 'B']
+            } else {
+                ['L', 'R']
+            };
+            seat = seat * 2
+                + match character {
+                    _ if character == low => 0,
+                    _ if character == high => 1,
+                    _ => {
+                        return Err(PassError::InvalidCharacter {
+                            position: position + 1,
+                            character,
+                            expected: [low, high],
+                        })
+                    }
+                };
         }
-        factor /= 2;
+        Ok(seat)
+    }
+
+    // The pass for a seat, or None if the plane doesn't have that many seats
+    fn encode(&self, seat: usize) -> Option<String> {
+        if seat >= self.seat_count() {
+            return None;
+        }
+
+        let row = seat / self.columns;
+        let column = seat % self.columns;
+        let bits = |value: usize, length: usize, [low, high]: [char; 2]| {
+            (0..length)
+                .rev()
+                .map(move |bit| if value >> bit & 1 == 1 { high } else { low })
+        };
+        Some(
+            bits(row, self.row_length(), ['F', 'B'])
+                .chain(bits(column, self.column_length(), ['L', 'R']))
+                .collect(),
+        )
+    }
+
+    // One line per row, from the first row with an occupied seat to the last, with the row's
+    // number and an aisle down the middle. Occupied seats are #, empty seats between the first
+    // and last occupied seats are ., and seats beyond those are blank.
+    fn render(&self, occupied: &BitSet) -> String {
+        let (Some(first), Some(last)) = (occupied.iter().next(), occupied.iter().last()) else {
+            return String::new();
+        };
+
+        let row_width = (self.rows - 1).to_string().len();
+        let mut map = String::new();
+        for row in first / self.columns..=last / self.columns {
+            write!(map, "{row:>row_width$} ").unwrap();
+            for column in 0..self.columns {
+                if column == self.columns / 2 {
+                    map.push(' ');
+                }
+                let seat = row * self.columns + column;
+                map.push(if occupied.contains(seat) {
+                    '#'
+                } else if (first..=last).contains(&seat) {
+                    '.'
+                } else {
+                    ' '
+                });
+            }
+            map.truncate(map.trim_end().len());
+            map.push('\n');
+        }
+        map
     }
-    column
 }
 
-fn parse_seat(line: &str) -> usize {
-    let bytes = line.as_bytes();
-    parse_row(&bytes[0..7]) * 8 + parse_column(&bytes[7..])
+// Each run of empty seats between the first and last occupied seats
+fn find_gaps(occupied: &BitSet) -> Vec<RangeInclusive<usize>> {
+    let mut gaps = Vec::new();
+    let mut previous: Option<usize> = None;
+    for seat in occupied {
+        if let Some(previous) = previous {
+            if seat > previous + 1 {
+                gaps.push(previous + 1..=seat - 1);
+            }
+        }
+        previous = Some(seat);
+    }
+    gaps
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
+
+    let plane = Plane::new(args.rows, args.columns);
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut max_seat = 0;
     let mut occupied = BitSet::new();
 
     let mut line = String::new();
+    let mut line_number = 0;
     loop {
         let bytes = reader
             .read_line(&mut line)
@@ -59,20 +216,113 @@ fn main() {
         if bytes == 0 {
             break;
         }
+        line_number += 1;
 
-        let seat = parse_seat(&line.trim());
-
-        max_seat = max(max_seat, seat);
-        occupied.insert(seat);
+        if !line.trim().is_empty() {
+            let seat = plane
+                .decode(line.trim())
+                .unwrap_or_else(|error| panic!("Line {}: {}", line_number, error));
+            occupied.insert(seat);
+        }
 
         line.clear();
     }
 
-    println!("Max seat: {}", max_seat);
-    for seat in &occupied {
-        if !occupied.contains(seat + 1) && occupied.contains(seat + 2) {
-            println!("My seat: {}", seat + 1);
-            break;
+    if args.map {
+        print!("{}", plane.render(&occupied));
+    }
+
+    if args.part == 1 {
+        let max_seat = occupied.iter().last().expect("Failed to find any seats");
+        println!("Max seat: {max_seat}");
+        return;
+    }
+
+    // My seat is the only one missing with both neighbors occupied
+    let seats: Vec<_> = find_gaps(&occupied)
+        .into_iter()
+        .filter(|gap| gap.start() == gap.end())
+        .map(|gap| *gap.start())
+        .collect();
+    assert!(
+        !seats.is_empty(),
+        "Failed to find an empty seat with both neighbors occupied"
+    );
+    for seat in seats {
+        let pass = plane.encode(seat).expect("Failed to encode seat");
+        println!("My seat: {seat} ({pass})");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bit_set::BitSet;
+
+    use super::{find_gaps, PassError, Plane};
+
+    const EXAMPLES: [(&str, usize); 4] = [
+        ("FBFBBFFRLR", 357),
+        ("BFFFBBFRRR", 567),
+        ("FFFBBBFRRR", 119),
+        ("BBFFBBFRLL", 820),
+    ];
+
+    #[test]
+    fn examples() {
+        let plane = Plane::new(128, 8);
+        for (pass, seat) in EXAMPLES {
+            assert_eq!(plane.decode(pass), Ok(seat));
+            assert_eq!(plane.encode(seat).unwrap(), pass);
         }
     }
+
+    #[test]
+    fn round_trip() {
+        for plane in [Plane::new(128, 8), Plane::new(16, 4), Plane::new(1, 2)] {
+            for seat in 0..plane.seat_count() {
+                let pass = plane.encode(seat).unwrap();
+                assert_eq!(plane.decode(&pass), Ok(seat));
+            }
+            assert!(plane.encode(plane.seat_count()).is_none());
+        }
+        assert_eq!(Plane::new(16, 4).encode(45).unwrap(), "BFBBLR");
+    }
+
+    #[test]
+    fn malformed_passes() {
+        let plane = Plane::new(128, 8);
+        assert_eq!(
+            plane.decode("FBFBBFFRL"),
+            Err(PassError::WrongLength {
+                expected: 10,
+                found: 9
+            })
+        );
+        assert_eq!(
+            plane.decode("FBFBBFRRLR"),
+            Err(PassError::InvalidCharacter {
+                position: 7,
+                character: 'R',
+                expected: ['F', 'B']
+            })
+        );
+        assert_eq!(
+            plane.decode("FBFBBFFRLx").unwrap_err().to_string(),
+            "expected L or R at position 10 but found 'x'"
+        );
+    }
+
+    #[test]
+    fn gaps_and_map() {
+        let plane = Plane::new(8, 4);
+        let occupied: BitSet = [5, 6, 8, 9, 10, 13, 14, 15, 16, 18]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(find_gaps(&occupied), [7..=7, 11..=12, 17..=17]);
+        assert_eq!(
+            plane.render(&occupied),
+            "1  # #.\n2 ## #.\n3 .# ##\n4 #. #\n"
+        );
+    }
 }
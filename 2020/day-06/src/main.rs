@@ -1,11 +1,19 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-    ops::AddAssign,
-};
+use std::{io::BufRead, ops::AddAssign};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 struct QuestionCounter {
     any_person: u32,
@@ -62,14 +70,9 @@ impl QuestionCounter {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut counter = QuestionCounter::new();
     let mut counts = Counts {
@@ -95,6 +98,9 @@ fn main() {
 
     counts += counter.add_line("").expect("Failed to find last record");
 
-    println!("Any person: {}", counts.any_person);
-    println!("All people: {}", counts.all_people);
+    if args.part == 1 {
+        println!("Any person: {}", counts.any_person);
+    } else {
+        println!("All people: {}", counts.all_people);
+    }
 }
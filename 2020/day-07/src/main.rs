@@ -2,125 +2,246 @@
 
 use std::{
     collections::{HashMap, HashSet, VecDeque},
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    fmt,
+    fmt::Write as _,
+    fs,
+    io::BufRead,
 };
 
-struct Bag {
-    name: String,
-    count: i32,
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Bag to ask about
+    #[arg(long, default_value = "shiny gold")]
+    bag: String,
+
+    /// File to write the rule graph to, in DOT format
+    #[arg(long)]
+    dot: Option<String>,
+}
+
+// Parses "<count> <name> bag(s)", allowing counts of any number of digits
+fn parse_contents(description: &str) -> (u64, &str) {
+    let description = description.trim().trim_end_matches('.');
+    let description = description
+        .strip_suffix(" bags")
+        .or_else(|| description.strip_suffix(" bag"))
+        .unwrap_or_else(|| panic!("Expected [{}] to end in bag or bags", description));
+    let (count, name) = description
+        .split_once(' ')
+        .unwrap_or_else(|| panic!("Expected a count and a name in [{}]", description));
+    let count = count
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse count [{}] as u64", count));
+    (count, name)
+}
+
+// Bags that are part of a loop of rules, starting and ending with the same bag
+struct Cycle(Vec<String>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.join(" -> "))
+    }
 }
 
-struct BagTracker {
-    held_by: HashMap<String, Vec<String>>,
-    holds: HashMap<String, Vec<Bag>>,
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    Unvisited,
+    InProgress,
+    Done,
 }
 
-impl BagTracker {
+// Each bag is a node, with an edge to each bag it directly holds, weighted by how many it holds
+struct BagGraph {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    holds: Vec<Vec<(usize, u64)>>,
+    held_by: Vec<Vec<usize>>,
+    // Bags that already have a rule, including ones that hold nothing
+    defined: HashSet<usize>,
+}
+
+impl BagGraph {
     fn new() -> Self {
         Self {
-            held_by: HashMap::new(),
-            holds: HashMap::new(),
+            names: Vec::new(),
+            indices: HashMap::new(),
+            holds: Vec::new(),
+            held_by: Vec::new(),
+            defined: HashSet::new(),
         }
     }
 
+    fn get_or_insert(&mut self, name: &str) -> usize {
+        if let Some(index) = self.indices.get(name) {
+            return *index;
+        }
+
+        let index = self.names.len();
+        self.names.push(String::from(name));
+        self.indices.insert(String::from(name), index);
+        self.holds.push(Vec::new());
+        self.held_by.push(Vec::new());
+        index
+    }
+
+    fn index(&self, name: &str) -> usize {
+        *self
+            .indices
+            .get(name)
+            .unwrap_or_else(|| panic!("Unknown bag [{}]", name))
+    }
+
     fn parse_line(&mut self, line: &str) {
-        let mut split = line.split("contain");
-        let container = split
-            .next()
-            .expect("Failed to find container")
-            .strip_suffix(" bags ")
-            .expect("Failed to strip 'bags' suffix");
-
-        split
-            .next()
-            .expect("Failed to find containees")
-            .split(',')
-            .filter_map(|token| {
-                let description = token
-                    .trim()
-                    .trim_end_matches('.')
-                    .trim_end_matches('s')
-                    .strip_suffix(" bag")
-                    .expect("Failed to strip 'bags' suffix");
-
-                if description == "no other" {
-                    return None;
-                }
+        let (container, contents) = line
+            .trim()
+            .split_once(" bags contain ")
+            .unwrap_or_else(|| panic!("Failed to parse rule [{}]", line.trim()));
 
-                Some(Bag {
-                    name: String::from(&description[2..]),
-                    count: description[0..1]
-                        .parse()
-                        .expect("Failed to parse count as i32"),
-                })
-            })
-            .for_each(|containee| {
-                self.held_by
-                    .entry(containee.name.clone())
-                    .or_default()
-                    .push(String::from(container));
-
-                self.holds
-                    .entry(String::from(container))
-                    .or_default()
-                    .push(containee);
-            });
-    }
-
-    fn compute_container_count(&self, name: &str) -> usize {
+        let container = self.get_or_insert(container);
+        assert!(
+            self.defined.insert(container),
+            "Found a second rule for [{}]",
+            self.names[container]
+        );
+
+        if contents.trim_end_matches('.') == "no other bags" {
+            return;
+        }
+
+        for description in contents.split(',') {
+            let (count, name) = parse_contents(description);
+            let inner = self.get_or_insert(name);
+            self.holds[container].push((inner, count));
+            self.held_by[inner].push(container);
+        }
+    }
+
+    // Finds a loop of rules reachable from the named bag, which would make it hold infinitely many
+    // others
+    fn find_cycle(&self, name: &str) -> Option<Cycle> {
+        let mut visits = vec![Visit::Unvisited; self.names.len()];
+        self.find_cycle_from(self.index(name), &mut visits, &mut Vec::new())
+    }
+
+    fn find_cycle_from(
+        &self,
+        bag: usize,
+        visits: &mut [Visit],
+        path: &mut Vec<usize>,
+    ) -> Option<Cycle> {
+        match visits[bag] {
+            Visit::Done => return None,
+            Visit::InProgress => {
+                let start = path
+                    .iter()
+                    .position(|index| *index == bag)
+                    .expect("Failed to find bag in path");
+                let names = path[start..]
+                    .iter()
+                    .chain(Some(&bag))
+                    .map(|index| self.names[*index].clone())
+                    .collect();
+                return Some(Cycle(names));
+            }
+            Visit::Unvisited => (),
+        }
+
+        visits[bag] = Visit::InProgress;
+        path.push(bag);
+        for (containee, _) in &self.holds[bag] {
+            if let Some(cycle) = self.find_cycle_from(*containee, visits, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        visits[bag] = Visit::Done;
+        None
+    }
+
+    // Every bag that can eventually hold the named bag, sorted by name
+    fn find_containers(&self, name: &str) -> Vec<&str> {
+        let mut found = vec![false; self.names.len()];
         let mut work_queue = VecDeque::new();
-        work_queue.push_back(name);
-
-        let mut containers = HashSet::new();
-
-        while !work_queue.is_empty() {
-            let current = work_queue
-                .pop_front()
-                .expect("Failed to pop front of queue");
-            if let Some(parents) = self.held_by.get(current) {
-                for parent in parents {
-                    if containers.insert(parent) {
-                        work_queue.push_back(parent)
-                    }
+        work_queue.push_back(self.index(name));
+
+        while let Some(current) = work_queue.pop_front() {
+            for parent in &self.held_by[current] {
+                if !found[*parent] {
+                    found[*parent] = true;
+                    work_queue.push_back(*parent);
                 }
             }
         }
 
-        containers.len()
+        let mut containers: Vec<_> = (0..self.names.len())
+            .filter(|index| found[*index])
+            .map(|index| self.names[index].as_str())
+            .collect();
+        containers.sort_unstable();
+        containers
     }
 
-    fn compute_containee_count(
-        &self,
-        container: &Bag,
-        containee_counts: &mut HashMap<String, i32>,
-    ) -> i32 {
-        if let Some(count) = containee_counts.get(&container.name) {
-            return container.count * (1 + *count);
+    // How many bags the named bag holds in total, not counting itself. No cycle may be reachable
+    // from the named bag.
+    fn count_contents(&self, name: &str) -> u64 {
+        self.count_contents_of(self.index(name), &mut vec![None; self.names.len()])
+    }
+
+    fn count_contents_of(&self, bag: usize, counts: &mut [Option<u64>]) -> u64 {
+        if let Some(count) = counts[bag] {
+            return count;
         }
 
-        let mut containee_count = 0;
-        for containee in self.holds.get(&container.name).unwrap_or(&Vec::new()) {
-            containee_count += self.compute_containee_count(containee, containee_counts);
+        let mut total = 0_u64;
+        for (containee, count) in &self.holds[bag] {
+            let each = 1 + self.count_contents_of(*containee, counts);
+            total = count
+                .checked_mul(each)
+                .and_then(|contents| total.checked_add(contents))
+                .expect("Failed to fit bag count in u64");
         }
 
-        containee_counts.insert(container.name.clone(), containee_count);
-        container.count * (1 + containee_count)
+        counts[bag] = Some(total);
+        total
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph bags {\n");
+        for name in &self.names {
+            writeln!(dot, "    \"{name}\";").unwrap();
+        }
+        for (container, contents) in self.holds.iter().enumerate() {
+            for (containee, count) in contents {
+                writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [label={}];",
+                    self.names[container], self.names[*containee], count
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut tracker = BagTracker::new();
+    let mut graph = BagGraph::new();
 
     let mut line = String::new();
     loop {
@@ -131,25 +252,158 @@ fn main() {
             break;
         }
 
-        tracker.parse_line(&line);
+        if !line.trim().is_empty() {
+            graph.parse_line(&line);
+        }
 
         line.clear();
     }
 
-    println!(
-        "Can contain shiny gold: {}",
-        tracker.compute_container_count("shiny gold")
-    );
-
-    // Subtract 1 since we don't want to account for the shiny gold bag itself
-    println!(
-        "Shiny gold contains: {}",
-        tracker.compute_containee_count(
-            &Bag {
-                name: String::from("shiny gold"),
-                count: 1,
-            },
-            &mut HashMap::new()
-        ) - 1
-    );
+    if let Some(filename) = &args.dot {
+        fs::write(filename, graph.to_dot())
+            .unwrap_or_else(|_| panic!("Failed to write {}", filename));
+    }
+
+    if args.part == 1 {
+        println!(
+            "Can contain {}: {}",
+            args.bag,
+            graph.find_containers(&args.bag).len()
+        );
+    } else {
+        // Containers can be found whatever the rules, but counting contents needs the rules the
+        // bag reaches to be acyclic
+        if let Some(cycle) = graph.find_cycle(&args.bag) {
+            panic!("Bag rules contain a cycle: {}", cycle);
+        }
+        println!("{} contains: {}", args.bag, graph.count_contents(&args.bag));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BagGraph;
+
+    const EXAMPLE: [&str; 9] = [
+        "light red bags contain 1 bright white bag, 2 muted yellow bags.",
+        "dark orange bags contain 3 bright white bags, 4 muted yellow bags.",
+        "bright white bags contain 1 shiny gold bag.",
+        "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.",
+        "shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.",
+        "dark olive bags contain 3 faded blue bags, 4 dotted black bags.",
+        "vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.",
+        "faded blue bags contain no other bags.",
+        "dotted black bags contain no other bags.",
+    ];
+
+    fn build(lines: &[&str]) -> BagGraph {
+        let mut graph = BagGraph::new();
+        for line in lines {
+            graph.parse_line(line);
+        }
+        graph
+    }
+
+    #[test]
+    fn example_queries() {
+        let graph = build(&EXAMPLE);
+        assert!(graph.find_cycle("shiny gold").is_none());
+        assert_eq!(
+            graph.find_containers("shiny gold"),
+            ["bright white", "dark orange", "light red", "muted yellow"]
+        );
+        assert_eq!(graph.count_contents("shiny gold"), 32);
+        assert_eq!(graph.count_contents("faded blue"), 0);
+    }
+
+    #[test]
+    fn deep_example() {
+        let graph = build(&[
+            "shiny gold bags contain 2 dark red bags.",
+            "dark red bags contain 2 dark orange bags.",
+            "dark orange bags contain 2 dark yellow bags.",
+            "dark yellow bags contain 2 dark green bags.",
+            "dark green bags contain 2 dark blue bags.",
+            "dark blue bags contain 2 dark violet bags.",
+            "dark violet bags contain no other bags.",
+        ]);
+        assert_eq!(graph.count_contents("shiny gold"), 126);
+    }
+
+    #[test]
+    fn multi_digit_counts() {
+        let graph = build(&[
+            "shiny gold bags contain 12 dark red bags, 1 pale cyan bag.",
+            "dark red bags contain 105 pale cyan bags.",
+            "pale cyan bags contain no other bags.",
+        ]);
+        assert_eq!(graph.count_contents("shiny gold"), 12 * 106 + 1);
+    }
+
+    #[test]
+    fn cycle() {
+        let graph = build(&[
+            "shiny gold bags contain 1 dark red bag.",
+            "dark red bags contain 2 pale cyan bags, 1 dotted black bag.",
+            "dotted black bags contain no other bags.",
+            "pale cyan bags contain 3 dark red bags.",
+        ]);
+        assert_eq!(
+            graph.find_cycle("shiny gold").unwrap().to_string(),
+            "dark red -> pale cyan -> dark red"
+        );
+    }
+
+    #[test]
+    fn unreachable_cycle() {
+        let graph = build(&[
+            "shiny gold bags contain 2 faded blue bags.",
+            "faded blue bags contain no other bags.",
+            "dark red bags contain 1 pale cyan bag.",
+            "pale cyan bags contain 1 dark red bag.",
+        ]);
+        assert!(graph.find_cycle("shiny gold").is_none());
+        assert!(graph.find_cycle("dark red").is_some());
+        assert_eq!(graph.count_contents("shiny gold"), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Found a second rule for [faded blue]")]
+    fn duplicate_empty_rule() {
+        build(&[
+            "faded blue bags contain no other bags.",
+            "faded blue bags contain no other bags.",
+        ]);
+    }
+
+    #[test]
+    fn containers_with_cycle() {
+        let graph = build(&[
+            "shiny gold bags contain 1 dark red bag.",
+            "dark red bags contain 1 shiny gold bag.",
+            "light red bags contain 1 dark red bag.",
+        ]);
+        assert!(graph.find_cycle("shiny gold").is_some());
+        assert_eq!(
+            graph.find_containers("shiny gold"),
+            ["dark red", "light red", "shiny gold"]
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let graph = build(&[
+            "bright white bags contain 1 shiny gold bag.",
+            "shiny gold bags contain no other bags.",
+        ]);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph bags {
+    \"bright white\";
+    \"shiny gold\";
+    \"bright white\" -> \"shiny gold\" [label=1];
+}
+"
+        );
+    }
 }
@@ -1,11 +1,23 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    convert::TryInto,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{convert::TryFrom, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Also look for repairs that change a jump's argument
+    #[arg(long)]
+    arguments: bool,
+}
 
 #[derive(Clone, Copy, PartialEq)]
 enum Command {
@@ -40,83 +52,192 @@ impl Operation {
         Operation { command, payload }
     }
 
-    fn execute(&self, flip_operation: bool, accumulator: &mut i32, pc: &mut usize) {
-        let command = if flip_operation {
-            match self.command {
-                Command::Accumulate => Command::Accumulate,
-                Command::Jump => Command::None,
-                Command::None => Command::Jump,
-            }
-        } else {
-            self.command
-        };
+    fn flipped(&self) -> Option<Command> {
+        match self.command {
+            Command::Accumulate => None,
+            Command::Jump => Some(Command::None),
+            Command::None => Some(Command::Jump),
+        }
+    }
 
-        match command {
-            Command::Accumulate => {
-                *accumulator += self.payload;
-                *pc += 1;
-            }
-            Command::Jump => {
-                let signed_pc: isize = (*pc).try_into().expect("Failed to fit PC in isize");
-                *pc = (signed_pc + self.payload as isize)
-                    .try_into()
-                    .expect("Failed to fit signed PC in usize");
-            }
-            Command::None => *pc += 1,
+    fn accumulation(&self) -> i32 {
+        if self.command == Command::Accumulate {
+            self.payload
+        } else {
+            0
         }
     }
 }
 
-struct Instruction {
-    operation: Operation,
-    visited: bool,
+fn to_i64(value: usize) -> i64 {
+    i64::try_from(value).expect("Failed to fit address in i64")
 }
 
-impl Instruction {
-    fn new(operation: Operation) -> Self {
-        Self {
-            operation,
-            visited: false,
-        }
+// Where control goes after running command at pc, or None if it leaves the program other than by
+// falling off the end. The program length is the termination address.
+fn get_target(pc: usize, command: Command, payload: i32, length: usize) -> Option<usize> {
+    let target = match command {
+        Command::Accumulate | Command::None => pc + 1,
+        Command::Jump => usize::try_from(to_i64(pc) + i64::from(payload)).ok()?,
+    };
+    if target <= length {
+        Some(target)
+    } else {
+        None
     }
 }
 
-fn run_program(program: &mut Vec<Instruction>, flip_pc: Option<usize>) -> Result<i32, i32> {
+enum Outcome {
+    Terminated,
+    Looped(i32),
+    Crashed,
+}
+
+// Runs the unpatched program, returning each PC it executes with the accumulator before it ran
+fn trace_program(program: &[Operation]) -> (Vec<(usize, i32)>, Outcome) {
+    let mut visited = vec![false; program.len()];
+    let mut path = Vec::new();
     let mut accumulator = 0;
-    let mut pc = 0_usize;
+    let mut pc = 0;
     loop {
-        let instruction = &mut program[pc as usize];
+        if pc == program.len() {
+            return (path, Outcome::Terminated);
+        }
+        if visited[pc] {
+            return (path, Outcome::Looped(accumulator));
+        }
+        visited[pc] = true;
+        path.push((pc, accumulator));
+
+        let operation = &program[pc];
+        accumulator += operation.accumulation();
+        match get_target(pc, operation.command, operation.payload, program.len()) {
+            Some(target) => pc = target,
+            None => return (path, Outcome::Crashed),
+        }
+    }
+}
+
+// Every address from which the unpatched program terminates. Each instruction has one target, so
+// these form a tree of predecessors rooted at the termination address, which is walked once.
+struct Reachability {
+    // Entry and exit order of a depth-first walk of the tree, for addresses in it
+    order: Vec<Option<(usize, usize)>>,
+    // How much the accumulator grows between reaching the address and terminating
+    accumulation: Vec<i32>,
+}
 
-        if instruction.visited {
-            return Err(accumulator);
+impl Reachability {
+    fn new(program: &[Operation]) -> Self {
+        let length = program.len();
+        let mut predecessors = vec![Vec::new(); length + 1];
+        for (pc, operation) in program.iter().enumerate() {
+            if let Some(target) = get_target(pc, operation.command, operation.payload, length) {
+                predecessors[target].push(pc);
+            }
         }
 
-        instruction.visited = true;
-        instruction.operation.execute(
-            flip_pc.map_or(false, |flip_pc| flip_pc == pc),
-            &mut accumulator,
-            &mut pc,
-        );
+        let mut order = vec![None; length + 1];
+        let mut accumulation = vec![0; length + 1];
+        let mut counter = 0;
+        let mut stack = vec![(length, false)];
+        while let Some((address, finished)) = stack.pop() {
+            if finished {
+                order[address] = order[address].map(|(entry, _)| (entry, counter));
+                counter += 1;
+                continue;
+            }
 
-        if pc == program.len() {
-            return Ok(accumulator);
+            order[address] = Some((counter, counter));
+            counter += 1;
+            stack.push((address, true));
+            for predecessor in &predecessors[address] {
+                accumulation[*predecessor] =
+                    program[*predecessor].accumulation() + accumulation[address];
+                stack.push((*predecessor, false));
+            }
+        }
+
+        Self {
+            order,
+            accumulation,
         }
+    }
+
+    fn terminates(&self, address: usize) -> bool {
+        self.order[address].is_some()
+    }
 
-        if pc > program.len() {
-            return Err(-1);
+    // Whether the unpatched run from address goes through pc on its way to terminating
+    fn passes_through(&self, address: usize, pc: usize) -> bool {
+        match (self.order[address], self.order[pc]) {
+            (Some((entry, exit)), Some((pc_entry, pc_exit))) => {
+                pc_entry <= entry && exit <= pc_exit
+            }
+            _ => false,
         }
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
+#[derive(Debug, PartialEq)]
+enum Patch {
+    Flip,
+    Argument(i32),
+}
+
+#[derive(Debug, PartialEq)]
+struct Repair {
+    pc: usize,
+    patch: Patch,
+    accumulator: i32,
+}
+
+// Finds every single-instruction patch that makes the program terminate. A patch only matters if
+// the unpatched run reaches it, and works if its new target terminates without coming back to it.
+fn find_repairs(program: &[Operation], change_arguments: bool) -> Vec<Repair> {
+    let length = program.len();
+    let reachability = Reachability::new(program);
+    let (path, _) = trace_program(program);
+
+    let mut repairs = Vec::new();
+    for (pc, accumulator) in path {
+        let operation = &program[pc];
+        let repair_to = |target: usize, patch: Patch| {
+            if reachability.terminates(target) && !reachability.passes_through(target, pc) {
+                Some(Repair {
+                    pc,
+                    patch,
+                    accumulator: accumulator + reachability.accumulation[target],
+                })
+            } else {
+                None
+            }
+        };
+
+        if let Some(command) = operation.flipped() {
+            if let Some(target) = get_target(pc, command, operation.payload, length) {
+                repairs.extend(repair_to(target, Patch::Flip));
+            }
+        }
+
+        if change_arguments && operation.command == Command::Jump {
+            for target in 0..=length {
+                let payload = i32::try_from(to_i64(target) - to_i64(pc))
+                    .expect("Failed to fit argument in i32");
+                if payload != operation.payload {
+                    repairs.extend(repair_to(target, Patch::Argument(payload)));
+                }
+            }
+        }
     }
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    repairs
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut program = Vec::new();
 
@@ -129,31 +250,173 @@ fn main() {
             break;
         }
 
-        program.push(Instruction::new(Operation::from_line(&line.trim())));
+        program.push(Operation::from_line(line.trim()));
 
         line.clear();
     }
 
-    if let Err(accumulator) = run_program(&mut program, None) {
-        println!("Infinite loop accumulator {}", accumulator);
+    if args.part == 1 {
+        if let (_, Outcome::Looped(accumulator)) = trace_program(&program) {
+            println!("Infinite loop accumulator {accumulator}");
+        }
+        return;
     }
 
-    for skip_pc in 0..program.len() {
-        if program[skip_pc].operation.command == Command::Accumulate {
-            continue;
+    for repair in find_repairs(&program, args.arguments) {
+        match repair.patch {
+            Patch::Flip => println!(
+                "Flipping PC {} terminated with accumulator {}",
+                repair.pc, repair.accumulator
+            ),
+            Patch::Argument(argument) => println!(
+                "Changing the argument at PC {} to {:+} terminated with accumulator {}",
+                repair.pc, argument, repair.accumulator
+            ),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::{find_repairs, get_target, Command, Operation, Patch, Repair};
 
-        // Reset visited bits before running
-        for instruction in &mut program {
-            instruction.visited = false;
+    const EXAMPLE: [&str; 9] = [
+        "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6",
+    ];
+
+    fn parse(lines: &[&str]) -> Vec<Operation> {
+        lines
+            .iter()
+            .map(|line| Operation::from_line(line))
+            .collect()
+    }
+
+    // Runs the program with one instruction replaced, returning the accumulator if it terminates
+    fn run_patched(
+        program: &[Operation],
+        pc: usize,
+        command: Command,
+        payload: i32,
+    ) -> Option<i32> {
+        let mut visited = vec![false; program.len()];
+        let mut accumulator = 0;
+        let mut current = 0;
+        while current != program.len() {
+            if visited[current] {
+                return None;
+            }
+            visited[current] = true;
+
+            let (command, payload) = if current == pc {
+                (command, payload)
+            } else {
+                (program[current].command, program[current].payload)
+            };
+            if command == Command::Accumulate {
+                accumulator += payload;
+            }
+            current = get_target(current, command, payload, program.len())?;
         }
+        Some(accumulator)
+    }
 
-        if let Ok(accumulator) = run_program(&mut program, Some(skip_pc)) {
-            println!(
-                "Flipping PC {} terminated with accumulator {}",
-                skip_pc, accumulator
-            );
-            break;
+    // Tries every patch by running the program again
+    fn find_repairs_by_running(program: &[Operation], change_arguments: bool) -> Vec<Repair> {
+        let mut repairs = Vec::new();
+        for (pc, operation) in program.iter().enumerate() {
+            if let Some(command) = operation.flipped() {
+                if let Some(accumulator) = run_patched(program, pc, command, operation.payload) {
+                    let patch = Patch::Flip;
+                    repairs.push(Repair {
+                        pc,
+                        patch,
+                        accumulator,
+                    });
+                }
+            }
+            if change_arguments && operation.command == Command::Jump {
+                let length = i32::try_from(program.len()).unwrap();
+                for payload in -length..=length {
+                    if payload == operation.payload {
+                        continue;
+                    }
+                    if let Some(accumulator) = run_patched(program, pc, Command::Jump, payload) {
+                        let patch = Patch::Argument(payload);
+                        repairs.push(Repair {
+                            pc,
+                            patch,
+                            accumulator,
+                        });
+                    }
+                }
+            }
+        }
+        repairs
+    }
+
+    fn sorted(mut repairs: Vec<Repair>) -> Vec<(usize, Option<i32>, i32)> {
+        let mut keys: Vec<_> = repairs
+            .drain(..)
+            .map(|repair| {
+                let argument = match repair.patch {
+                    Patch::Flip => None,
+                    Patch::Argument(argument) => Some(argument),
+                };
+                (repair.pc, argument, repair.accumulator)
+            })
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn example_flip() {
+        let program = parse(&EXAMPLE);
+        assert_eq!(
+            find_repairs(&program, false),
+            [Repair {
+                pc: 7,
+                patch: Patch::Flip,
+                accumulator: 8
+            }]
+        );
+    }
+
+    #[test]
+    fn example_arguments() {
+        let program = parse(&EXAMPLE);
+        let repairs = find_repairs(&program, true);
+        assert!(repairs.contains(&Repair {
+            pc: 4,
+            patch: Patch::Argument(5),
+            accumulator: 5
+        }));
+        assert_eq!(
+            sorted(repairs),
+            sorted(find_repairs_by_running(&program, true))
+        );
+    }
+
+    #[test]
+    fn matches_running_every_patch() {
+        let programs: [&[&str]; 4] = [
+            &EXAMPLE,
+            // Already terminates, so some flips break it
+            &["acc +1", "jmp +2", "acc +5", "nop -1", "acc +2"],
+            // Jumps out of range crash rather than terminate
+            &["nop +5", "jmp -1", "acc +3", "jmp -3"],
+            &["jmp +2", "acc +7", "nop -2", "jmp -1", "acc +1", "jmp -5"],
+        ];
+        for program in &programs {
+            let program = parse(program);
+            for change_arguments in [false, true] {
+                assert_eq!(
+                    sorted(find_repairs(&program, change_arguments)),
+                    sorted(find_repairs_by_running(&program, change_arguments))
+                );
+            }
         }
     }
 }
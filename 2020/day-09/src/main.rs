@@ -1,18 +1,39 @@
 #![deny(clippy::all, clippy::pedantic)]
-#![allow(clippy::clippy::comparison_chain)]
 
 use std::{
     collections::{HashMap, VecDeque},
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
+    ops::Range,
 };
 
+use clap::{builder::RangedU64ValueParser, Parser};
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Number of previous values each value has to be a sum of two of
+    #[arg(long, default_value_t = 25, value_parser = RangedU64ValueParser::<usize>::new().range(2..))]
+    preamble: usize,
+
+    /// Report every invalid value and every range summing to it, not just the first
+    #[arg(long)]
+    all: bool,
+}
+
 struct XmasValidator {
     preamble_length: usize,
     valid_sums: HashMap<i64, usize>,
     window: VecDeque<i64>,
     values: Vec<i64>,
+    // Index and value of every value that isn't a sum of two in the window before it
+    invalid: Vec<(usize, i64)>,
 }
 
 impl XmasValidator {
@@ -22,6 +43,7 @@ impl XmasValidator {
             valid_sums: HashMap::new(),
             window: VecDeque::new(),
             values: Vec::new(),
+            invalid: Vec::new(),
         }
     }
 
@@ -45,11 +67,13 @@ impl XmasValidator {
         }
     }
 
-    fn add_value(&mut self, value: i64) -> bool {
-        self.values.push(value);
-
+    fn add_value(&mut self, value: i64) {
         let is_valid =
-            self.window.len() < self.preamble_length || self.valid_sums.get(&value).is_some();
+            self.window.len() < self.preamble_length || self.valid_sums.contains_key(&value);
+        if !is_valid {
+            self.invalid.push((self.values.len(), value));
+        }
+        self.values.push(value);
 
         if self.window.len() == self.preamble_length {
             self.remove_oldest();
@@ -59,45 +83,48 @@ impl XmasValidator {
             *self.valid_sums.entry(value + previous_value).or_default() += 1;
         }
         self.window.push_back(value);
-
-        is_valid
     }
 
-    fn find_weakness(&self, invalid_number: i64) -> i64 {
-        let mut first = 0_usize;
-        let mut last = 1_usize;
-        let mut sum = self.values[first] + self.values[last];
-        while sum != invalid_number {
-            if sum < invalid_number {
-                last += 1;
-                sum += self.values[last];
-            } else if sum > invalid_number {
-                sum -= self.values[first];
-                first += 1;
+    // Every run of at least two consecutive values that sums to target, in order of where they
+    // start
+    fn find_ranges(&self, target: i64) -> Vec<Range<usize>> {
+        // The sum of values[i..j] is prefix[j] - prefix[i]
+        let mut starts: HashMap<i64, Vec<usize>> = HashMap::new();
+        let mut ranges = Vec::new();
+        let mut prefix = 0;
+        starts.entry(0).or_default().push(0);
+        for (index, value) in self.values.iter().enumerate() {
+            prefix += value;
+            let end = index + 1;
+            if let Some(matching) = starts.get(&(prefix - target)) {
+                ranges.extend(
+                    matching
+                        .iter()
+                        .filter(|start| end - **start >= 2)
+                        .map(|start| *start..end),
+                );
             }
+            starts.entry(prefix).or_default().push(end);
         }
 
-        let mut min = self.values[first];
-        let mut max = self.values[first];
-        for value in &self.values[first..=last] {
-            min = min.min(*value);
-            max = max.max(*value);
-        }
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+
+    fn get_weakness(&self, range: &Range<usize>) -> i64 {
+        let values = &self.values[range.clone()];
+        let min = values.iter().min().expect("Failed to find min of range");
+        let max = values.iter().max().expect("Failed to find max of range");
         min + max
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut validator = XmasValidator::new(25);
+    let mut validator = XmasValidator::new(args.preamble);
 
     let mut line = String::new();
     loop {
@@ -108,13 +135,104 @@ fn main() {
             break;
         }
 
-        let value = line.trim().parse().expect("Failed to parse line as i64");
-        if !validator.add_value(value) {
-            println!("First invalid value: {}", value);
-            println!("Weakness: {}", validator.find_weakness(value));
-            break;
+        if !line.trim().is_empty() {
+            let value = line.trim().parse().expect("Failed to parse line as i64");
+            validator.add_value(value);
         }
 
         line.clear();
     }
+
+    if validator.invalid.is_empty() {
+        println!("All values are valid");
+        return;
+    }
+
+    if !args.all {
+        let (_, value) = validator.invalid[0];
+        if args.part == 1 {
+            println!("First invalid value: {value}");
+        } else {
+            let range = validator
+                .find_ranges(value)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| panic!("No contiguous range sums to {}", value));
+            println!("Weakness: {}", validator.get_weakness(&range));
+        }
+        return;
+    }
+
+    for (index, value) in &validator.invalid {
+        println!("Invalid value {value} at index {index}");
+        if args.part == 1 {
+            continue;
+        }
+
+        let ranges = validator.find_ranges(*value);
+        if ranges.is_empty() {
+            println!("  No contiguous range sums to {value}");
+        }
+        for range in ranges {
+            println!(
+                "  Indices {} to {}: weakness {}",
+                range.start,
+                range.end - 1,
+                validator.get_weakness(&range)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XmasValidator;
+
+    const EXAMPLE: [i64; 20] = [
+        35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
+    ];
+
+    fn validate(values: &[i64], preamble_length: usize) -> XmasValidator {
+        let mut validator = XmasValidator::new(preamble_length);
+        for value in values {
+            validator.add_value(*value);
+        }
+        validator
+    }
+
+    #[test]
+    fn example() {
+        let validator = validate(&EXAMPLE, 5);
+        assert_eq!(validator.invalid, [(14, 127)]);
+
+        let ranges = validator.find_ranges(127);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 2..6);
+        assert_eq!(validator.get_weakness(&ranges[0]), 62);
+    }
+
+    #[test]
+    fn every_invalid_value() {
+        // Past the first invalid value, keep checking against the values before each one
+        let mut values = EXAMPLE.to_vec();
+        values.extend([1000, 853]);
+        let validator = validate(&values, 5);
+        assert_eq!(validator.invalid, [(14, 127), (20, 1000)]);
+        assert!(validator.find_ranges(1000).is_empty());
+    }
+
+    #[test]
+    fn several_ranges() {
+        let validator = validate(&[1, 2, 3, 3, 2, 1], 2);
+        assert_eq!(validator.find_ranges(6), [0..3, 2..4, 3..6]);
+        assert_eq!(validator.find_ranges(100), []);
+    }
+
+    #[test]
+    fn no_range_for_single_value() {
+        let validator = validate(&[4, 5, 9, 20], 2);
+        assert_eq!(validator.invalid, [(3, 20)]);
+        assert!(validator.find_ranges(20).is_empty());
+        assert_eq!(validator.find_ranges(9).first(), Some(&(0..2)));
+    }
 }
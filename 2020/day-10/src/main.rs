@@ -1,78 +1,171 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{fmt, io::BufRead};
+
+use clap::{builder::RangedU64ValueParser, Parser};
+use common::open_input;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Largest difference in joltage an adapter can take
+    #[arg(long, default_value_t = 3, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    max_gap: usize,
+
+    /// Also print this many of the arrangements
+    #[arg(long)]
+    list: Option<usize>,
+}
+
+// Two neighboring joltages in the chain are further apart than any adapter can bridge
+#[derive(Debug, PartialEq)]
+struct GapError {
+    from: usize,
+    to: usize,
+    max_gap: usize,
+}
+
+impl fmt::Display for GapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no adapter bridges the gap from {} to {} jolts, which is more than {}",
+            self.from, self.to, self.max_gap
+        )
+    }
+}
 
 struct AdapterChainer {
-    adapters: Vec<usize>,
+    // Sorted joltages, starting with the outlet at 0 and ending with the device
+    joltages: Vec<usize>,
+    max_gap: usize,
 }
 
 impl AdapterChainer {
-    fn new() -> Self {
-        Self {
-            adapters: Vec::new(),
+    // The device is rated max_gap jolts above the highest adapter
+    fn new(mut adapters: Vec<usize>, max_gap: usize) -> Result<Self, GapError> {
+        adapters.sort_unstable();
+        let device = adapters.last().copied().unwrap_or(0) + max_gap;
+
+        let mut joltages = vec![0];
+        joltages.append(&mut adapters);
+        joltages.push(device);
+
+        if let Some(window) = joltages
+            .windows(2)
+            .find(|window| window[1] - window[0] > max_gap)
+        {
+            return Err(GapError {
+                from: window[0],
+                to: window[1],
+                max_gap,
+            });
         }
-    }
 
-    fn add_adapter(&mut self, adapter: usize) {
-        self.adapters.push(adapter);
+        Ok(Self { joltages, max_gap })
     }
 
-    fn get_difference_product(&mut self) -> i32 {
-        self.adapters.sort_unstable();
+    // How many times each gap, from 0 to max_gap, appears when every adapter is used
+    fn get_gap_counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.max_gap + 1];
+        for window in self.joltages.windows(2) {
+            counts[window[1] - window[0]] += 1;
+        }
+        counts
+    }
 
-        // Add the first and last differences
-        let mut differences = vec![0, 0, 1];
-        differences[self.adapters[0] - 1] += 1;
+    // The number of 1-jolt gaps times the number of the largest allowed gaps
+    fn get_difference_product(&self) -> usize {
+        let counts = self.get_gap_counts();
+        counts[1.min(self.max_gap)] * counts[self.max_gap]
+    }
 
-        for window in self.adapters.windows(2) {
-            differences[window[1] - window[0] - 1] += 1;
+    fn get_arrangement_count(&self) -> BigUint {
+        // Arrangements from the outlet up to each joltage, which is the sum of the arrangements
+        // of every joltage within reach below it
+        let mut arrangements: Vec<BigUint> = Vec::with_capacity(self.joltages.len());
+        arrangements.push(BigUint::one());
+        for (index, joltage) in self.joltages.iter().enumerate().skip(1) {
+            let mut count = BigUint::zero();
+            for previous in (0..index).rev() {
+                if joltage - self.joltages[previous] > self.max_gap {
+                    break;
+                }
+                count += &arrangements[previous];
+            }
+            arrangements.push(count);
         }
 
-        differences[0] * differences[2]
+        arrangements
+            .pop()
+            .expect("Failed to find device arrangements")
     }
 
-    fn get_arrangement_count(&mut self) -> usize {
-        let back = self.adapters[self.adapters.len() - 1];
-        // Add one value outside the range [1,3] to break out of the inner loop below
-        self.adapters.push(back + 4);
-        self.adapters.reverse();
-        // Add the implicit 0 for the outlet
-        self.adapters.push(0);
+    fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            chainer: self,
+            path: Vec::new(),
+        }
+    }
+}
 
-        let mut arrangements = Vec::new();
-        arrangements.resize(self.adapters.len(), 0_usize);
-        // This is the final adapter, which always hooks directly to the device
-        arrangements[1] = 1;
+// Every valid chain from the outlet to the device, using as many adapters as possible first
+struct Arrangements<'a> {
+    chainer: &'a AdapterChainer,
+    // Indices into joltages of the current chain, or empty before the first one
+    path: Vec<usize>,
+}
+
+impl Arrangements<'_> {
+    // Uses every adapter from the end of the path on, which is always possible
+    fn extend(&mut self) {
+        let last = *self.path.last().expect("Failed to find end of path");
+        self.path.extend(last + 1..self.chainer.joltages.len());
+    }
+}
 
-        for index in 2..self.adapters.len() {
-            for offset in 1..=3 {
-                if self.adapters[index - offset] - self.adapters[index] < 4 {
-                    arrangements[index] += arrangements[index - offset];
-                } else {
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let joltages = &self.chainer.joltages;
+        if self.path.is_empty() {
+            self.path.push(0);
+            self.extend();
+        } else {
+            // Skip the latest adapter that can be skipped, and use everything after it again
+            loop {
+                let removed = self.path.pop().expect("Failed to pop path");
+                let previous = *self.path.last()?;
+                let next = removed + 1;
+                if next < joltages.len()
+                    && joltages[next] - joltages[previous] <= self.chainer.max_gap
+                {
+                    self.path.push(next);
+                    self.extend();
                     break;
                 }
             }
         }
 
-        arrangements[arrangements.len() - 1]
+        Some(self.path.iter().map(|index| joltages[*index]).collect())
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut chainer = AdapterChainer::new();
+    let mut adapters = Vec::new();
 
     let mut line = String::new();
     loop {
@@ -83,11 +176,116 @@ fn main() {
             break;
         }
 
-        chainer.add_adapter(line.trim().parse().expect("Failed to parse adapter"));
+        if !line.trim().is_empty() {
+            adapters.push(line.trim().parse().expect("Failed to parse adapter"));
+        }
 
         line.clear();
     }
 
-    println!("Difference product: {}", chainer.get_difference_product());
-    println!("Arrangements: {}", chainer.get_arrangement_count())
+    let chainer = AdapterChainer::new(adapters, args.max_gap)
+        .unwrap_or_else(|error| panic!("Unusable adapters: {}", error));
+
+    if args.part == 1 {
+        println!("Difference product: {}", chainer.get_difference_product());
+        return;
+    }
+
+    println!("Arrangements: {}", chainer.get_arrangement_count());
+    if let Some(count) = args.list {
+        for arrangement in chainer.arrangements().take(count) {
+            let joltages: Vec<_> = arrangement.iter().map(ToString::to_string).collect();
+            println!("{}", joltages.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use num_bigint::BigUint;
+
+    use super::{AdapterChainer, GapError};
+
+    const SMALL_EXAMPLE: [usize; 11] = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+
+    const LARGE_EXAMPLE: [usize; 31] = [
+        28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38, 39, 11, 1, 32, 25, 35, 8,
+        17, 7, 9, 4, 2, 34, 10, 3,
+    ];
+
+    #[test]
+    fn examples() {
+        let chainer = AdapterChainer::new(SMALL_EXAMPLE.to_vec(), 3).unwrap();
+        assert_eq!(chainer.get_difference_product(), 35);
+        assert_eq!(chainer.get_arrangement_count(), BigUint::from(8_u32));
+
+        let chainer = AdapterChainer::new(LARGE_EXAMPLE.to_vec(), 3).unwrap();
+        assert_eq!(chainer.get_gap_counts(), [0, 22, 0, 10]);
+        assert_eq!(chainer.get_difference_product(), 220);
+        assert_eq!(chainer.get_arrangement_count(), BigUint::from(19208_u32));
+    }
+
+    #[test]
+    fn other_gaps() {
+        // Only one way to chain when every adapter has to be used
+        let chainer = AdapterChainer::new(vec![1, 2, 3, 4], 1).unwrap();
+        assert_eq!(chainer.get_arrangement_count(), BigUint::from(1_u32));
+
+        let chainer = AdapterChainer::new(vec![2, 5, 6, 8], 5).unwrap();
+        assert_eq!(chainer.get_gap_counts(), [0, 1, 2, 1, 0, 1]);
+        assert_eq!(chainer.get_difference_product(), 1);
+        // 8 is needed to reach the device at 13, and at least one of 5 and 6 to reach 8, but 2 is
+        // only needed to reach 6 on its own
+        assert_eq!(chainer.get_arrangement_count(), BigUint::from(5_u32));
+    }
+
+    #[test]
+    fn unusable_adapters() {
+        assert_eq!(
+            AdapterChainer::new(vec![1, 2, 6], 3).err(),
+            Some(GapError {
+                from: 2,
+                to: 6,
+                max_gap: 3
+            })
+        );
+        assert!(AdapterChainer::new(vec![4, 5], 3).is_err());
+    }
+
+    #[test]
+    fn long_chain() {
+        let chainer = AdapterChainer::new((1..=100).collect(), 3).unwrap();
+
+        // Tribonacci numbers, which outgrow a u64 well before 100
+        let mut counts = [0_u128, 0, 1];
+        for _ in 1..=100 {
+            counts = [counts[1], counts[2], counts[0] + counts[1] + counts[2]];
+        }
+        assert!(counts[2] > u128::from(u64::MAX));
+        assert_eq!(chainer.get_arrangement_count(), BigUint::from(counts[2]));
+    }
+
+    #[test]
+    fn enumerate() {
+        let chainer = AdapterChainer::new(SMALL_EXAMPLE.to_vec(), 3).unwrap();
+        let arrangements: Vec<_> = chainer.arrangements().collect();
+        assert_eq!(arrangements.len(), 8);
+        assert_eq!(
+            arrangements[0],
+            [0, 1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19, 22]
+        );
+        assert_eq!(arrangements[7], [0, 1, 4, 7, 10, 12, 15, 16, 19, 22]);
+        assert_eq!(arrangements.iter().collect::<HashSet<_>>().len(), 8);
+        for arrangement in &arrangements {
+            assert!(arrangement
+                .windows(2)
+                .all(|window| window[1] - window[0] <= 3));
+        }
+
+        let chainer = AdapterChainer::new(LARGE_EXAMPLE.to_vec(), 3).unwrap();
+        assert_eq!(chainer.arrangements().take(5).count(), 5);
+        assert_eq!(chainer.arrangements().count(), 19208);
+    }
 }
@@ -3,14 +3,25 @@
 
 use std::{
     convert::TryInto,
-    env,
     fmt::{Display, Formatter},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
 };
 
+use clap::Parser;
+use common::open_input;
+
 extern crate test;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy)]
 enum Cell {
     Floor,
@@ -49,7 +60,7 @@ impl Layout {
                 b'L' => Cell::Empty,
                 b'#' => Cell::Occupied,
                 _ => panic!("Unexpected byte [{}]", byte),
-            })
+            });
         }
 
         let incoming_column_count: i32 = line
@@ -131,12 +142,12 @@ impl Layout {
             for column in 0..self.column_count {
                 let index = self.get_index(row, column);
                 if let Cell::Floor = self.map[index as usize] {
-                    self.adjacent_indices.append(&mut vec![u16::max_value(); 8]);
+                    self.adjacent_indices.append(&mut vec![u16::MAX; 8]);
                     continue;
                 }
 
                 let mut adjacent_indices = self.get_adjacent_indices(row, column);
-                adjacent_indices.resize(8, u16::max_value());
+                adjacent_indices.resize(8, u16::MAX);
                 self.adjacent_indices.append(&mut adjacent_indices);
                 self.updated_indices.push(index);
             }
@@ -150,7 +161,7 @@ impl Layout {
         for adjacent_index in
             &self.adjacent_indices[((index as usize) * 8)..((index as usize) * 8 + 8)]
         {
-            if *adjacent_index == u16::max_value() {
+            if *adjacent_index == u16::MAX {
                 break;
             }
 
@@ -199,7 +210,7 @@ impl Layout {
     fn count_occupants(&self) -> i32 {
         self.occupied_seats
             .iter()
-            .map(|occupied| if *occupied { 1 } else { 0 })
+            .map(|occupied| i32::from(*occupied))
             .sum()
     }
 }
@@ -231,18 +242,11 @@ impl Display for Layout {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 || args.len() > 3 {
-        return;
-    }
-
-    let line_of_sight = args.len() == 3 && args[2] == "los";
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut layout = Layout::new(line_of_sight);
+    let mut layout = Layout::new(args.part == 2);
 
     let mut line = String::new();
     loop {
@@ -270,8 +274,7 @@ mod tests {
     use test::Bencher;
 
     fn get_layout(line_of_sight: bool) -> Layout {
-        let file = File::open("input.txt").expect("Failed to open input.txt");
-        let mut reader = BufReader::new(file);
+        let mut reader = open_input(Some("input.txt"));
 
         let mut layout = Layout::new(line_of_sight);
 
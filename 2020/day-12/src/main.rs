@@ -1,34 +1,80 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{fmt, fmt::Write as _, fs, io::BufRead, path::Path, str::FromStr};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// File to write the trajectory to, as an SVG path if it ends in .svg and as CSV otherwise
+    #[arg(long)]
+    trace: Option<String>,
+}
+
+// Room around the path in SVG output, so the stroke isn't clipped at the edges
+const SVG_MARGIN: i32 = 5;
 
 #[derive(Clone, Copy)]
-enum Direction {
-    North = 0,
-    East = 1,
-    South = 2,
-    West = 3,
+enum Instruction {
+    North(i32),
+    East(i32),
+    South(i32),
+    West(i32),
+    // Turns in degrees, always a multiple of 90
+    Left(i32),
+    Right(i32),
+    Forward(i32),
 }
 
-impl Direction {
-    fn from_i32(value: i32) -> Self {
-        match value {
-            0 => Direction::North,
-            1 => Direction::East,
-            2 => Direction::South,
-            3 => Direction::West,
-            _ => panic!("Unexpected value {}", value),
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let action = line.chars().next().ok_or("Empty instruction")?;
+        let amount = &line[action.len_utf8()..];
+        let amount: i32 = amount
+            .parse()
+            .map_err(|_| format!("Failed to parse amount [{amount}] as i32"))?;
+
+        if matches!(action, 'L' | 'R') && amount % 90 != 0 {
+            return Err(format!(
+                "Turn of {amount} degrees is not a multiple of 90 degrees"
+            ));
+        }
+
+        match action {
+            'N' => Ok(Self::North(amount)),
+            'E' => Ok(Self::East(amount)),
+            'S' => Ok(Self::South(amount)),
+            'W' => Ok(Self::West(amount)),
+            'L' => Ok(Self::Left(amount)),
+            'R' => Ok(Self::Right(amount)),
+            'F' => Ok(Self::Forward(amount)),
+            _ => Err(format!("Unexpected action '{action}'")),
         }
     }
 }
 
-enum Rotation {
-    Right,
-    Left,
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::North(amount) => write!(f, "N{amount}"),
+            Self::East(amount) => write!(f, "E{amount}"),
+            Self::South(amount) => write!(f, "S{amount}"),
+            Self::West(amount) => write!(f, "W{amount}"),
+            Self::Left(degrees) => write!(f, "L{degrees}"),
+            Self::Right(degrees) => write!(f, "R{degrees}"),
+            Self::Forward(amount) => write!(f, "F{amount}"),
+        }
+    }
 }
 
 enum Mode {
@@ -36,115 +82,138 @@ enum Mode {
     Waypoint,
 }
 
+// Positions after an instruction, with the waypoint relative to the ship
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct State {
+    ship: (i32, i32),
+    waypoint: (i32, i32),
+}
+
+// Moving forward always moves the ship by the waypoint. When steering the ship directly, the
+// waypoint is just a unit vector in the direction the ship is facing.
 struct Navigator {
     mode: Mode,
-    x: i32,
-    y: i32,
-    direction: Direction,
-    waypoint_x: i32,
-    waypoint_y: i32,
+    state: State,
+    trajectory: Vec<(Option<Instruction>, State)>,
 }
 
 impl Navigator {
     fn new(mode: Mode) -> Self {
+        let waypoint = match mode {
+            Mode::Ship => (1, 0),
+            Mode::Waypoint => (10, 1),
+        };
+        let state = State {
+            ship: (0, 0),
+            waypoint,
+        };
+
         Self {
             mode,
-            x: 0,
-            y: 0,
-            direction: Direction::East,
-            waypoint_x: 10,
-            waypoint_y: 1,
+            state,
+            trajectory: vec![(None, state)],
         }
     }
 
-    fn translate(&mut self, direction: Direction, amount: i32) {
+    fn translate(&mut self, dx: i32, dy: i32) {
         let (x, y) = match self.mode {
-            Mode::Ship => (&mut self.x, &mut self.y),
-            Mode::Waypoint => (&mut self.waypoint_x, &mut self.waypoint_y),
-        };
-
-        match direction {
-            Direction::North => {
-                *y += amount;
-            }
-            Direction::East => {
-                *x += amount;
-            }
-            Direction::South => {
-                *y -= amount;
-            }
-            Direction::West => {
-                *x -= amount;
-            }
+            Mode::Ship => &mut self.state.ship,
+            Mode::Waypoint => &mut self.state.waypoint,
         };
+        *x += dx;
+        *y += dy;
     }
 
-    fn rotate_waypoint_clockwise(&mut self) {
-        let (x, y) = (self.waypoint_y, -self.waypoint_x);
-        self.waypoint_x = x;
-        self.waypoint_y = y;
-    }
-
-    fn turn(&mut self, rotation: &Rotation, amount: i32) {
-        let clockwise_amount = match rotation {
-            Rotation::Right => amount,
-            Rotation::Left => 360 - amount,
-        };
-        let direction = self.direction as i32 + clockwise_amount / 90;
-        for _ in 0..(clockwise_amount / 90) {
-            self.rotate_waypoint_clockwise();
+    fn turn_clockwise(&mut self, degrees: i32) {
+        for _ in 0..(degrees / 90).rem_euclid(4) {
+            let (x, y) = self.state.waypoint;
+            self.state.waypoint = (y, -x);
         }
-        self.direction = Direction::from_i32(direction % 4);
     }
 
-    fn move_forward(&mut self, amount: i32) {
-        match self.mode {
-            Mode::Ship => self.translate(self.direction, amount),
-            Mode::Waypoint => {
-                self.x += self.waypoint_x * amount;
-                self.y += self.waypoint_y * amount;
+    fn apply(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::North(amount) => self.translate(0, amount),
+            Instruction::East(amount) => self.translate(amount, 0),
+            Instruction::South(amount) => self.translate(0, -amount),
+            Instruction::West(amount) => self.translate(-amount, 0),
+            Instruction::Left(degrees) => self.turn_clockwise(-degrees),
+            Instruction::Right(degrees) => self.turn_clockwise(degrees),
+            Instruction::Forward(amount) => {
+                let (x, y) = &mut self.state.ship;
+                *x += self.state.waypoint.0 * amount;
+                *y += self.state.waypoint.1 * amount;
             }
         }
+
+        self.trajectory.push((Some(instruction), self.state));
+    }
+
+    fn get_distance(&self) -> i32 {
+        self.state.ship.0.abs() + self.state.ship.1.abs()
     }
 
-    fn parse_line(&mut self, line: &str) {
-        let amount = line[1..].parse().expect("Failed to parse amount as i32");
-        match line.as_bytes()[0] {
-            b'N' => self.translate(Direction::North, amount),
-            b'E' => self.translate(Direction::East, amount),
-            b'S' => self.translate(Direction::South, amount),
-            b'W' => self.translate(Direction::West, amount),
-            b'L' => self.turn(&Rotation::Left, amount),
-            b'R' => self.turn(&Rotation::Right, amount),
-            b'F' => self.move_forward(amount),
-            _ => panic!("Unexpected prefix {}", line.as_bytes()[0]),
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("step,instruction,ship_x,ship_y,waypoint_x,waypoint_y\n");
+        for (step, (instruction, state)) in self.trajectory.iter().enumerate() {
+            let instruction =
+                instruction.map_or(String::new(), |instruction| instruction.to_string());
+            writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                step, instruction, state.ship.0, state.ship.1, state.waypoint.0, state.waypoint.1
+            )
+            .unwrap();
         }
+        csv
     }
 
-    fn get_distance(&self) -> i32 {
-        self.x.abs() + self.y.abs()
+    // Draws the ship's path, with north at the top
+    fn to_svg(&self) -> String {
+        let points: Vec<_> = self
+            .trajectory
+            .iter()
+            .map(|(_, state)| (state.ship.0, -state.ship.1))
+            .collect();
+        let min_x = points.iter().map(|(x, _)| *x).min().unwrap() - SVG_MARGIN;
+        let max_x = points.iter().map(|(x, _)| *x).max().unwrap() + SVG_MARGIN;
+        let min_y = points.iter().map(|(_, y)| *y).min().unwrap() - SVG_MARGIN;
+        let max_y = points.iter().map(|(_, y)| *y).max().unwrap() + SVG_MARGIN;
+
+        let path: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(index, (x, y))| format!("{} {} {}", if index == 0 { 'M' } else { 'L' }, x, y))
+            .collect();
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n  \
+             <path d=\"{}\" fill=\"none\" stroke=\"black\" vector-effect=\"non-scaling-stroke\"/>\n\
+             </svg>\n",
+            min_x,
+            min_y,
+            max_x - min_x,
+            max_y - min_y,
+            path.join(" ")
+        )
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 || args.len() > 3 {
-        return;
-    }
+    let args = Args::parse();
 
-    let mode = match args[2].as_str() {
-        "ship" => Mode::Ship,
-        "waypoint" => Mode::Waypoint,
-        _ => panic!("Unexpected mode {}", args[2].as_str()),
+    let mode = if args.part == 1 {
+        Mode::Ship
+    } else {
+        Mode::Waypoint
     };
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut navigator = Navigator::new(mode);
 
     let mut line = String::new();
+    let mut line_number = 0;
     loop {
         let bytes = reader
             .read_line(&mut line)
@@ -152,11 +221,121 @@ fn main() {
         if bytes == 0 {
             break;
         }
+        line_number += 1;
 
-        navigator.parse_line(line.trim());
+        if !line.trim().is_empty() {
+            let instruction = line
+                .trim()
+                .parse()
+                .unwrap_or_else(|error| panic!("Line {}: {}", line_number, error));
+            navigator.apply(instruction);
+        }
 
         line.clear();
     }
 
+    if let Some(filename) = &args.trace {
+        let trace = if Path::new(filename)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+        {
+            navigator.to_svg()
+        } else {
+            navigator.to_csv()
+        };
+        fs::write(filename, trace).unwrap_or_else(|_| panic!("Failed to write {}", filename));
+    }
+
     println!("Distance: {}", navigator.get_distance());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Instruction, Mode, Navigator, State};
+
+    const EXAMPLE: [&str; 5] = ["F10", "N3", "F7", "R90", "F11"];
+
+    fn navigate(mode: Mode, lines: &[&str]) -> Navigator {
+        let mut navigator = Navigator::new(mode);
+        for line in lines {
+            navigator.apply(line.parse().unwrap());
+        }
+        navigator
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(navigate(Mode::Ship, &EXAMPLE).get_distance(), 25);
+        assert_eq!(navigate(Mode::Waypoint, &EXAMPLE).get_distance(), 286);
+    }
+
+    #[test]
+    fn turns() {
+        for (first, second) in [
+            ("L90", "R270"),
+            ("L270", "R90"),
+            ("R450", "R90"),
+            ("L0", "R360"),
+            ("L-90", "R90"),
+        ] {
+            assert_eq!(
+                navigate(Mode::Waypoint, &[first]).state,
+                navigate(Mode::Waypoint, &[second]).state
+            );
+            // Turns are shown as written, not normalized
+            assert_eq!(first.parse::<Instruction>().unwrap().to_string(), first);
+        }
+
+        let navigator = navigate(Mode::Waypoint, &["L180"]);
+        assert_eq!(navigator.state.waypoint, (-10, -1));
+    }
+
+    #[test]
+    fn reject_partial_turns() {
+        assert_eq!(
+            "R45".parse::<Instruction>().err().unwrap(),
+            "Turn of 45 degrees is not a multiple of 90 degrees"
+        );
+        assert!("L100".parse::<Instruction>().is_err());
+        assert!("X10".parse::<Instruction>().is_err());
+        assert!("F".parse::<Instruction>().is_err());
+    }
+
+    #[test]
+    fn trajectory() {
+        let navigator = navigate(Mode::Waypoint, &EXAMPLE);
+        assert_eq!(navigator.trajectory.len(), 6);
+        assert_eq!(
+            navigator.trajectory[4].1,
+            State {
+                ship: (170, 38),
+                waypoint: (4, -10)
+            }
+        );
+
+        let csv = navigator.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "step,instruction,ship_x,ship_y,waypoint_x,waypoint_y"
+        );
+        assert_eq!(lines[1], "0,,0,0,10,1");
+        assert_eq!(lines[2], "1,F10,100,10,10,1");
+        assert_eq!(lines[6], "5,F11,214,-72,4,-10");
+
+        let csv = navigate(Mode::Waypoint, &["L90", "R180"]).to_csv();
+        let instructions: Vec<_> = csv
+            .lines()
+            .skip(2)
+            .map(|line| line.split(',').nth(1).unwrap())
+            .collect();
+        assert_eq!(instructions, ["L90", "R180"]);
+    }
+
+    #[test]
+    fn svg_path() {
+        let svg = navigate(Mode::Ship, &EXAMPLE).to_svg();
+        assert!(svg.contains("viewBox=\"-5 -8 27 21\""));
+        assert!(svg.contains("d=\"M 0 0 L 10 0 L 10 -3 L 17 -3 L 17 -3 L 17 8\""));
+    }
+}
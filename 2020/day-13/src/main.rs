@@ -1,21 +1,24 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    convert::TryInto,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{convert::TryInto, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut line = String::new();
 
@@ -47,12 +50,15 @@ fn main() {
         .min_by_key(|(_route, next_arrival)| *next_arrival)
         .expect("Failed to find next arrival");
 
-    println!(
-        "Next arrival {} in {} minutes (product {})",
-        route,
-        next_arrival,
-        route * next_arrival
-    );
+    if args.part == 1 {
+        println!(
+            "Next arrival {} in {} minutes (product {})",
+            route,
+            next_arrival,
+            route * next_arrival
+        );
+        return;
+    }
 
     let mut timestamp = 0;
     let mut skip = 1;
@@ -80,8 +86,8 @@ fn main() {
             timestamp += skip;
         }
 
-        skip *= id
+        skip *= id;
     }
 
-    println!("First subsequent timestamp: {}", timestamp);
+    println!("First subsequent timestamp: {timestamp}");
 }
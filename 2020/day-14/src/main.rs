@@ -1,23 +1,85 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    collections::HashMap,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashMap, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 enum Mode {
     Address,
     Value,
 }
 
+// A set of addresses written as a ternary pattern, where each bit is 0, 1, or floating (X)
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AddressSet {
+    // Value of the fixed bits, always zero where the bit is floating
+    ones: u64,
+    floating: u64,
+}
+
+impl AddressSet {
+    fn new(address: u64, floating: u64) -> Self {
+        Self {
+            ones: address & !floating,
+            floating,
+        }
+    }
+
+    fn len(self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    fn intersects(self, other: Self) -> bool {
+        let fixed_in_both = !self.floating & !other.floating;
+        (self.ones ^ other.ones) & fixed_in_both == 0
+    }
+
+    // Splits the addresses in self but not in other into disjoint sets, one per bit that floats in
+    // self but is fixed in other
+    fn subtract(self, other: Self) -> Vec<Self> {
+        if !self.intersects(other) {
+            return vec![self];
+        }
+
+        let mut remainder = self;
+        let mut pieces = Vec::new();
+        let mut splits = self.floating & !other.floating;
+        while splits != 0 {
+            let bit = splits & splits.wrapping_neg();
+            splits &= !bit;
+
+            remainder.floating &= !bit;
+            pieces.push(Self {
+                ones: remainder.ones | (!other.ones & bit),
+                floating: remainder.floating,
+            });
+            remainder.ones |= other.ones & bit;
+        }
+
+        // Whatever is left of self lies entirely within other
+        pieces
+    }
+}
+
 struct ProgramLoader {
     mode: Mode,
     set_mask: u64,
     clear_mask: u64,
-    floating_bits: Vec<u8>,
+    floating_mask: u64,
     memory: HashMap<u64, u64>,
+    // Disjoint address sets and the value stored at every address in each of them
+    regions: Vec<(AddressSet, u64)>,
 }
 
 impl ProgramLoader {
@@ -28,22 +90,22 @@ impl ProgramLoader {
             mode,
             set_mask: 0,
             clear_mask: 0,
-            floating_bits: Vec::new(),
+            floating_mask: 0,
             memory,
+            regions: Vec::new(),
         }
     }
 
     fn update_masks(&mut self, mask: &str) {
         self.set_mask = 0;
         self.clear_mask = 0;
-        self.floating_bits.clear();
-        let mut index = 36_u8;
+        self.floating_mask = 0;
         for byte in mask.as_bytes() {
             self.set_mask <<= 1;
             self.clear_mask <<= 1;
-            index -= 1;
+            self.floating_mask <<= 1;
             match *byte {
-                b'X' => self.floating_bits.push(index),
+                b'X' => self.floating_mask |= 1,
                 b'0' => self.clear_mask |= 1,
                 b'1' => self.set_mask |= 1,
                 _ => panic!("Unexpected mask byte {}", *byte),
@@ -51,24 +113,15 @@ impl ProgramLoader {
         }
     }
 
-    fn write_value(memory: &mut HashMap<u64, u64>, address: u64, floating_bits: &[u8], value: u64) {
-        if floating_bits.is_empty() {
-            memory.insert(address, value);
-            return;
+    fn write_address_set(&mut self, addresses: AddressSet, value: u64) {
+        let mut regions = Vec::with_capacity(self.regions.len() + 1);
+        for (region, region_value) in self.regions.drain(..) {
+            for piece in region.subtract(addresses) {
+                regions.push((piece, region_value));
+            }
         }
-
-        ProgramLoader::write_value(
-            memory,
-            address | 1_u64 << floating_bits[0],
-            &floating_bits[1..],
-            value,
-        );
-        ProgramLoader::write_value(
-            memory,
-            address & !(1_u64 << floating_bits[0]),
-            &floating_bits[1..],
-            value,
-        );
+        regions.push((addresses, value));
+        self.regions = regions;
     }
 
     fn write_memory(&mut self, line: &str) {
@@ -88,10 +141,8 @@ impl ProgramLoader {
 
         match self.mode {
             Mode::Address => {
-                ProgramLoader::write_value(
-                    &mut self.memory,
-                    address | self.set_mask,
-                    &self.floating_bits,
+                self.write_address_set(
+                    AddressSet::new(address | self.set_mask, self.floating_mask),
                     value,
                 );
                 None
@@ -111,25 +162,27 @@ impl ProgramLoader {
     }
 
     fn get_memory_sum(&self) -> u64 {
-        self.memory.iter().map(|(_, value)| *value).sum()
+        match self.mode {
+            Mode::Address => self
+                .regions
+                .iter()
+                .map(|(region, value)| region.len() * value)
+                .sum(),
+            Mode::Value => self.memory.values().copied().sum(),
+        }
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 || args.len() > 3 {
-        return;
-    }
+    let args = Args::parse();
 
-    let mode = match args[2].as_ref() {
-        "address" => Mode::Address,
-        "value" => Mode::Value,
-        _ => panic!("Unexpected mode {}", args[2]),
+    let mode = if args.part == 1 {
+        Mode::Value
+    } else {
+        Mode::Address
     };
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut loader = ProgramLoader::new(mode);
 
@@ -149,3 +202,70 @@ fn main() {
 
     println!("Sum: {}", loader.get_memory_sum());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressSet, Mode, ProgramLoader};
+
+    fn run_program(mode: Mode, lines: &[&str]) -> u64 {
+        let mut loader = ProgramLoader::new(mode);
+        for line in lines {
+            loader.parse_line(line);
+        }
+        loader.get_memory_sum()
+    }
+
+    #[test]
+    fn value_mode_example() {
+        let lines = [
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X",
+            "mem[8] = 11",
+            "mem[7] = 101",
+            "mem[8] = 0",
+        ];
+        assert_eq!(run_program(Mode::Value, &lines), 165);
+    }
+
+    #[test]
+    fn address_mode_example() {
+        let lines = [
+            "mask = 000000000000000000000000000000X1001X",
+            "mem[42] = 100",
+            "mask = 00000000000000000000000000000000X0XX",
+            "mem[26] = 1",
+        ];
+        assert_eq!(run_program(Mode::Address, &lines), 208);
+    }
+
+    #[test]
+    fn address_mode_with_many_floating_bits() {
+        // Each write covers 2^32 addresses, and the second one overwrites half of the first
+        let lines = [
+            "mask = 0000XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX",
+            "mem[0] = 3",
+            "mask = 0000XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX1",
+            "mem[0] = 5",
+        ];
+        assert_eq!(
+            run_program(Mode::Address, &lines),
+            (1 << 31) * 3 + (1 << 31) * 5
+        );
+    }
+
+    #[test]
+    fn subtract_address_sets() {
+        // 1X0X minus 1XX1 leaves 1X00
+        let set = AddressSet::new(0b1000, 0b0101);
+        let other = AddressSet::new(0b1001, 0b0110);
+        let pieces = set.subtract(other);
+        assert_eq!(pieces, [AddressSet::new(0b1000, 0b0100)]);
+
+        // Disjoint sets are left alone
+        let other = AddressSet::new(0b0000, 0b0111);
+        assert_eq!(set.subtract(other), [set]);
+
+        // A subset disappears entirely
+        let other = AddressSet::new(0b0000, 0b1111);
+        assert!(set.subtract(other).is_empty());
+    }
+}
@@ -1,75 +1,318 @@
 #![deny(clippy::all, clippy::pedantic)]
+#![feature(test)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+extern crate test;
 
-struct MemoryGame {
-    current_turn: u32,
-    previous_number: u32,
-    last_seen: Vec<u32>,
+use std::{collections::HashMap, convert::TryFrom, io::BufRead};
+
+use clap::{Parser, ValueEnum};
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Number of turns to play, overriding the count for the selected part
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    turns: Option<u64>,
+
+    /// How to remember when each number was last spoken
+    #[arg(long, value_enum, default_value_t = Layout::Hybrid)]
+    layout: Layout,
+
+    /// Print every number spoken, not just the last one
+    #[arg(long)]
+    sequence: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layout {
+    /// A hash map from number to turn
+    Sparse,
+    /// A table for small numbers and a hash map for the rest
+    Hybrid,
 }
 
-impl MemoryGame {
-    fn new(initial_numbers: &str, turns: u32) -> Self {
-        let mut current_turn = 1;
-        let mut previous_number = 0;
-        let mut last_seen = vec![0; turns as usize];
+// Most numbers spoken are small, so this covers nearly all of them while keeping the table to
+// 32MB however many turns are played
+const DENSE_LIMIT: u64 = 1 << 23;
 
-        for number in initial_numbers.split(',').map(|number| {
-            number
-                .parse::<u32>()
-                .expect("Failed to parse number as i32")
-        }) {
-            last_seen[number as usize] = current_turn;
-            previous_number = number;
-            current_turn += 1;
+trait Memory {
+    // Records that number was spoken on turn, returning the turn it was previously spoken on
+    fn remember(&mut self, number: u64, turn: u64) -> Option<u64>;
+}
+
+fn to_index(number: u64) -> usize {
+    usize::try_from(number).expect("Failed to fit number in usize")
+}
+
+struct SparseMemory {
+    last_seen: HashMap<u64, u64>,
+}
+
+impl SparseMemory {
+    fn new() -> Self {
+        Self {
+            last_seen: HashMap::new(),
         }
+    }
+}
 
+impl Memory for SparseMemory {
+    fn remember(&mut self, number: u64, turn: u64) -> Option<u64> {
+        self.last_seen.insert(number, turn)
+    }
+}
+
+// A fixed-size table for numbers below its size, with a hash map for the rest. Table entries are
+// u32 turns to halve its size, with 0 meaning not spoken since turns start at 1. Turns past
+// u32::MAX go in the hash map instead.
+struct HybridMemory {
+    dense: Vec<u32>,
+    sparse: HashMap<u64, u64>,
+}
+
+impl HybridMemory {
+    // Numbers below dense_limit go in the table
+    fn new(dense_limit: u64) -> Self {
         Self {
-            current_turn,
-            previous_number,
-            last_seen,
+            dense: vec![0; to_index(dense_limit)],
+            sparse: HashMap::new(),
         }
     }
+}
+
+impl Memory for HybridMemory {
+    fn remember(&mut self, number: u64, turn: u64) -> Option<u64> {
+        let Some(cell) = self.dense.get_mut(to_index(number)) else {
+            return self.sparse.insert(number, turn);
+        };
 
-    fn nth(&mut self, n: u32) -> u32 {
-        while self.current_turn <= n {
-            let current_number = if self.last_seen[self.previous_number as usize] > 0 {
-                self.current_turn - 1 - self.last_seen[self.previous_number as usize]
-            } else {
-                0
-            };
+        let previous = match std::mem::take(cell) {
+            0 => None,
+            previous => Some(u64::from(previous)),
+        };
+        if let Ok(turn) = u32::try_from(turn) {
+            *cell = turn;
+            previous
+        } else {
+            // Once one turn is too big for the table, every later one is too
+            let sparse_previous = self.sparse.insert(number, turn);
+            previous.or(sparse_previous)
+        }
+    }
+}
 
-            self.last_seen[self.previous_number as usize] = self.current_turn - 1;
+// The numbers spoken on each turn, starting from turn 1
+struct MemoryGame<M: Memory> {
+    starting_numbers: Vec<u64>,
+    memory: M,
+    turn: u64,
+    upcoming: u64,
+}
 
-            self.previous_number = current_number;
-            self.current_turn += 1;
+impl<M: Memory> MemoryGame<M> {
+    fn new(starting_numbers: Vec<u64>, memory: M) -> Self {
+        Self {
+            starting_numbers,
+            memory,
+            turn: 0,
+            upcoming: 0,
         }
+    }
 
-        self.previous_number
+    // Plays up to and including the given turn, returning the number spoken on it
+    fn play_until(&mut self, turn: u64) -> u64 {
+        assert!(turn > self.turn, "Turn {} has already been played", turn);
+        let mut number = 0;
+        while self.turn < turn {
+            number = self.next().expect("The game never ends");
+        }
+        number
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        return;
+impl<M: Memory> Iterator for MemoryGame<M> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.turn += 1;
+        let number = match self.starting_numbers.get(to_index(self.turn - 1)) {
+            Some(number) => *number,
+            None => self.upcoming,
+        };
+        self.upcoming = self
+            .memory
+            .remember(number, self.turn)
+            .map_or(0, |previous| self.turn - previous);
+        Some(number)
     }
+}
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+fn parse_numbers(line: &str) -> Vec<u64> {
+    line.split(',')
+        .map(|number| {
+            number
+                .parse()
+                .unwrap_or_else(|_| panic!("Failed to parse {} as u64", number))
+        })
+        .collect()
+}
+
+fn run<M: Memory>(mut game: MemoryGame<M>, turns: u64, sequence: bool) {
+    if sequence {
+        for number in game.by_ref().take(to_index(turns)) {
+            println!("{number}");
+        }
+    } else {
+        println!("nth number: {}", game.play_until(turns));
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut line = String::new();
     reader
         .read_line(&mut line)
         .unwrap_or_else(|_| panic!("Failed to read line"));
+    let numbers = parse_numbers(line.trim());
+
+    let turns = args
+        .turns
+        .unwrap_or(if args.part == 1 { 2020 } else { 30_000_000 });
+    match args.layout {
+        Layout::Sparse => run(
+            MemoryGame::new(numbers, SparseMemory::new()),
+            turns,
+            args.sequence,
+        ),
+        Layout::Hybrid => run(
+            MemoryGame::new(numbers, HybridMemory::new(turns.min(DENSE_LIMIT))),
+            turns,
+            args.sequence,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::Bencher;
 
-    let n: u32 = args[2].parse().expect("Failed to parse n as u32");
+    use super::{parse_numbers, HybridMemory, Memory, MemoryGame, SparseMemory};
+
+    fn play<M: Memory>(numbers: &str, memory: M, turn: u64) -> u64 {
+        MemoryGame::new(parse_numbers(numbers), memory).play_until(turn)
+    }
 
-    let mut game = MemoryGame::new(line.trim(), n);
-    println!("nth number: {}", game.nth(n));
+    #[test]
+    fn examples() {
+        for (numbers, expected) in [
+            ("0,3,6", 436),
+            ("1,3,2", 1),
+            ("2,1,3", 10),
+            ("1,2,3", 27),
+            ("2,3,1", 78),
+            ("3,2,1", 438),
+            ("3,1,2", 1836),
+        ] {
+            assert_eq!(play(numbers, HybridMemory::new(64), 2020), expected);
+        }
+    }
+
+    #[test]
+    #[ignore = "plays 30 million turns"]
+    fn thirty_million_turns() {
+        assert_eq!(
+            play("0,3,6", HybridMemory::new(1 << 20), 30_000_000),
+            175_594
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        let game = MemoryGame::new(parse_numbers("0,3,6"), SparseMemory::new());
+        assert_eq!(
+            game.take(10).collect::<Vec<_>>(),
+            [0, 3, 6, 0, 3, 3, 1, 0, 4, 0]
+        );
+    }
+
+    #[test]
+    fn starting_turns() {
+        assert_eq!(play("0,3,6", SparseMemory::new(), 1), 0);
+        assert_eq!(play("0,3,6", HybridMemory::new(64), 2), 3);
+        assert_eq!(play("0,3,6", HybridMemory::new(64), 3), 6);
+    }
+
+    #[test]
+    fn layouts_agree() {
+        let full_table: Vec<_> =
+            MemoryGame::new(parse_numbers("7,12,1,0,16,2"), HybridMemory::new(100_000))
+                .take(100_000)
+                .collect();
+        let sparse: Vec<_> = MemoryGame::new(parse_numbers("7,12,1,0,16,2"), SparseMemory::new())
+            .take(100_000)
+            .collect();
+        let hybrid: Vec<_> =
+            MemoryGame::new(parse_numbers("7,12,1,0,16,2"), HybridMemory::new(100))
+                .take(100_000)
+                .collect();
+        assert_eq!(full_table, sparse);
+        assert_eq!(full_table, hybrid);
+    }
+
+    #[test]
+    fn turns_past_u32() {
+        let mut memory = HybridMemory::new(16);
+        let turn = u64::from(u32::MAX) + 10;
+        assert_eq!(memory.remember(3, turn), None);
+        assert_eq!(memory.remember(3, turn + 5), Some(turn));
+        assert_eq!(memory.remember(1 << 40, turn), None);
+        assert_eq!(memory.remember(1 << 40, turn + 7), Some(turn));
+    }
+
+    #[test]
+    fn large_starting_numbers() {
+        // Starting numbers can be far bigger than the turn count, so they mustn't size the table
+        assert_eq!(
+            play("4000000000,1,4000000000", HybridMemory::new(10), 10),
+            play("4000000000,1,4000000000", SparseMemory::new(), 10)
+        );
+        assert_eq!(
+            MemoryGame::new(
+                parse_numbers("4000000000,4000000000"),
+                HybridMemory::new(10)
+            )
+            .take(4)
+            .collect::<Vec<_>>(),
+            [4_000_000_000, 4_000_000_000, 1, 0]
+        );
+    }
+
+    const BENCH_TURNS: u64 = 1_000_000;
+
+    // Every number spoken after the starting numbers is below the number of turns, so this table
+    // never falls back to the hash map
+    #[bench]
+    fn bench_full_table(bencher: &mut Bencher) {
+        bencher.iter(|| play("0,3,6", HybridMemory::new(BENCH_TURNS), BENCH_TURNS));
+    }
+
+    #[bench]
+    fn bench_sparse(bencher: &mut Bencher) {
+        bencher.iter(|| play("0,3,6", SparseMemory::new(), BENCH_TURNS));
+    }
+
+    #[bench]
+    fn bench_hybrid(bencher: &mut Bencher) {
+        bencher.iter(|| play("0,3,6", HybridMemory::new(BENCH_TURNS / 8), BENCH_TURNS));
+    }
 }
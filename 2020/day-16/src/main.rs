@@ -1,12 +1,24 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{fmt, io::BufRead};
 
 use bit_set::BitSet;
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Print every field of your ticket instead of the departure product
+    #[arg(long)]
+    decode: bool,
+}
 
 struct Range {
     begin: i32,
@@ -19,6 +31,51 @@ struct Field {
     ranges: Vec<Range>,
 }
 
+enum AssignmentError {
+    // No assignment gives these columns a field
+    Impossible(Vec<usize>),
+    // The column can hold either field, with the remaining columns still assignable in both cases
+    Ambiguous { column: usize, fields: [String; 2] },
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Impossible(columns) => write!(f, "no field fits columns {columns:?}"),
+            Self::Ambiguous { column, fields } => write!(
+                f,
+                "column {} could be either {} or {}",
+                column, fields[0], fields[1]
+            ),
+        }
+    }
+}
+
+// Tries to give column a field, moving previously assigned columns to other fields if necessary.
+// This is one step of Kuhn's augmenting path algorithm for bipartite matching.
+fn augment(
+    possibilities: &[BitSet],
+    column: usize,
+    field_columns: &mut [Option<usize>],
+    visited: &mut BitSet,
+    excluded: Option<(usize, usize)>,
+) -> bool {
+    for field in &possibilities[column] {
+        if excluded == Some((column, field)) || !visited.insert(field) {
+            continue;
+        }
+
+        if field_columns[field].is_none_or(|other| {
+            augment(possibilities, other, field_columns, visited, excluded)
+        }) {
+            field_columns[field] = Some(column);
+            return true;
+        }
+    }
+
+    false
+}
+
 struct TicketValidator {
     fields: Vec<Field>,
 }
@@ -109,64 +166,64 @@ impl TicketValidator {
         possibilities
     }
 
-    fn get_field_name(&self, id: usize) -> String {
-        for field in &self.fields {
-            if field.id == id {
-                return field.name.clone();
-            }
+    // Returns the field ID for each column, as long as there is exactly one way to assign them
+    fn assign_fields(&self, possibilities: &[BitSet]) -> Result<Vec<usize>, AssignmentError> {
+        let mut field_columns = vec![None; self.fields.len()];
+        let unassigned: Vec<usize> = (0..possibilities.len())
+            .filter(|column| {
+                !augment(
+                    possibilities,
+                    *column,
+                    &mut field_columns,
+                    &mut BitSet::new(),
+                    None,
+                )
+            })
+            .collect();
+        if !unassigned.is_empty() {
+            return Err(AssignmentError::Impossible(unassigned));
         }
 
-        String::from("Unknown")
-    }
-}
-
-fn simplify_possibilities(possibilities: &mut Vec<BitSet>) {
-    let mut singletons: Vec<usize> = possibilities
-        .iter()
-        .filter_map(|field_possibilities| {
-            if field_possibilities.len() == 1 {
-                Some(
-                    field_possibilities
-                        .iter()
-                        .next()
-                        .expect("Failed to get only element"),
-                )
-            } else {
-                None
+        let mut column_fields = vec![0; possibilities.len()];
+        for (field, column) in field_columns.iter().enumerate() {
+            if let Some(column) = column {
+                column_fields[*column] = field;
             }
-        })
-        .collect();
+        }
 
-    while !singletons.is_empty() {
-        let singleton = singletons
-            .pop()
-            .expect("Failed to get singleton from non-empty collection");
-
-        for field_possibilities in &mut *possibilities {
-            if field_possibilities.len() > 1 {
-                field_possibilities.remove(singleton);
-                if field_possibilities.len() == 1 {
-                    singletons.push(
-                        field_possibilities
-                            .iter()
-                            .next()
-                            .expect("Failed to get only singleton"),
-                    );
-                }
+        // The assignment is unique unless some column can give up its field and still be assigned
+        for (column, field) in column_fields.iter().enumerate() {
+            let mut alternative = field_columns.clone();
+            alternative[*field] = None;
+            if augment(
+                possibilities,
+                column,
+                &mut alternative,
+                &mut BitSet::new(),
+                Some((column, *field)),
+            ) {
+                let other = alternative
+                    .iter()
+                    .position(|assigned| *assigned == Some(column))
+                    .expect("Failed to find alternative field");
+                return Err(AssignmentError::Ambiguous {
+                    column,
+                    fields: [
+                        self.fields[*field].name.clone(),
+                        self.fields[other].name.clone(),
+                    ],
+                });
             }
         }
+
+        Ok(column_fields)
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut validator = TicketValidator::new();
 
@@ -207,7 +264,8 @@ fn main() {
         .unwrap_or_else(|_| panic!("Failed to read 'nearby tickets' header"));
     line.clear();
 
-    let mut possibilities = Vec::new();
+    // Your ticket is known to be valid, so it narrows the possibilities too
+    let mut possibilities = validator.get_possible_field_ids(&your_ticket);
 
     let mut invalid_sum = 0;
     loop {
@@ -220,8 +278,6 @@ fn main() {
 
         if let Some(ticket_sum) = validator.get_invalid_sum(line.trim()) {
             invalid_sum += ticket_sum;
-        } else if possibilities.is_empty() {
-            possibilities = validator.get_possible_field_ids(line.trim());
         } else {
             let ticket_possibilities = validator.get_possible_field_ids(line.trim());
             for i in 0..possibilities.len() {
@@ -232,31 +288,116 @@ fn main() {
         line.clear();
     }
 
-    simplify_possibilities(&mut possibilities);
+    if args.part == 1 && !args.decode {
+        println!("Invalid sum: {invalid_sum}");
+        return;
+    }
+
+    let assignment = validator
+        .assign_fields(&possibilities)
+        .unwrap_or_else(|error| panic!("Failed to assign fields: {}", error));
 
-    let mut your_values = your_ticket
+    let your_values: Vec<i64> = your_ticket
         .split(',')
-        .map(|field| field.parse::<i64>().expect("Failed to parse field as i64"));
+        .map(|field| field.parse::<i64>().expect("Failed to parse field as i64"))
+        .collect();
 
-    let product: i64 = possibilities
+    if args.decode {
+        for (field, value) in assignment.iter().zip(&your_values) {
+            println!("{}: {}", validator.fields[*field].name, value);
+        }
+        return;
+    }
+
+    let product: i64 = assignment
         .iter()
-        .filter_map(|field_possibilities| {
-            let value = your_values.next().expect("Failed to find field value");
-            let field_name = validator.get_field_name(
-                field_possibilities
-                    .iter()
-                    .next()
-                    .expect("Failed to find only field id"),
-            );
+        .zip(&your_values)
+        .filter(|(field, _)| validator.fields[**field].name.starts_with("departure"))
+        .map(|(_, value)| value)
+        .product();
+
+    println!("Your product: {product}");
+}
 
-            if field_name.len() >= 9 && &field_name[0..9] == "departure" {
-                Some(value)
-            } else {
-                None
+#[cfg(test)]
+mod tests {
+    use bit_set::BitSet;
+
+    use super::{AssignmentError, TicketValidator};
+
+    fn validator(fields: &[&str]) -> TicketValidator {
+        let mut validator = TicketValidator::new();
+        for field in fields {
+            validator.add_field(field);
+        }
+        validator
+    }
+
+    fn possibilities(validator: &TicketValidator, tickets: &[&str]) -> Vec<BitSet> {
+        let mut possibilities = validator.get_possible_field_ids(tickets[0]);
+        for ticket in &tickets[1..] {
+            for (column, ticket_possibilities) in
+                validator.get_possible_field_ids(ticket).iter().enumerate()
+            {
+                possibilities[column].intersect_with(ticket_possibilities);
             }
-        })
-        .product();
+        }
+        possibilities
+    }
+
+    #[test]
+    fn invalid_sum() {
+        let validator = validator(&[
+            "class: 1-3 or 5-7",
+            "row: 6-11 or 33-44",
+            "seat: 13-40 or 45-50",
+        ]);
+        let sum: i32 = ["7,3,47", "40,4,50", "55,2,20", "38,6,12"]
+            .iter()
+            .filter_map(|ticket| validator.get_invalid_sum(ticket))
+            .sum();
+        assert_eq!(sum, 71);
+    }
 
-    println!("Invalid sum: {}", invalid_sum);
-    println!("Your product: {}", product);
+    #[test]
+    fn assign_example() {
+        let validator = validator(&[
+            "class: 0-1 or 4-19",
+            "row: 0-5 or 8-19",
+            "seat: 0-13 or 16-19",
+        ]);
+        let possibilities = possibilities(&validator, &["11,12,13", "3,9,18", "15,1,5", "5,14,9"]);
+        assert_eq!(
+            validator.assign_fields(&possibilities).ok(),
+            Some(vec![1, 0, 2])
+        );
+    }
+
+    #[test]
+    fn assign_ambiguous() {
+        let validator = validator(&[
+            "class: 0-1 or 4-19",
+            "row: 0-5 or 8-19",
+            "seat: 0-13 or 16-19",
+        ]);
+        let possibilities = possibilities(&validator, &["11,12,13", "3,9,18"]);
+        match validator.assign_fields(&possibilities) {
+            Err(AssignmentError::Ambiguous { column, fields }) => {
+                assert_eq!(column, 0);
+                assert!(fields.contains(&String::from("row")));
+            }
+            _ => panic!("Expected an ambiguous assignment"),
+        }
+    }
+
+    #[test]
+    fn assign_impossible() {
+        // Both columns only fit the same field
+        let validator = validator(&["low: 0-5 or 6-9", "high: 10-15 or 16-19"]);
+        let possibilities = possibilities(&validator, &["1,2", "3,4"]);
+        match validator.assign_fields(&possibilities) {
+            Err(AssignmentError::Impossible(columns)) => assert_eq!(columns, [1]),
+            _ => panic!("Expected an impossible assignment"),
+        }
+    }
 }
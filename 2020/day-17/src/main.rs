@@ -1,150 +1,148 @@
 #![deny(clippy::all, clippy::pedantic)]
 
 use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    io::BufRead,
 };
 
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Number of dimensions to simulate, overriding the count for the selected part
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(2..))]
+    dimensions: Option<u8>,
+}
+
+// Cubes are stored as [x, y, extra...], where the initial slice lies at zero in every extra
+// dimension. The rules are symmetric under negating or permuting the extra coordinates, so only one
+// cube from each of those orbits is stored: the one whose extra coordinates are non-negative and
+// sorted.
 struct PocketDimension {
-    dimensions: u32,
-    side_length: usize,
-    margin: usize,
-    cubes: Vec<bool>,
+    active: HashSet<Vec<i32>>,
+    // Every offset to a neighboring cube, in all dimensions
+    neighbor_offsets: Vec<Vec<i32>>,
 }
 
 impl PocketDimension {
-    fn address_helper(side_length: usize, x: usize, y: usize, z: usize, w: usize) -> usize {
-        w * side_length * side_length * side_length
-            + z * side_length * side_length
-            + y * side_length
-            + x
-    }
-
-    fn get_address(&self, x: usize, y: usize, z: usize, w: usize) -> usize {
-        PocketDimension::address_helper(self.side_length, x, y, z, w)
-    }
-
-    fn new(dimensions: u32, iterations: usize, initial_state: &[String]) -> Self {
-        let mut cubes = Vec::new();
-        let margin = iterations + 1;
-        let side_length = initial_state.len() + margin * 2;
-        cubes.resize(side_length * side_length * side_length * side_length, false);
-
-        for y in 0..initial_state.len() {
-            let line = &initial_state[y];
-            for x in 0..initial_state.len() {
-                let cube = match line.as_bytes()[x] {
-                    b'#' => true,
-                    b'.' => false,
-                    _ => panic!("Unexpected byte {}", line.as_bytes()[x]),
-                };
-                let w = match dimensions {
-                    3 => 0,
-                    4 => margin,
-                    _ => panic!("Unexpected dimensionality {}", dimensions),
-                };
-                cubes[PocketDimension::address_helper(side_length, x + margin, y + margin, margin, w)] = cube;
+    fn new(dimensions: usize, initial_state: &[String]) -> Self {
+        assert!(dimensions >= 2, "Unexpected dimensionality {}", dimensions);
+
+        let mut active = HashSet::new();
+        for (y, line) in initial_state.iter().enumerate() {
+            for (x, byte) in line.bytes().enumerate() {
+                match byte {
+                    b'#' => {
+                        let mut cube = vec![0; dimensions];
+                        cube[0] = i32::try_from(x).expect("Row is too long");
+                        cube[1] = i32::try_from(y).expect("Too many rows");
+                        active.insert(cube);
+                    }
+                    b'.' => (),
+                    _ => panic!("Unexpected byte {}", byte),
+                }
             }
         }
 
+        let mut neighbor_offsets = vec![Vec::new()];
+        for _ in 0..dimensions {
+            neighbor_offsets = neighbor_offsets
+                .into_iter()
+                .flat_map(|offset| {
+                    (-1..=1).map(move |delta| {
+                        let mut offset = offset.clone();
+                        offset.push(delta);
+                        offset
+                    })
+                })
+                .collect();
+        }
+        neighbor_offsets.retain(|offset| offset.iter().any(|delta| *delta != 0));
+
         Self {
-            dimensions,
-            side_length,
-            margin,
-            cubes,
+            active,
+            neighbor_offsets,
         }
     }
 
-    fn count_active_neighbors(
-        &self,
-        center_x: usize,
-        center_y: usize,
-        center_z: usize,
-        center_w: usize,
-    ) -> u32 {
-        let mut count = 0;
-
-        let w_range = match self.dimensions {
-            3 => center_w..=center_w,
-            4 => center_w - 1..=center_w + 1,
-            _ => panic!("Unexpected dimensionality {}", self.dimensions),
-        };
-
-        for w in w_range {
-            for z in center_z - 1..=center_z + 1 {
-                for y in center_y - 1..=center_y + 1 {
-                    for x in center_x - 1..=center_x + 1 {
-                        if x == center_x && y == center_y && z == center_z && w == center_w {
-                            continue;
-                        }
-
-                        if self.cubes[self.get_address(x, y, z, w)] {
-                            count += 1;
-                            if count >= 4 {
-                                return count;
-                            }
-                        }
-                    }
-                }
-            }
+    fn canonicalize(mut cube: Vec<i32>) -> Vec<i32> {
+        for coordinate in &mut cube[2..] {
+            *coordinate = coordinate.abs();
         }
+        cube[2..].sort_unstable();
+        cube
+    }
 
-        count
+    // Number of cubes that canonicalize to the given cube: every distinct ordering of the extra
+    // coordinates, with every choice of sign for the non-zero ones
+    fn get_orbit_size(cube: &[i32]) -> u64 {
+        let extra = &cube[2..];
+        let mut size = 1;
+        let mut run_length = 0;
+        for (index, coordinate) in extra.iter().enumerate() {
+            if *coordinate != 0 {
+                size *= 2;
+            }
+            run_length = if index > 0 && extra[index - 1] == *coordinate {
+                run_length + 1
+            } else {
+                1
+            };
+            size = size * (index as u64 + 1) / run_length;
+        }
+        size
     }
 
     fn simulate(&mut self) {
-        let mut changes = Vec::new();
-
-        let range = self.margin - 1..self.side_length - self.margin;
-        self.margin -= 1;
-
-        let w_range = match self.dimensions {
-            3 => 0..1,
-            4 => range.clone(),
-            _ => panic!("Unexpected dimensionality {}", self.dimensions),
-        };
-
-        for w in w_range {
-            for z in range.clone() {
-                for y in range.clone() {
-                    for x in range.clone() {
-                        let address = self.get_address(x, y, z, w);
-                        if self.cubes[address] {
-                            let active_neighbors = self.count_active_neighbors(x, y, z, w);
-                            if !(2..=3).contains(&active_neighbors) {
-                                changes.push(address);
-                            }
-                        } else if self.count_active_neighbors(x, y, z, w) == 3 {
-                            changes.push(address);
-                        }
-                    }
-                }
+        // Summing the orbit sizes of active neighbors over-counts by the size of the target's own
+        // orbit, which is divided back out below
+        let mut weighted_counts = HashMap::new();
+        for cube in &self.active {
+            let weight = PocketDimension::get_orbit_size(cube);
+            for offset in &self.neighbor_offsets {
+                let neighbor = cube
+                    .iter()
+                    .zip(offset)
+                    .map(|(coordinate, delta)| coordinate + delta)
+                    .collect();
+                *weighted_counts
+                    .entry(PocketDimension::canonicalize(neighbor))
+                    .or_insert(0) += weight;
             }
         }
 
-        for change in changes {
-            self.cubes[change] ^= true;
-        }
+        let active = &self.active;
+        self.active = weighted_counts
+            .into_iter()
+            .filter(|(cube, weighted_count)| {
+                let active_neighbors = weighted_count / PocketDimension::get_orbit_size(cube);
+                active_neighbors == 3 || (active_neighbors == 2 && active.contains(cube))
+            })
+            .map(|(cube, _)| cube)
+            .collect();
     }
 
-    fn get_active_count(&self) -> u32 {
-        self.cubes
+    fn get_active_count(&self) -> u64 {
+        self.active
             .iter()
-            .map(|active| if *active { 1 } else { 0 })
+            .map(|cube| PocketDimension::get_orbit_size(cube))
             .sum()
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
     let mut initial_state = Vec::new();
 
@@ -162,12 +160,89 @@ fn main() {
         line.clear();
     }
 
-    let dimensions: u32 = args[2].parse().expect("Failed to parse dimensionality");
+    let dimensions = args
+        .dimensions
+        .unwrap_or(if args.part == 1 { 3 } else { 4 });
 
     let iterations = 6;
-    let mut pocket_dimension = PocketDimension::new(dimensions, iterations, &initial_state);
+    let mut pocket_dimension = PocketDimension::new(usize::from(dimensions), &initial_state);
     for _ in 0..iterations {
         pocket_dimension.simulate();
     }
     println!("Active cubes: {}", pocket_dimension.get_active_count());
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::PocketDimension;
+
+    fn example() -> Vec<String> {
+        [".#.", "..#", "###"]
+            .iter()
+            .map(|line| String::from(*line))
+            .collect()
+    }
+
+    fn run(dimensions: usize, iterations: usize) -> u64 {
+        let mut pocket_dimension = PocketDimension::new(dimensions, &example());
+        for _ in 0..iterations {
+            pocket_dimension.simulate();
+        }
+        pocket_dimension.get_active_count()
+    }
+
+    // Simulates every cube, without folding away the symmetric ones
+    fn run_unfolded(dimensions: usize, iterations: usize) -> u64 {
+        let pocket_dimension = PocketDimension::new(dimensions, &example());
+        let mut active = pocket_dimension.active;
+        for _ in 0..iterations {
+            let mut counts = HashMap::new();
+            for cube in &active {
+                for offset in &pocket_dimension.neighbor_offsets {
+                    let neighbor: Vec<_> = cube.iter().zip(offset).map(|(c, d)| c + d).collect();
+                    *counts.entry(neighbor).or_insert(0) += 1;
+                }
+            }
+            active = counts
+                .into_iter()
+                .filter(|(cube, count)| *count == 3 || (*count == 2 && active.contains(cube)))
+                .map(|(cube, _)| cube)
+                .collect::<HashSet<_>>();
+        }
+        active.len() as u64
+    }
+
+    #[test]
+    fn orbit_size() {
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5]), 1);
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5, 0, 0]), 1);
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5, 0, 1]), 4);
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5, 1, 1]), 4);
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5, 0, 1, 2]), 24);
+        assert_eq!(PocketDimension::get_orbit_size(&[5, 5, 0, 0, 2, 2]), 24);
+    }
+
+    #[test]
+    fn example_3d() {
+        assert_eq!(run(3, 6), 112);
+    }
+
+    #[test]
+    fn example_4d() {
+        assert_eq!(run(4, 6), 848);
+    }
+
+    #[test]
+    fn example_6d() {
+        assert_eq!(run(6, 6), 35936);
+    }
+
+    #[test]
+    fn folded_matches_unfolded() {
+        for dimensions in 2..=5 {
+            assert_eq!(run(dimensions, 3), run_unfolded(dimensions, 3));
+        }
+    }
+}
@@ -1,109 +1,288 @@
 #![deny(clippy::all, clippy::pedantic)]
 
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-#[derive(Clone, Copy, Debug)]
-enum Command {
+use std::{convert::TryFrom, fmt, io::BufRead, str::FromStr};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Operator precedence to use instead of the selected part's, as groups of operators from
+    /// highest to lowest precedence (e.g. "*/ +-"), with an `r` after a group to make it
+    /// right-associative
+    #[arg(long)]
+    precedence: Option<PrecedenceTable>,
+
+    /// Print the parsed expression tree for each line
+    #[arg(long)]
+    ast: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
     Add,
+    Subtract,
     Multiply,
+    Divide,
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Operation {
-    command: Command,
-    value: i64,
+impl Operator {
+    const ALL: [Operator; 4] = [
+        Operator::Add,
+        Operator::Subtract,
+        Operator::Multiply,
+        Operator::Divide,
+    ];
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'+' => Some(Self::Add),
+            b'-' => Some(Self::Subtract),
+            b'*' => Some(Self::Multiply),
+            b'/' => Some(Self::Divide),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            Self::Add => '+',
+            Self::Subtract => '-',
+            Self::Multiply => '*',
+            Self::Divide => '/',
+        }
+    }
+
+    fn apply(self, left: i64, right: i64) -> i64 {
+        match self {
+            Self::Add => left + right,
+            Self::Subtract => left - right,
+            Self::Multiply => left * right,
+            Self::Divide => {
+                assert!(right != 0, "Division by zero");
+                left / right
+            }
+        }
+    }
 }
 
-fn get_next_value(advanced: bool, expression: &str) -> (i64, usize) {
-    match &expression[0..1] {
-        "(" => evaluate_expression(advanced, &expression[1..]),
-        _ => (
-            expression[0..1]
-                .parse()
-                .expect("Failed to parse digit as i64"),
-            1,
-        ),
-    }
-}
-
-fn flatten_operations(advanced: bool, operations: &[Operation]) -> i64 {
-    if advanced {
-        let mut reduced = Vec::new();
-        reduced.push(operations[0]);
-        for operation in operations.iter().skip(1) {
-            match operation.command {
-                Command::Add => {
-                    reduced
-                        .last_mut()
-                        .expect("Failed to get last reduced element")
-                        .value += operation.value
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Associativity {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PrecedenceTable {
+    // Indexed by Operator, higher levels bind more tightly
+    levels: [(u8, Associativity); 4],
+}
+
+impl PrecedenceTable {
+    // Part 1: every operator has the same precedence, evaluated left to right
+    fn new_math() -> Self {
+        "+-*/".parse().unwrap()
+    }
+
+    // Part 2: addition and subtraction bind more tightly than multiplication and division
+    fn advanced_math() -> Self {
+        "+- */".parse().unwrap()
+    }
+
+    fn get(&self, operator: Operator) -> (u8, Associativity) {
+        self.levels[operator as usize]
+    }
+}
+
+impl FromStr for PrecedenceTable {
+    type Err = String;
+
+    fn from_str(table: &str) -> Result<Self, Self::Err> {
+        let groups: Vec<_> = table.split_whitespace().collect();
+        let mut levels = [None; 4];
+        for (index, group) in groups.iter().enumerate() {
+            // Leave level 0 free to mean "stop parsing"
+            let level = u8::try_from(groups.len() - index).map_err(|_| "Too many groups")?;
+            let (operators, associativity) = match group.strip_suffix('r') {
+                Some(operators) => (operators, Associativity::Right),
+                None => (*group, Associativity::Left),
+            };
+            for byte in operators.bytes() {
+                let operator = Operator::from_byte(byte)
+                    .ok_or_else(|| format!("Unexpected operator '{}'", char::from(byte)))?;
+                if levels[operator as usize].is_some() {
+                    return Err(format!("Operator '{}' appears twice", operator.symbol()));
                 }
-                Command::Multiply => reduced.push(*operation),
+                levels[operator as usize] = Some((level, associativity));
             }
         }
 
-        return flatten_operations(false, &reduced);
+        let mut table = [(0, Associativity::Left); 4];
+        for operator in Operator::ALL {
+            table[operator as usize] = levels[operator as usize]
+                .ok_or_else(|| format!("Missing operator '{}'", operator.symbol()))?;
+        }
+        Ok(Self { levels: table })
     }
+}
 
-    let result = operations
-        .iter()
-        .fold(0, |value, operation| match operation.command {
-            Command::Add => value + operation.value,
-            Command::Multiply => value * operation.value,
-        });
-
-    result
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Operator(Operator),
+    LeftParenthesis,
+    RightParenthesis,
 }
 
-fn evaluate_expression(advanced: bool, expression: &str) -> (i64, usize) {
+fn tokenize(expression: &str) -> Vec<Token> {
+    let bytes = expression.as_bytes();
+    let mut tokens = Vec::new();
     let mut cursor = 0;
-    let mut operations = Vec::new();
-
-    let (value, advance) = get_next_value(advanced, expression);
-    operations.push(Operation {
-        command: Command::Add,
-        value,
-    });
-    cursor += advance;
-
-    while cursor < expression.len() {
-        if &expression[cursor..=cursor] == ")" {
-            return (flatten_operations(advanced, &operations), cursor + 2);
-        }
-
-        let command = match &expression[cursor..cursor + 3] {
-            " + " => Command::Add,
-            " * " => Command::Multiply,
-            _ => panic!(
-                "Unexpected continuation [{}]",
-                &expression[cursor..cursor + 3]
-            ),
+    while cursor < bytes.len() {
+        let byte = bytes[cursor];
+        if byte.is_ascii_digit() {
+            let end = bytes[cursor..]
+                .iter()
+                .position(|byte| !byte.is_ascii_digit())
+                .map_or(bytes.len(), |length| cursor + length);
+            let number = expression[cursor..end]
+                .parse()
+                .unwrap_or_else(|_| panic!("Failed to parse {} as i64", &expression[cursor..end]));
+            tokens.push(Token::Number(number));
+            cursor = end;
+            continue;
+        }
+
+        match byte {
+            b'(' => tokens.push(Token::LeftParenthesis),
+            b')' => tokens.push(Token::RightParenthesis),
+            b' ' | b'\t' => (),
+            _ => tokens.push(Token::Operator(Operator::from_byte(byte).unwrap_or_else(
+                || panic!("Unexpected character '{}'", char::from(byte)),
+            ))),
+        }
+        cursor += 1;
+    }
+    tokens
+}
+
+#[derive(Debug, PartialEq)]
+enum Expression {
+    Number(i64),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self) -> i64 {
+        match self {
+            Self::Number(value) => *value,
+            Self::Negate(operand) => -operand.evaluate(),
+            Self::Binary(operator, left, right) => {
+                operator.apply(left.evaluate(), right.evaluate())
+            }
+        }
+    }
+}
+
+// Prints the tree as an S-expression, so the grouping chosen by the parser is explicit
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Negate(operand) => write!(f, "(- {operand})"),
+            Self::Binary(operator, left, right) => {
+                write!(f, "({} {} {})", operator.symbol(), left, right)
+            }
+        }
+    }
+}
+
+// Pratt parser: each call consumes operators that bind at least as tightly as min_level
+struct ExpressionParser<'a> {
+    tokens: Vec<Token>,
+    cursor: usize,
+    table: &'a PrecedenceTable,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn parse(expression: &str, table: &'a PrecedenceTable) -> Expression {
+        let mut parser = Self {
+            tokens: tokenize(expression),
+            cursor: 0,
+            table,
         };
+        let result = parser.parse_expression(1);
+        if let Some(token) = parser.tokens.get(parser.cursor) {
+            panic!("Unexpected {:?} in [{}]", token, expression);
+        }
+        result
+    }
 
-        let (value, advance) = get_next_value(advanced, &expression[cursor + 3..]);
-        cursor += 3 + advance;
-        operations.push(Operation { command, value })
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.cursor).copied();
+        self.cursor += 1;
+        token
     }
 
-    (flatten_operations(advanced, &operations), cursor)
+    fn parse_operand(&mut self) -> Expression {
+        match self.next() {
+            Some(Token::Number(value)) => Expression::Number(value),
+            // Unary minus binds more tightly than any binary operator
+            Some(Token::Operator(Operator::Subtract)) => {
+                Expression::Negate(Box::new(self.parse_operand()))
+            }
+            Some(Token::LeftParenthesis) => {
+                let inner = self.parse_expression(1);
+                match self.next() {
+                    Some(Token::RightParenthesis) => inner,
+                    token => panic!("Expected ')' but found {:?}", token),
+                }
+            }
+            token => panic!("Expected an operand but found {:?}", token),
+        }
+    }
+
+    fn parse_expression(&mut self, min_level: u8) -> Expression {
+        let mut left = self.parse_operand();
+
+        while let Some(Token::Operator(operator)) = self.tokens.get(self.cursor).copied() {
+            let (level, associativity) = self.table.get(operator);
+            if level < min_level {
+                break;
+            }
+            self.cursor += 1;
+
+            let right = match associativity {
+                Associativity::Left => self.parse_expression(level + 1),
+                Associativity::Right => self.parse_expression(level),
+            };
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+
+        left
+    }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let table = match &args.precedence {
+        Some(table) => table.clone(),
+        None if args.part == 1 => PrecedenceTable::new_math(),
+        None => PrecedenceTable::advanced_math(),
+    };
 
-    let mut new_math_sum = 0;
-    let mut advanced_math_sum = 0;
+    let mut reader = open_input(args.filename.as_deref());
+
+    let mut sum = 0;
 
     let mut line = String::new();
     loop {
@@ -114,18 +293,98 @@ fn main() {
             break;
         }
 
-        {
-            let (value, _) = evaluate_expression(false, line.trim());
-            new_math_sum += value;
-        }
-        {
-            let (value, _) = evaluate_expression(true, line.trim());
-            advanced_math_sum += value;
+        if !line.trim().is_empty() {
+            let expression = ExpressionParser::parse(line.trim(), &table);
+            if args.ast {
+                println!("{expression}");
+            }
+            sum += expression.evaluate();
         }
 
         line.clear();
     }
 
-    println!("New math sum: {}", new_math_sum);
-    println!("Advanced math sum: {}", advanced_math_sum);
+    if args.precedence.is_some() {
+        println!("Sum: {sum}");
+    } else if args.part == 1 {
+        println!("New math sum: {sum}");
+    } else {
+        println!("Advanced math sum: {sum}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExpressionParser, PrecedenceTable};
+
+    fn evaluate(expression: &str, table: &str) -> i64 {
+        ExpressionParser::parse(expression, &table.parse().unwrap()).evaluate()
+    }
+
+    #[test]
+    fn new_math_examples() {
+        let table = PrecedenceTable::new_math();
+        for (expression, value) in [
+            ("1 + 2 * 3 + 4 * 5 + 6", 71),
+            ("1 + (2 * 3) + (4 * (5 + 6))", 51),
+            ("2 * 3 + (4 * 5)", 26),
+            ("5 + (8 * 3 + 9 + 3 * 4 * 3)", 437),
+            ("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", 12240),
+            ("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", 13632),
+        ] {
+            assert_eq!(
+                ExpressionParser::parse(expression, &table).evaluate(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn advanced_math_examples() {
+        let table = PrecedenceTable::advanced_math();
+        for (expression, value) in [
+            ("1 + 2 * 3 + 4 * 5 + 6", 231),
+            ("1 + (2 * 3) + (4 * (5 + 6))", 51),
+            ("2 * 3 + (4 * 5)", 46),
+            ("5 + (8 * 3 + 9 + 3 * 4 * 3)", 1445),
+            ("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", 669_060),
+            ("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", 23340),
+        ] {
+            assert_eq!(
+                ExpressionParser::parse(expression, &table).evaluate(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn standard_math() {
+        assert_eq!(evaluate("12 + 34 * 2", "*/ +-"), 80);
+        assert_eq!(evaluate("10 - 4 - 3", "*/ +-"), 3);
+        assert_eq!(evaluate("100 / 7 / 2", "*/ +-"), 7);
+        assert_eq!(evaluate("-2 * -(3 + 4)", "*/ +-"), 14);
+        assert_eq!(evaluate("2 - -3", "*/ +-"), 5);
+    }
+
+    #[test]
+    fn right_associative() {
+        assert_eq!(evaluate("10 - 4 - 3", "*/ +-r"), 9);
+        assert_eq!(evaluate("64 / 8 / 2", "*/r +-"), 16);
+    }
+
+    #[test]
+    fn print_ast() {
+        let table = PrecedenceTable::advanced_math();
+        assert_eq!(
+            ExpressionParser::parse("2 * 3 + -(4 * 5)", &table).to_string(),
+            "(* 2 (+ 3 (- (* 4 5))))"
+        );
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert!("+-*".parse::<PrecedenceTable>().is_err());
+        assert!("+-*/ +".parse::<PrecedenceTable>().is_err());
+        assert!("+-*/^".parse::<PrecedenceTable>().is_err());
+    }
 }
@@ -1,127 +1,370 @@
 #![deny(clippy::all, clippy::pedantic)]
 
 use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    collections::{HashMap, HashSet},
+    fmt,
+    io::BufRead,
 };
 
-#[derive(Clone, Debug)]
-enum Rule {
-    Indirect(Vec<Vec<u8>>),
-    Direct(String),
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Print the parse tree of each valid message and where each invalid one fails
+    #[arg(long)]
+    explain: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Symbol {
+    Rule(u32),
+    Terminal(String),
+}
+
+// An Earley item: a rule alternative, how much of it has been matched, and where the match began
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Item {
+    rule: u32,
+    alternative: usize,
+    dot: usize,
+    origin: usize,
+}
+
+impl Item {
+    fn advance(self) -> Self {
+        Self {
+            dot: self.dot + 1,
+            ..self
+        }
+    }
+}
+
+// Earley sets, one per position in the message, holding the items that end at that position
+struct Chart {
+    sets: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+}
+
+impl Chart {
+    fn new(length: usize) -> Self {
+        Self {
+            sets: vec![Vec::new(); length + 1],
+            seen: vec![HashSet::new(); length + 1],
+        }
+    }
+
+    fn add(&mut self, position: usize, item: Item) {
+        if self.seen[position].insert(item) {
+            self.sets[position].push(item);
+        }
+    }
+
+    fn contains(&self, position: usize, item: Item) -> bool {
+        self.seen[position].contains(&item)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseTree {
+    Rule(u32, Vec<ParseTree>),
+    Terminal(String),
+}
+
+impl ParseTree {
+    fn write_indented(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        match self {
+            Self::Rule(id, children) => {
+                writeln!(f, "{:indent$}{}", "", id, indent = depth * 2)?;
+                for child in children {
+                    child.write_indented(f, depth + 1)?;
+                }
+                Ok(())
+            }
+            Self::Terminal(terminal) => {
+                writeln!(f, "{:indent$}\"{}\"", "", terminal, indent = depth * 2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+// Where parsing of an invalid message got stuck, and which rules were hoping for which terminals
+#[derive(Debug, PartialEq)]
+struct Failure {
+    position: usize,
+    found: Option<char>,
+    expected: Vec<(u32, String)>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found {
+            Some(found) => write!(f, "unexpected '{}' at position {}", found, self.position)?,
+            None => write!(f, "unexpected end of message at position {}", self.position)?,
+        }
+        for (index, (rule, terminal)) in self.expected.iter().enumerate() {
+            let separator = if index == 0 { ", expected" } else { " or" };
+            write!(f, "{separator} \"{terminal}\" (rule {rule})")?;
+        }
+        Ok(())
+    }
 }
 
 struct MessageValidator {
-    rules: Vec<Rule>,
+    rules: HashMap<u32, Vec<Vec<Symbol>>>,
+    loops: bool,
 }
 
 impl MessageValidator {
-    fn new() -> Self {
-        let mut rules = Vec::new();
-        rules.resize(256, Rule::Indirect(Vec::new()));
-        Self { rules }
+    const START: u32 = 0;
+
+    fn new(loops: bool) -> Self {
+        Self {
+            rules: HashMap::new(),
+            loops,
+        }
     }
 
-    fn parse_indirect(indirect: &str) -> Vec<Vec<u8>> {
-        let split = indirect.split(" | ");
-        let indirect: Vec<Vec<u8>> = split
+    fn parse_alternatives(alternatives: &str) -> Vec<Vec<Symbol>> {
+        alternatives
+            .split('|')
             .map(|alternative| {
-                let alternative = alternative.trim();
-                let split = alternative.split(' ');
-                let children: Vec<u8> = split
-                    .map(|child| child.parse::<u8>().expect("Failed to fit child in u8"))
+                let symbols: Vec<_> = alternative
+                    .split_whitespace()
+                    .map(|symbol| {
+                        if symbol.starts_with('"') {
+                            let terminal = symbol.trim_matches('"');
+                            assert!(!terminal.is_empty(), "Empty terminal in [{}]", alternatives);
+                            Symbol::Terminal(String::from(terminal))
+                        } else {
+                            Symbol::Rule(symbol.parse().expect("Failed to parse rule ID"))
+                        }
+                    })
                     .collect();
-                children
+                assert!(
+                    !symbols.is_empty(),
+                    "Empty alternative in [{}]",
+                    alternatives
+                );
+                symbols
             })
-            .collect();
-        indirect
+            .collect()
     }
 
     fn add_rule(&mut self, rule: &str) {
         let mut split = rule.split(':');
 
-        let id: u8 = split
+        let id: u32 = split
             .next()
             .expect("Failed to find ID in split")
             .parse()
             .expect("Failed to parse rule ID");
 
-        if id == 8 {
-            self.rules[8] = Rule::Indirect(vec![vec![42], vec![42, 8]]);
-            return;
-        } else if id == 11 {
-            self.rules[11] = Rule::Indirect(vec![vec![42, 31], vec![42, 11, 31]]);
-            return;
-        }
-
-        let contents = split.next().expect("Failed to find rule").trim();
-        self.rules[id as usize] = match &contents[0..=0] {
-            "\"" => Rule::Direct(String::from(&contents[1..=1])),
-            _ => Rule::Indirect(MessageValidator::parse_indirect(&contents[..])),
+        let contents = match id {
+            8 if self.loops => "42 | 42 8",
+            11 if self.loops => "42 31 | 42 11 31",
+            _ => split.next().expect("Failed to find rule").trim(),
         };
+        self.rules
+            .insert(id, MessageValidator::parse_alternatives(contents));
+    }
+
+    fn alternatives(&self, rule: u32) -> &[Vec<Symbol>] {
+        self.rules
+            .get(&rule)
+            .unwrap_or_else(|| panic!("Rule {} is not defined", rule))
+    }
+
+    fn next_symbol(&self, item: Item) -> Option<&Symbol> {
+        self.alternatives(item.rule)[item.alternative].get(item.dot)
     }
 
-    fn message_matches_rule(&self, rule: &Rule, message: &str) -> Vec<usize> {
-        if message.is_empty() {
-            return Vec::new();
+    fn predict(&self, chart: &mut Chart, position: usize, rule: u32) {
+        for alternative in 0..self.alternatives(rule).len() {
+            chart.add(
+                position,
+                Item {
+                    rule,
+                    alternative,
+                    dot: 0,
+                    origin: position,
+                },
+            );
         }
+    }
 
-        match rule {
-            Rule::Direct(string) => {
-                if &message[0..string.len()] == string {
-                    vec![1]
-                } else {
-                    Vec::new()
-                }
-            }
-            Rule::Indirect(alternatives) => {
-                let mut lengths = Vec::new();
-
-                for alternative in alternatives {
-                    let mut cursors = vec![0];
-                    for child_id in alternative {
-                        let mut new_cursors = Vec::new();
-                        for cursor in cursors {
-                            let lengths = self.message_matches_rule(
-                                &self.rules[(*child_id) as usize],
-                                &message[cursor..],
-                            );
-                            for length in lengths {
-                                new_cursors.push(cursor + length);
-                            }
+    // Earley recognition. Alternatives and terminals are never empty, so a completed item always
+    // starts in an earlier set than the one it ends in, and no nullable special cases are needed.
+    fn build_chart(&self, message: &[u8]) -> Chart {
+        let mut chart = Chart::new(message.len());
+        self.predict(&mut chart, 0, MessageValidator::START);
+
+        for position in 0..=message.len() {
+            let mut index = 0;
+            while index < chart.sets[position].len() {
+                let item = chart.sets[position][index];
+                index += 1;
+
+                match self.next_symbol(item) {
+                    Some(Symbol::Rule(rule)) => self.predict(&mut chart, position, *rule),
+                    Some(Symbol::Terminal(terminal)) => {
+                        if message[position..].starts_with(terminal.as_bytes()) {
+                            chart.add(position + terminal.len(), item.advance());
                         }
-                        cursors = new_cursors;
-                        if cursors.is_empty() {
-                            break;
+                    }
+                    None => {
+                        for parent_index in 0..chart.sets[item.origin].len() {
+                            let parent = chart.sets[item.origin][parent_index];
+                            if self.next_symbol(parent) == Some(&Symbol::Rule(item.rule)) {
+                                chart.add(position, parent.advance());
+                            }
                         }
                     }
-
-                    lengths.append(&mut cursors);
                 }
-
-                lengths
             }
         }
+
+        chart
+    }
+
+    fn is_complete(&self, item: Item) -> bool {
+        item.dot == self.alternatives(item.rule)[item.alternative].len()
     }
 
     fn message_is_valid(&self, message: &str) -> bool {
-        let match_lengths = self.message_matches_rule(&self.rules[0], message);
-        match_lengths.iter().any(|length| *length == message.len())
+        let chart = self.build_chart(message.as_bytes());
+        chart.sets[message.len()].iter().any(|item| {
+            item.rule == MessageValidator::START && item.origin == 0 && self.is_complete(*item)
+        })
+    }
+
+    // Finds a derivation of rule over message[start..end]. Derivations that revisit a span they
+    // are already inside are skipped, so cyclic rules like "1: 2 | 3" and "2: 1" still terminate.
+    fn build_tree(
+        &self,
+        chart: &Chart,
+        message: &[u8],
+        rule: u32,
+        (start, end): (usize, usize),
+        in_progress: &mut HashSet<(u32, usize, usize)>,
+    ) -> Option<ParseTree> {
+        if !in_progress.insert((rule, start, end)) {
+            return None;
+        }
+
+        let tree = chart.sets[end]
+            .iter()
+            .filter(|item| item.rule == rule && item.origin == start && self.is_complete(**item))
+            .find_map(|item| {
+                self.build_children(chart, message, *item, (start, end), in_progress)
+                    .map(|children| ParseTree::Rule(rule, children))
+            });
+
+        in_progress.remove(&(rule, start, end));
+        tree
+    }
+
+    // Finds children for the symbols before the item's dot, spanning message[start..end]
+    fn build_children(
+        &self,
+        chart: &Chart,
+        message: &[u8],
+        item: Item,
+        (start, end): (usize, usize),
+        in_progress: &mut HashSet<(u32, usize, usize)>,
+    ) -> Option<Vec<ParseTree>> {
+        if item.dot == 0 {
+            return if start == end { Some(Vec::new()) } else { None };
+        }
+
+        let previous = Item {
+            dot: item.dot - 1,
+            ..item
+        };
+        match &self.alternatives(item.rule)[item.alternative][previous.dot] {
+            Symbol::Terminal(terminal) => {
+                let middle = end.checked_sub(terminal.len())?;
+                if middle < start
+                    || &message[middle..end] != terminal.as_bytes()
+                    || !chart.contains(middle, previous)
+                {
+                    return None;
+                }
+                let mut children =
+                    self.build_children(chart, message, previous, (start, middle), in_progress)?;
+                children.push(ParseTree::Terminal(terminal.clone()));
+                Some(children)
+            }
+            Symbol::Rule(child) => (start..end).find_map(|middle| {
+                if !chart.contains(middle, previous) {
+                    return None;
+                }
+                let child_tree =
+                    self.build_tree(chart, message, *child, (middle, end), in_progress)?;
+                let mut children =
+                    self.build_children(chart, message, previous, (start, middle), in_progress)?;
+                children.push(child_tree);
+                Some(children)
+            }),
+        }
+    }
+
+    fn explain_failure(&self, chart: &Chart, message: &str) -> Failure {
+        let position = (0..chart.sets.len())
+            .rev()
+            .find(|position| !chart.sets[*position].is_empty())
+            .expect("Failed to find any Earley items");
+
+        let mut expected: Vec<_> = chart.sets[position]
+            .iter()
+            .filter_map(|item| match self.next_symbol(*item) {
+                Some(Symbol::Terminal(terminal)) => Some((item.rule, terminal.clone())),
+                _ => None,
+            })
+            .collect();
+        expected.sort_unstable();
+        expected.dedup();
+
+        Failure {
+            position,
+            found: message[position..].chars().next(),
+            expected,
+        }
+    }
+
+    fn parse(&self, message: &str) -> Result<ParseTree, Failure> {
+        let bytes = message.as_bytes();
+        let chart = self.build_chart(bytes);
+        self.build_tree(
+            &chart,
+            bytes,
+            MessageValidator::START,
+            (0, bytes.len()),
+            &mut HashSet::new(),
+        )
+        .ok_or_else(|| self.explain_failure(&chart, message))
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return;
-    }
+    let args = Args::parse();
 
-    let filename = &args[1];
-    let file = File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {}", filename));
-    let mut reader = BufReader::new(file);
+    let mut reader = open_input(args.filename.as_deref());
 
-    let mut validator = MessageValidator::new();
+    let mut validator = MessageValidator::new(args.part == 2);
 
     let mut line = String::new();
     loop {
@@ -154,13 +397,205 @@ fn main() {
         }
 
         let message = line.trim();
-        let valid = validator.message_is_valid(message);
-        if valid {
+        if args.explain {
+            match validator.parse(message) {
+                Ok(tree) => {
+                    valid_messages += 1;
+                    print!("{message}: valid\n{tree}");
+                }
+                Err(failure) => println!("{message}: invalid, {failure}"),
+            }
+        } else if validator.message_is_valid(message) {
             valid_messages += 1;
         }
 
         line.clear();
     }
 
-    println!("{} valid messages", valid_messages);
+    println!("{valid_messages} valid messages");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Failure, MessageValidator, ParseTree};
+
+    const EXAMPLE: &str = r#"0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: "a"
+5: "b""#;
+
+    const LOOP_EXAMPLE: &str = r#"42: 9 14 | 10 1
+9: 14 27 | 1 26
+10: 23 14 | 28 1
+1: "a"
+11: 42 31
+5: 1 14 | 15 1
+19: 14 1 | 14 14
+12: 24 14 | 19 1
+16: 15 1 | 14 14
+31: 14 17 | 1 13
+6: 14 14 | 1 14
+2: 1 24 | 14 4
+0: 8 11
+13: 14 3 | 1 12
+15: 1 | 14
+17: 14 2 | 1 7
+23: 25 1 | 22 14
+28: 16 1
+4: 1 1
+20: 14 14 | 1 15
+3: 5 14 | 16 1
+27: 1 6 | 14 18
+14: "b"
+21: 14 1 | 1 14
+25: 1 1 | 1 14
+22: 14 14
+8: 42
+26: 14 22 | 1 20
+18: 15 15
+7: 14 5 | 1 21
+24: 14 1"#;
+
+    const LOOP_MESSAGES: [&str; 15] = [
+        "abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa",
+        "bbabbbbaabaabba",
+        "babbbbaabbbbbabbbbbbaabaaabaaa",
+        "aaabbbbbbaaaabaababaabababbabaaabbababababaaa",
+        "bbbbbbbaaaabbbbaaabbabaaa",
+        "bbbababbbbaaaaaaaabbababaaababaabab",
+        "ababaaaaaabaaab",
+        "ababaaaaabbbaba",
+        "baabbaaaabbaaaababbaababb",
+        "abbbbabbbbaaaababbbbbbaaaababb",
+        "aaaaabbaabaaaaababaa",
+        "aaaabbaaaabbaaa",
+        "aaaabbaabbaaaaaaabbbabbbaaabbaabaaa",
+        "babaaabbbaaabaababbaabababaaab",
+        "aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba",
+    ];
+
+    fn validator(rules: &str, loops: bool) -> MessageValidator {
+        let mut validator = MessageValidator::new(loops);
+        for rule in rules.lines() {
+            validator.add_rule(rule);
+        }
+        validator
+    }
+
+    fn count_valid(validator: &MessageValidator, messages: &[&str]) -> usize {
+        messages
+            .iter()
+            .filter(|message| validator.message_is_valid(message))
+            .count()
+    }
+
+    #[test]
+    fn example() {
+        let validator = validator(EXAMPLE, false);
+        let messages = ["ababbb", "bababa", "abbbab", "aaabbb", "aaaabbb"];
+        assert_eq!(count_valid(&validator, &messages), 2);
+    }
+
+    #[test]
+    fn loop_example() {
+        assert_eq!(
+            count_valid(&validator(LOOP_EXAMPLE, false), &LOOP_MESSAGES),
+            3
+        );
+        assert_eq!(
+            count_valid(&validator(LOOP_EXAMPLE, true), &LOOP_MESSAGES),
+            12
+        );
+    }
+
+    #[test]
+    fn short_messages() {
+        let validator = validator(r#"0: "abc" | "abc" 0"#, false);
+        assert!(!validator.message_is_valid(""));
+        assert!(!validator.message_is_valid("ab"));
+        assert!(validator.message_is_valid("abc"));
+        assert!(validator.message_is_valid("abcabc"));
+        assert!(!validator.message_is_valid("abcab"));
+    }
+
+    #[test]
+    fn ambiguous_left_recursion() {
+        // Sums of ones, which can be grouped in many ways
+        let validator = validator(
+            r#"0: 0 "+" 0 | 1
+1: "1""#,
+            false,
+        );
+        assert!(validator.message_is_valid("1+1+1+1"));
+        assert!(!validator.message_is_valid("1+1+"));
+        assert!(validator.parse("1+1+1").is_ok());
+    }
+
+    #[test]
+    fn unit_cycle() {
+        let validator = validator(
+            r#"0: 1 | "x"
+1: 0 | "y""#,
+            false,
+        );
+        assert_eq!(
+            validator.parse("y"),
+            Ok(ParseTree::Rule(
+                0,
+                vec![ParseTree::Rule(
+                    1,
+                    vec![ParseTree::Terminal(String::from("y"))]
+                )]
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_tree() {
+        let validator = validator(EXAMPLE, false);
+        let tree = validator.parse("ababbb").unwrap();
+        assert_eq!(
+            tree.to_string(),
+            r#"0
+  4
+    "a"
+  1
+    3
+      5
+        "b"
+      4
+        "a"
+    2
+      5
+        "b"
+      5
+        "b"
+  5
+    "b"
+"#
+        );
+    }
+
+    #[test]
+    fn failure() {
+        let validator = validator(EXAMPLE, false);
+        assert_eq!(
+            validator.parse("aaab"),
+            Err(Failure {
+                position: 4,
+                found: None,
+                expected: vec![(4, String::from("a"))],
+            })
+        );
+        assert_eq!(
+            validator.parse("ababbbb").unwrap_err().to_string(),
+            "unexpected 'b' at position 6"
+        );
+        assert_eq!(
+            validator.parse("bababa").unwrap_err().to_string(),
+            "unexpected 'b' at position 0, expected \"a\" (rule 4)"
+        );
+    }
 }
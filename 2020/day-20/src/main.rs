@@ -5,11 +5,39 @@
 extern crate bitflags;
 extern crate test;
 
-use std::{collections::HashMap, convert::TryInto};
-
-use clap::{crate_name, App, Arg};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+};
+
+use clap::Parser;
 use common::LineReader;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// File containing the pattern to search for, instead of the sea monster
+    #[arg(long)]
+    pattern: Option<String>,
+
+    /// Print the location and orientation of every pattern match
+    #[arg(long)]
+    list_matches: bool,
+
+    /// File to write the assembled image to, as PNG if it ends in .png and as text otherwise
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
 bitflags! {
     struct Transform: u8 {
         const ROTATE_90 = 1 << 0;
@@ -18,8 +46,14 @@ bitflags! {
     }
 }
 
-const TILE_SIZE: usize = 10;
-const IMAGE_SIZE: usize = TILE_SIZE - 2;
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+// Each character of the image becomes a square of this many pixels in PNG output
+const PNG_SCALE: usize = 4;
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum Side {
@@ -46,7 +80,7 @@ struct Tile {
     id: u16,
     image: Vec<Vec<u8>>,
     // Stored LTRB, horizontal L->R, vertical T->B
-    sides: [[u8; TILE_SIZE]; 4],
+    sides: [Vec<u8>; 4],
     sides_with_neighbors: Vec<Side>,
 }
 
@@ -60,28 +94,25 @@ impl Tile {
             .parse()
             .expect("Failed to parse ID as i16");
 
-        let mut image = vec![vec![b' '; IMAGE_SIZE]; IMAGE_SIZE];
+        let size = lines.len() - 1;
+        let image_size = size - 2;
+        let mut image = Vec::with_capacity(image_size);
 
-        let mut left = [b'*'; TILE_SIZE];
-        let mut right = [b'*'; TILE_SIZE];
+        let mut left = Vec::with_capacity(size);
+        let mut right = Vec::with_capacity(size);
         for (row, line) in lines.iter().skip(1).enumerate() {
             let bytes = line.as_bytes();
-            left[row] = bytes[0];
-            right[row] = bytes[bytes.len() - 1];
+            assert!(bytes.len() == size, "Tile {} is not square", id);
+            left.push(bytes[0]);
+            right.push(bytes[size - 1]);
 
-            if (1..=IMAGE_SIZE).contains(&row) {
-                image[row - 1][..IMAGE_SIZE].clone_from_slice(&bytes[1..=IMAGE_SIZE]);
+            if (1..=image_size).contains(&row) {
+                image.push(Vec::from(&bytes[1..=image_size]));
             }
         }
 
-        let top = lines[1]
-            .as_bytes()
-            .try_into()
-            .expect("Failed to pack top row into byte array");
-        let bottom = lines[lines.len() - 1]
-            .as_bytes()
-            .try_into()
-            .expect("Failed to pack bottom row into byte array");
+        let top = Vec::from(lines[1].as_bytes());
+        let bottom = Vec::from(lines[size].as_bytes());
 
         let sides = [left, top, right, bottom];
 
@@ -94,7 +125,7 @@ impl Tile {
     }
 
     #[cfg(test)]
-    fn from_sides(sides: [[u8; TILE_SIZE]; 4]) -> Self {
+    fn from_sides(sides: [Vec<u8>; 4]) -> Self {
         Self {
             id: 0,
             image: Vec::new(),
@@ -103,12 +134,15 @@ impl Tile {
         }
     }
 
-    fn get_unique_sides(&self) -> Vec<[u8; TILE_SIZE]> {
+    fn size(&self) -> usize {
+        self.sides[0].len()
+    }
+
+    fn get_unique_sides(&self) -> Vec<Vec<u8>> {
         let mut unique_sides = Vec::new();
         for side in &self.sides {
-            unique_sides.push(*side);
-            unique_sides.push(*side);
-            unique_sides.last_mut().unwrap().reverse();
+            unique_sides.push(side.clone());
+            unique_sides.push(side.iter().rev().copied().collect());
         }
         unique_sides.sort_unstable();
         unique_sides.dedup();
@@ -127,7 +161,7 @@ impl Tile {
         }
     }
 
-    fn get_side_after_transform(&self, mut side: Side, transform: Transform) -> [u8; TILE_SIZE] {
+    fn get_side_after_transform(&self, mut side: Side, transform: Transform) -> Vec<u8> {
         let mut reverse = false;
 
         if transform.contains(Transform::FLIP_VERTICAL) {
@@ -162,14 +196,14 @@ impl Tile {
             side = Side::from_index((((side as u8) + 3) % 4) as usize);
         }
 
-        let mut side_bytes = self.sides[side as usize];
+        let mut side_bytes = self.sides[side as usize].clone();
         if reverse {
             side_bytes.reverse();
         }
         side_bytes
     }
 
-    fn get_transform_to_match_side(&self, side: Side, bytes: &[u8; TILE_SIZE]) -> Transform {
+    fn get_transform_to_match_side(&self, side: Side, bytes: &[u8]) -> Transform {
         for transform_bits in 0..8 {
             let transform =
                 Transform::from_bits(transform_bits).expect("Failed to parse bits as Transform");
@@ -192,83 +226,152 @@ impl TransformedTile {
     }
 }
 
-fn assemble_tiles(
-    top_left_corner_id: u16,
-    tiles: &HashMap<u16, Tile>,
-    tiles_with_side: &HashMap<[u8; TILE_SIZE], Vec<u16>>,
-) -> Vec<Vec<TransformedTile>> {
-    let mut rows = Vec::new();
+struct Jigsaw {
+    tiles: HashMap<u16, Tile>,
+    tiles_with_side: HashMap<Vec<u8>, Vec<u16>>,
+    corners: Vec<u16>,
+}
 
-    let mut first_row = Vec::new();
-    first_row.push(TransformedTile::new(
-        tiles[&top_left_corner_id].id,
-        tiles[&top_left_corner_id].get_transform_to_be_top_left(),
-    ));
+impl Jigsaw {
+    fn new(tile_list: Vec<Tile>) -> Self {
+        let tile_size = tile_list.first().expect("Failed to find any tiles").size();
+
+        let mut tiles = HashMap::new();
+        let mut tiles_with_side = HashMap::new();
+        for tile in tile_list {
+            assert!(
+                tile.size() == tile_size,
+                "Tile {} has size {}, but the first tile has size {}",
+                tile.id,
+                tile.size(),
+                tile_size
+            );
+            for side in tile.get_unique_sides() {
+                tiles_with_side
+                    .entry(side)
+                    .or_insert_with(Vec::new)
+                    .push(tile.id);
+            }
+            tiles.insert(tile.id, tile);
+        }
 
-    loop {
-        let previous = first_row.last().expect("Failed to find previous tile");
-        let previous_tile = &tiles[&previous.id];
+        let mut corners = Vec::new();
+        for tile in tiles.values_mut() {
+            let mut sides_with_neighbors = Vec::new();
+            for (i, side) in tile.sides.iter().enumerate() {
+                if tiles_with_side[side].iter().any(|id| *id != tile.id) {
+                    sides_with_neighbors.push(Side::from_index(i));
+                }
+            }
 
-        let previous_right_side =
-            previous_tile.get_side_after_transform(Side::Right, previous.transform);
-        if let Some(current_tile) = tiles_with_side[&previous_right_side].iter().find_map(|id| {
-            if *id == previous_tile.id {
-                None
-            } else {
-                Some(&tiles[id])
+            if sides_with_neighbors.len() == 2 {
+                corners.push(tile.id);
             }
-        }) {
-            let current_transform =
-                current_tile.get_transform_to_match_side(Side::Left, &previous_right_side);
-            first_row.push(TransformedTile::new(current_tile.id, current_transform));
-        } else {
-            break;
+
+            tile.sides_with_neighbors = sides_with_neighbors;
+        }
+        corners.sort_unstable();
+
+        Self {
+            tiles,
+            tiles_with_side,
+            corners,
         }
     }
-    rows.push(first_row);
 
-    loop {
-        let mut row = Vec::new();
+    fn get_corner_product(&self) -> u64 {
+        self.corners.iter().map(|id| u64::from(*id)).product()
+    }
 
-        loop {
-            let column_index = row.len();
-            let last_row = rows.last().expect("Failed to find previous row");
-            if column_index >= last_row.len() {
-                break;
+    fn find_neighbor(&self, id: u16, side: &[u8]) -> Option<&Tile> {
+        self.tiles_with_side[side].iter().find_map(|neighbor_id| {
+            if *neighbor_id == id {
+                None
+            } else {
+                Some(&self.tiles[neighbor_id])
             }
+        })
+    }
 
-            let previous = &last_row[column_index];
-            let previous_tile = &tiles[&previous.id];
+    fn assemble_tiles(&self) -> Vec<Vec<TransformedTile>> {
+        let top_left_corner = &self.tiles[self.corners.first().expect("Failed to find a corner")];
 
-            let previous_bottom_side =
-                previous_tile.get_side_after_transform(Side::Bottom, previous.transform);
-            if let Some(current_tile) =
-                tiles_with_side[&previous_bottom_side]
-                    .iter()
-                    .find_map(|id| {
-                        if *id == previous_tile.id {
-                            None
-                        } else {
-                            Some(&tiles[id])
-                        }
-                    })
-            {
+        let mut rows = Vec::new();
+
+        let mut first_row = Vec::new();
+        first_row.push(TransformedTile::new(
+            top_left_corner.id,
+            top_left_corner.get_transform_to_be_top_left(),
+        ));
+
+        loop {
+            let previous = first_row.last().expect("Failed to find previous tile");
+            let previous_tile = &self.tiles[&previous.id];
+
+            let previous_right_side =
+                previous_tile.get_side_after_transform(Side::Right, previous.transform);
+            if let Some(current_tile) = self.find_neighbor(previous_tile.id, &previous_right_side) {
                 let current_transform =
-                    current_tile.get_transform_to_match_side(Side::Top, &previous_bottom_side);
-                row.push(TransformedTile::new(current_tile.id, current_transform));
+                    current_tile.get_transform_to_match_side(Side::Left, &previous_right_side);
+                first_row.push(TransformedTile::new(current_tile.id, current_transform));
             } else {
                 break;
             }
         }
+        rows.push(first_row);
 
-        if row.is_empty() {
-            break;
+        loop {
+            let mut row = Vec::new();
+
+            loop {
+                let column_index = row.len();
+                let last_row = rows.last().expect("Failed to find previous row");
+                if column_index >= last_row.len() {
+                    break;
+                }
+
+                let previous = &last_row[column_index];
+                let previous_tile = &self.tiles[&previous.id];
+
+                let previous_bottom_side =
+                    previous_tile.get_side_after_transform(Side::Bottom, previous.transform);
+                if let Some(current_tile) =
+                    self.find_neighbor(previous_tile.id, &previous_bottom_side)
+                {
+                    let current_transform =
+                        current_tile.get_transform_to_match_side(Side::Top, &previous_bottom_side);
+                    row.push(TransformedTile::new(current_tile.id, current_transform));
+                } else {
+                    break;
+                }
+            }
+
+            if row.is_empty() {
+                break;
+            }
+
+            rows.push(row);
         }
 
-        rows.push(row);
+        rows
     }
 
-    rows
+    fn assemble_image(&self) -> Vec<Vec<u8>> {
+        let mut image = Vec::new();
+        for row in &self.assemble_tiles() {
+            let mut lines = Vec::new();
+            for placed_tile in row {
+                let tile = &self.tiles[&placed_tile.id];
+                let tile_image = transform_image(&tile.image, placed_tile.transform);
+                lines.resize(tile_image.len(), Vec::new());
+                for (line, tile_line) in lines.iter_mut().zip(&tile_image) {
+                    line.extend_from_slice(tile_line);
+                }
+            }
+            image.append(&mut lines);
+        }
+        image
+    }
 }
 
 fn transform_image(image: &[Vec<u8>], transform: Transform) -> Vec<Vec<u8>> {
@@ -297,139 +400,277 @@ fn transform_image(image: &[Vec<u8>], transform: Transform) -> Vec<Vec<u8>> {
     result
 }
 
-fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<FILE>"))
-        .get_matches();
+struct Pattern {
+    height: usize,
+    width: usize,
+    // (row, column) of every '#' in the pattern
+    cells: Vec<(usize, usize)>,
+}
 
-    let mut tiles = HashMap::new();
-    let mut tiles_with_side = HashMap::new();
+impl Pattern {
+    fn from_lines<S: AsRef<str>>(lines: &[S]) -> Self {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        for (row, line) in lines.iter().enumerate() {
+            let line = line.as_ref().trim_end();
+            width = width.max(line.len());
+            for (column, byte) in line.bytes().enumerate() {
+                if byte == b'#' {
+                    cells.push((row, column));
+                }
+            }
+        }
+        assert!(!cells.is_empty(), "Pattern has no '#' cells");
 
-    let mut reader = LineReader::new(args.value_of("FILE").unwrap());
+        Self {
+            height: lines.len(),
+            width,
+            cells,
+        }
+    }
 
-    let mut tile_lines = Vec::new();
-    while reader.read_with(|line| tile_lines.push(String::from(line))) {
-        let tile = Tile::from_lines(&tile_lines);
-        for side in tile.get_unique_sides() {
-            tiles_with_side
-                .entry(side)
-                .or_insert_with(Vec::new)
-                .push(tile.id);
-        }
-        tiles.insert(tile.id, tile);
-        tile_lines.clear();
-    }
-
-    let mut corner_product = 1;
-    let mut corners = Vec::new();
-
-    for tile in tiles.values_mut() {
-        let mut sides_with_neighbors = Vec::new();
-        for (i, side) in tile.sides.iter().enumerate() {
-            if tiles_with_side[side].iter().any(|id| *id != tile.id) {
-                sides_with_neighbors.push(Side::from_index(i));
+    // Returns the top-left corner of every placement of the pattern, including overlapping ones
+    fn find_matches(&self, image: &[Vec<u8>]) -> Vec<(usize, usize)> {
+        let mut matches = Vec::new();
+        let rows = (image.len() + 1).saturating_sub(self.height);
+        let columns = (image.first().map_or(0, Vec::len) + 1).saturating_sub(self.width);
+        for origin_row in 0..rows {
+            for origin_column in 0..columns {
+                if self
+                    .cells
+                    .iter()
+                    .all(|(row, column)| image[origin_row + row][origin_column + column] == b'#')
+                {
+                    matches.push((origin_row, origin_column));
+                }
             }
         }
+        matches
+    }
+}
 
-        if sides_with_neighbors.len() == 2 {
-            corner_product *= u64::from(tile.id);
-            corners.push(tile.id);
-        };
+struct Search {
+    transform: Transform,
+    matches: Vec<(usize, usize)>,
+    // The image in the searched orientation, with every matched cell replaced by 'O'
+    image: Vec<Vec<u8>>,
+}
 
-        tile.sides_with_neighbors = sides_with_neighbors;
-    }
+fn search_orientations(image: &[Vec<u8>], pattern: &Pattern) -> Vec<Search> {
+    (0..8)
+        .map(|transform_bits| {
+            let transform = Transform::from_bits(transform_bits)
+                .expect("Failed to convert transform bits into Transform");
+            let mut image = transform_image(image, transform);
+            let matches = pattern.find_matches(&image);
+            for (origin_row, origin_column) in &matches {
+                for (row, column) in &pattern.cells {
+                    image[origin_row + row][origin_column + column] = b'O';
+                }
+            }
+            Search {
+                transform,
+                matches,
+                image,
+            }
+        })
+        .collect()
+}
 
-    println!("Corner product: {}", corner_product);
+fn count_roughness(image: &[Vec<u8>]) -> usize {
+    image
+        .iter()
+        .flat_map(|row| row.iter())
+        .filter(|byte| **byte == b'#')
+        .count()
+}
 
-    let rows = assemble_tiles(corners[0], &tiles, &tiles_with_side);
+fn write_text(filename: &str, image: &[Vec<u8>]) {
+    let mut text = String::new();
+    for row in image {
+        text.push_str(&String::from_utf8_lossy(row));
+        text.push('\n');
+    }
+    fs::write(filename, text).unwrap_or_else(|_| panic!("Failed to write {}", filename));
+}
 
-    let mut image = Vec::new();
-    for row in &rows {
-        let mut lines = vec![Vec::new(); TILE_SIZE - 2];
-        for placed_tile in row {
-            let tile = &tiles[&placed_tile.id];
-            let tile_image = transform_image(&tile.image, placed_tile.transform);
-            for line in 0..TILE_SIZE - 2 {
-                lines[line].extend_from_slice(&tile_image[line]);
+fn write_png(filename: &str, image: &[Vec<u8>]) {
+    let height = image.len() * PNG_SCALE;
+    let width = image.first().map_or(0, Vec::len) * PNG_SCALE;
+
+    let mut data = Vec::with_capacity(width * height * 3);
+    for row in image {
+        let mut line = Vec::with_capacity(width * 3);
+        for byte in row {
+            let color = match byte {
+                b'#' => [0x1f, 0x4e, 0x79],
+                b'O' => [0xff, 0xc0, 0x00],
+                _ => [0x9d, 0xc3, 0xe6],
+            };
+            for _ in 0..PNG_SCALE {
+                line.extend_from_slice(&color);
             }
         }
-        image.append(&mut lines);
+        for _ in 0..PNG_SCALE {
+            data.extend_from_slice(&line);
+        }
     }
 
-    let pattern = [
-        b"                  # ",
-        b"#    ##    ##    ###",
-        b" #  #  #  #  #  #   ",
-    ];
+    let file = File::create(filename).unwrap_or_else(|_| panic!("Failed to create {}", filename));
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        u32::try_from(width).expect("Image is too wide for PNG"),
+        u32::try_from(height).expect("Image is too tall for PNG"),
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .unwrap_or_else(|_| panic!("Failed to write {}", filename));
+}
 
-    for transform_bits in 0..8 {
-        let transform = Transform::from_bits(transform_bits)
-            .expect("Failed to convert transform bits into Transform");
+fn main() {
+    let args = Args::parse();
 
-        let mut instance_count = 0;
+    let mut reader = LineReader::new(args.filename.as_deref());
 
-        let transformed_image = transform_image(&image, transform);
+    let mut tiles = Vec::new();
+    let mut tile_lines = Vec::new();
+    loop {
+        let more = reader.read_with(|line| tile_lines.push(String::from(line)));
+        if !tile_lines.is_empty() {
+            tiles.push(Tile::from_lines(&tile_lines));
+            tile_lines.clear();
+        }
+        if !more {
+            break;
+        }
+    }
 
-        for origin_row in 0..image.len() - (pattern.len() - 1) {
-            for origin_column in 0..image.len() - (pattern[0].len() - 1) {
-                let mut all_found = true;
-                for row in 0..pattern.len() {
-                    for column in 0..pattern[0].len() {
-                        if pattern[row][column] == b'#'
-                            && transformed_image[origin_row + row][origin_column + column] != b'#'
-                        {
-                            all_found = false;
-                            break;
-                        }
-                    }
+    let jigsaw = Jigsaw::new(tiles);
 
-                    if !all_found {
-                        break;
-                    }
-                }
+    if args.part == 1 {
+        println!("Corner product: {}", jigsaw.get_corner_product());
+        return;
+    }
 
-                if all_found {
-                    instance_count += 1;
-                }
+    let pattern = match &args.pattern {
+        Some(filename) => {
+            let text = fs::read_to_string(filename)
+                .unwrap_or_else(|_| panic!("Failed to read pattern {}", filename));
+            let lines: Vec<_> = text.lines().collect();
+            Pattern::from_lines(&lines)
+        }
+        None => Pattern::from_lines(&SEA_MONSTER),
+    };
+
+    let image = jigsaw.assemble_image();
+    let searches = search_orientations(&image, &pattern);
+
+    if args.list_matches {
+        for search in &searches {
+            for (row, column) in &search.matches {
+                println!(
+                    "Match at row {}, column {} in orientation {:?}",
+                    row, column, search.transform
+                );
             }
         }
+    }
 
-        if instance_count > 0 {
-            let pattern_hash_count = pattern
-                .iter()
-                .flat_map(|row| row.iter())
-                .filter(|byte| **byte == b'#')
-                .count();
-
-            let image_hash_count = transformed_image
-                .iter()
-                .flat_map(|row| row.iter())
-                .filter(|byte| **byte == b'#')
-                .count();
-
-            println!(
-                "Water roughness: {}",
-                image_hash_count - pattern_hash_count * instance_count
-            );
-            break;
+    // The orientation with the most matches is the one the image was meant to be viewed in. Ties go
+    // to the earliest orientation, so an image without matches is left untransformed.
+    let best = searches
+        .iter()
+        .rev()
+        .max_by_key(|search| search.matches.len())
+        .expect("Failed to search any orientations");
+
+    if let Some(filename) = &args.output {
+        if Path::new(filename)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+        {
+            write_png(filename, &best.image);
+        } else {
+            write_text(filename, &best.image);
         }
     }
+
+    println!("Water roughness: {}", count_roughness(&best.image));
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Side, Tile, Transform, TILE_SIZE};
+    use std::convert::TryFrom;
+
+    use super::{
+        count_roughness, search_orientations, transform_image, Jigsaw, Pattern, Side, Tile,
+        Transform,
+    };
     // use test::Bencher;
 
-    fn get_test_sides() -> [[u8; TILE_SIZE]; 4] {
+    // Cuts a pseudo-random picture into a grid of tiles whose touching borders match, then
+    // scrambles each tile's orientation. Returns the tiles and the picture without borders.
+    fn make_jigsaw(grid_size: usize, tile_size: usize) -> (Vec<Tile>, Vec<Vec<u8>>) {
+        let mut state = 12345_u32;
+        let mut grid = vec![vec![Vec::new(); grid_size]; grid_size];
+        for row in 0..grid_size {
+            for column in 0..grid_size {
+                let mut tile = vec![vec![b'.'; tile_size]; tile_size];
+                for line in &mut tile {
+                    for byte in line {
+                        state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        if (state >> 16) & 1 == 1 {
+                            *byte = b'#';
+                        }
+                    }
+                }
+                if column > 0 {
+                    let left: &Vec<Vec<u8>> = &grid[row][column - 1];
+                    for (line, left_line) in tile.iter_mut().zip(left) {
+                        line[0] = left_line[tile_size - 1];
+                    }
+                }
+                if row > 0 {
+                    tile[0] = grid[row - 1][column][tile_size - 1].clone();
+                }
+                grid[row][column] = tile;
+            }
+        }
+
+        let mut picture = vec![Vec::new(); grid_size * (tile_size - 2)];
+        let mut tiles = Vec::new();
+        for (row, grid_row) in grid.iter().enumerate() {
+            for (column, tile) in grid_row.iter().enumerate() {
+                for (line, tile_line) in tile[1..tile_size - 1].iter().enumerate() {
+                    picture[row * (tile_size - 2) + line]
+                        .extend_from_slice(&tile_line[1..tile_size - 1]);
+                }
+
+                let id = 1000 + row * grid_size + column;
+                let transform = Transform::from_bits(u8::try_from(id % 8).unwrap()).unwrap();
+                let mut lines = vec![format!("Tile {}:", id)];
+                for line in transform_image(tile, transform) {
+                    lines.push(String::from_utf8(line).unwrap());
+                }
+                tiles.push(Tile::from_lines(&lines));
+            }
+        }
+
+        (tiles, picture)
+    }
+
+    fn get_test_sides() -> [Vec<u8>; 4] {
         // 10 ... 19
         // ...   ...
         // 37 ... 28
 
-        let left = [10, 45, 44, 43, 42, 41, 40, 39, 38, 37];
-        let top = [10, 11, 12, 13, 14, 15, 16, 17, 18, 19];
-        let right = [19, 20, 21, 22, 23, 24, 25, 26, 27, 28];
-        let bottom = [37, 36, 35, 34, 33, 32, 31, 30, 29, 28];
+        let left = vec![10, 45, 44, 43, 42, 41, 40, 39, 38, 37];
+        let top = vec![10, 11, 12, 13, 14, 15, 16, 17, 18, 19];
+        let right = vec![19, 20, 21, 22, 23, 24, 25, 26, 27, 28];
+        let bottom = vec![37, 36, 35, 34, 33, 32, 31, 30, 29, 28];
         [left, top, right, bottom]
     }
 
@@ -467,7 +708,7 @@ mod tests {
             tile.get_side_after_transform(Side::Left, Transform::ROTATE_90),
             get_test_sides()[Side::Bottom as usize]
         );
-        let mut reversed_left = get_test_sides()[Side::Left as usize];
+        let mut reversed_left = get_test_sides()[Side::Left as usize].clone();
         reversed_left.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Top, Transform::ROTATE_90),
@@ -477,7 +718,7 @@ mod tests {
             tile.get_side_after_transform(Side::Right, Transform::ROTATE_90),
             get_test_sides()[Side::Top as usize]
         );
-        let mut reversed_right = get_test_sides()[Side::Right as usize];
+        let mut reversed_right = get_test_sides()[Side::Right as usize].clone();
         reversed_right.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Bottom, Transform::ROTATE_90),
@@ -497,7 +738,7 @@ mod tests {
             tile.get_side_after_transform(Side::Left, Transform::FLIP_HORIZONTAL),
             get_test_sides()[Side::Right as usize]
         );
-        let mut reversed_top = get_test_sides()[Side::Top as usize];
+        let mut reversed_top = get_test_sides()[Side::Top as usize].clone();
         reversed_top.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Top, Transform::FLIP_HORIZONTAL),
@@ -507,7 +748,7 @@ mod tests {
             tile.get_side_after_transform(Side::Right, Transform::FLIP_HORIZONTAL),
             get_test_sides()[Side::Left as usize]
         );
-        let mut reversed_bottom = get_test_sides()[Side::Bottom as usize];
+        let mut reversed_bottom = get_test_sides()[Side::Bottom as usize].clone();
         reversed_bottom.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Bottom, Transform::FLIP_HORIZONTAL),
@@ -523,7 +764,7 @@ mod tests {
 
         let tile = Tile::from_sides(get_test_sides());
 
-        let mut reversed_left = get_test_sides()[Side::Left as usize];
+        let mut reversed_left = get_test_sides()[Side::Left as usize].clone();
         reversed_left.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Left, Transform::FLIP_VERTICAL),
@@ -533,7 +774,7 @@ mod tests {
             tile.get_side_after_transform(Side::Top, Transform::FLIP_VERTICAL),
             get_test_sides()[Side::Bottom as usize]
         );
-        let mut reversed_right = get_test_sides()[Side::Right as usize];
+        let mut reversed_right = get_test_sides()[Side::Right as usize].clone();
         reversed_right.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Right, Transform::FLIP_VERTICAL),
@@ -556,7 +797,7 @@ mod tests {
 
         let tile = Tile::from_sides(get_test_sides());
 
-        let mut reversed_top = get_test_sides()[Side::Top as usize];
+        let mut reversed_top = get_test_sides()[Side::Top as usize].clone();
         reversed_top.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Left, rotate_270),
@@ -566,7 +807,7 @@ mod tests {
             tile.get_side_after_transform(Side::Top, rotate_270),
             get_test_sides()[Side::Right as usize]
         );
-        let mut reversed_bottom = get_test_sides()[Side::Bottom as usize];
+        let mut reversed_bottom = get_test_sides()[Side::Bottom as usize].clone();
         reversed_bottom.reverse();
         assert_eq!(
             tile.get_side_after_transform(Side::Right, rotate_270),
@@ -604,8 +845,8 @@ mod tests {
             Transform::ROTATE_90 | Transform::FLIP_HORIZONTAL
         );
 
-        for side_index in 0..4 {
-            sides[side_index].reverse();
+        for side in &mut sides {
+            side.reverse();
         }
 
         assert_eq!(
@@ -625,4 +866,40 @@ mod tests {
             Transform::ROTATE_90
         );
     }
+
+    #[test]
+    fn assemble_tiles_of_any_size() {
+        for (grid_size, tile_size) in [(3, 10), (4, 14), (2, 24)] {
+            let (tiles, picture) = make_jigsaw(grid_size, tile_size);
+            let jigsaw = Jigsaw::new(tiles);
+
+            let last = 1000 + grid_size * grid_size - 1;
+            assert_eq!(
+                jigsaw.get_corner_product(),
+                [1000, 1000 + grid_size - 1, last + 1 - grid_size, last]
+                    .iter()
+                    .map(|id| *id as u64)
+                    .product()
+            );
+
+            let image = jigsaw.assemble_image();
+            assert!((0..8).any(|transform_bits| {
+                transform_image(&picture, Transform::from_bits(transform_bits).unwrap()) == image
+            }));
+        }
+    }
+
+    #[test]
+    fn find_overlapping_matches() {
+        let pattern = Pattern::from_lines(&["##", " #"]);
+        let image: Vec<Vec<u8>> = ["###.", ".##.", "..#."]
+            .iter()
+            .map(|line| Vec::from(line.as_bytes()))
+            .collect();
+        assert_eq!(pattern.find_matches(&image), [(0, 0), (0, 1), (1, 1)]);
+
+        let searches = search_orientations(&image, &pattern);
+        assert_eq!(searches[0].matches.len(), 3);
+        assert_eq!(count_roughness(&searches[0].image), 0);
+    }
 }
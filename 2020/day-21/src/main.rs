@@ -3,28 +3,109 @@
 
 extern crate test;
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+};
 
-use clap::{crate_name, App, Arg};
+use clap::Parser;
 use common::LineReader;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Print which foods prove each allergen's ingredient
+    #[arg(long)]
+    report: bool,
+}
+
+// No consistent assignment, or more than one
+enum AssignmentError {
+    // No assignment gives every allergen a different ingredient. Lists the allergens that have
+    // no candidates at all, which may be empty if the allergens just compete for too few.
+    Impossible(Vec<String>),
+    // The allergen could be in either ingredient, with the rest still assignable in both cases
+    Ambiguous {
+        allergen: String,
+        ingredients: [String; 2],
+    },
+}
+
+impl fmt::Display for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Impossible(allergens) if allergens.is_empty() => {
+                write!(f, "no way to give every allergen a different ingredient")
+            }
+            Self::Impossible(allergens) => {
+                write!(f, "no ingredient could contain {}", allergens.join(", "))
+            }
+            Self::Ambiguous {
+                allergen,
+                ingredients,
+            } => write!(
+                f,
+                "{} could be in either {} or {}",
+                allergen, ingredients[0], ingredients[1]
+            ),
+        }
+    }
+}
+
+// Why an allergen has to be in an ingredient
+struct Proof<'a> {
+    allergen: &'a str,
+    ingredient: &'a str,
+    // Foods listing the allergen, numbered from 1, whose ingredients all include the ingredient
+    foods: Vec<usize>,
+    // Other ingredients in all of those foods, with the allergen each of them holds instead
+    ruled_out: Vec<(&'a str, &'a str)>,
+}
+
+impl fmt::Display for Proof<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let foods: Vec<_> = self.foods.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "{}: {} (listed in foods {}",
+            self.allergen,
+            self.ingredient,
+            foods.join(", ")
+        )?;
+        for (ingredient, allergen) in &self.ruled_out {
+            write!(f, "; {ingredient} has {allergen}")?;
+        }
+        write!(f, ")")
+    }
+}
+
 struct AllergenTracker {
-    candidate_ingredients: HashMap<String, HashSet<String>>,
+    // Ingredients in every food listing the allergen
+    candidate_ingredients: BTreeMap<String, BTreeSet<String>>,
     ingredient_counts: HashMap<String, i32>,
+    // Allergens listed by each food
+    food_allergens: Vec<Vec<String>>,
 }
 
 impl AllergenTracker {
     fn new() -> Self {
         Self {
-            candidate_ingredients: HashMap::new(),
+            candidate_ingredients: BTreeMap::new(),
             ingredient_counts: HashMap::new(),
+            food_allergens: Vec::new(),
         }
     }
 
     fn add_food(&mut self, line: &str) {
         let mut split = line.split('(');
 
-        let ingredients: HashSet<String> = split
+        let ingredients: BTreeSet<String> = split
             .next()
             .expect("Failed to find ingredients")
             .trim()
@@ -48,13 +129,13 @@ impl AllergenTracker {
             .map(String::from)
             .collect();
 
-        for allergen in allergens {
-            match self.candidate_ingredients.get_mut(&allergen) {
+        for allergen in &allergens {
+            match self.candidate_ingredients.get_mut(allergen) {
                 Some(candidate_ingredients) => {
                     *candidate_ingredients = candidate_ingredients
                         .intersection(&ingredients)
                         .cloned()
-                        .collect()
+                        .collect();
                 }
                 None => {
                     self.candidate_ingredients
@@ -62,111 +143,257 @@ impl AllergenTracker {
                 }
             }
         }
-    }
-
-    fn collapse_known_allergens(&mut self) {
-        let mut changed = true;
-        while changed {
-            let known_allergens: HashSet<String> = self
-                .candidate_ingredients
-                .values()
-                .filter_map(|ingredients| {
-                    if ingredients.len() == 1 {
-                        Some(
-                            ingredients
-                                .iter()
-                                .next()
-                                .expect("Failed to get only element")
-                                .clone(),
-                        )
-                    } else {
-                        None
-                    }
-                })
-                .collect();
 
-            changed = false;
-            for ingredients in self.candidate_ingredients.values_mut() {
-                if ingredients.len() > 1 {
-                    *ingredients = ingredients.difference(&known_allergens).cloned().collect();
-
-                    changed = true;
-                }
-            }
-        }
+        self.food_allergens.push(allergens);
     }
 
+    // Ingredients that aren't a candidate for any allergen can't contain one, whatever the
+    // assignment turns out to be
     fn get_safe_ingredient_count(&self) -> i32 {
-        let allergens: HashSet<String> = self
-            .candidate_ingredients
-            .values()
-            .map(|ingredients| {
-                ingredients
-                    .iter()
-                    .next()
-                    .expect("Failed to find only ingredient")
-                    .clone()
-            })
-            .collect();
-
         self.ingredient_counts
             .iter()
-            .map(|(ingredient, count)| {
-                if allergens.contains(ingredient) {
-                    0
-                } else {
-                    *count
-                }
+            .filter(|(ingredient, _)| {
+                !self
+                    .candidate_ingredients
+                    .values()
+                    .any(|candidates| candidates.contains(*ingredient))
             })
+            .map(|(_, count)| *count)
             .sum()
     }
 
-    fn get_canonical_list(&self) -> String {
-        let mut allergens: Vec<(String, String)> = self
+    // Assigns each allergen, in name order, a different candidate ingredient, as long as there's
+    // exactly one way to do so
+    fn assign(&self) -> Result<Vec<&str>, AssignmentError> {
+        let candidates: Vec<Vec<&str>> = self
             .candidate_ingredients
-            .iter()
-            .map(|(allergen, ingredients)| {
-                (
-                    ingredients
-                        .iter()
-                        .next()
-                        .expect("Failed to find only ingredient")
-                        .clone(),
-                    allergen.clone(),
-                )
-            })
+            .values()
+            .map(|ingredients| ingredients.iter().map(String::as_str).collect())
             .collect();
 
-        allergens.sort_by_key(|(_ingredient, allergen)| allergen.clone());
+        // Fill in the most constrained allergens first, so dead ends show up early
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by_key(|allergen| candidates[*allergen].len());
+
+        let mut solutions = Vec::new();
+        search(
+            &candidates,
+            &order,
+            &mut vec![""; candidates.len()],
+            &mut HashSet::new(),
+            &mut solutions,
+        );
 
-        let allergens: Vec<String> = allergens
+        match solutions.len() {
+            0 => Err(AssignmentError::Impossible(
+                self.candidate_ingredients
+                    .iter()
+                    .filter(|(_, ingredients)| ingredients.is_empty())
+                    .map(|(allergen, _)| allergen.clone())
+                    .collect(),
+            )),
+            1 => Ok(solutions.pop().unwrap()),
+            _ => {
+                let (allergen, ingredients) = self
+                    .candidate_ingredients
+                    .keys()
+                    .zip(solutions[0].iter().zip(&solutions[1]))
+                    .find(|(_, (first, second))| first != second)
+                    .expect("Failed to find where solutions differ");
+                Err(AssignmentError::Ambiguous {
+                    allergen: allergen.clone(),
+                    ingredients: [ingredients.0.to_string(), ingredients.1.to_string()],
+                })
+            }
+        }
+    }
+
+    fn get_canonical_list(assignment: &[&str]) -> String {
+        // Allergens are already in name order
+        assignment.join(",")
+    }
+
+    fn prove<'a>(&'a self, assignment: &[&'a str]) -> Vec<Proof<'a>> {
+        let holders: HashMap<&str, &str> = assignment
             .iter()
-            .map(|(ingredient, _allergen)| ingredient.clone())
+            .copied()
+            .zip(self.candidate_ingredients.keys().map(String::as_str))
             .collect();
-        allergens.as_slice().join(",")
+
+        self.candidate_ingredients
+            .iter()
+            .zip(assignment)
+            .map(|((allergen, candidates), ingredient)| Proof {
+                allergen,
+                ingredient,
+                foods: (0..self.food_allergens.len())
+                    .filter(|food| self.food_allergens[*food].contains(allergen))
+                    .map(|food| food + 1)
+                    .collect(),
+                ruled_out: candidates
+                    .iter()
+                    .filter(|candidate| candidate != ingredient)
+                    .map(|candidate| {
+                        let holder = holders
+                            .get(candidate.as_str())
+                            .expect("Failed to find allergen of ruled-out ingredient");
+                        (candidate.as_str(), *holder)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+// Backtracking search over the allergens in order, stopping once two solutions are found since
+// that's enough to know the answer isn't unique
+fn search<'a>(
+    candidates: &[Vec<&'a str>],
+    order: &[usize],
+    assignment: &mut Vec<&'a str>,
+    used: &mut HashSet<&'a str>,
+    solutions: &mut Vec<Vec<&'a str>>,
+) {
+    let Some((allergen, rest)) = order.split_first() else {
+        solutions.push(assignment.clone());
+        return;
+    };
+
+    for ingredient in &candidates[*allergen] {
+        if solutions.len() == 2 {
+            return;
+        }
+        if used.insert(ingredient) {
+            assignment[*allergen] = ingredient;
+            search(candidates, rest, assignment, used, solutions);
+            used.remove(ingredient);
+        }
     }
 }
 
 fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<FILE>"))
-        .get_matches();
+    let args = Args::parse();
 
     let mut tracker = AllergenTracker::new();
 
-    let mut reader = LineReader::new(args.value_of("FILE").unwrap());
+    let mut reader = LineReader::new(args.filename.as_deref());
     reader.read_with(|line| tracker.add_food(line));
 
-    tracker.collapse_known_allergens();
+    if args.part == 1 {
+        println!(
+            "Safe ingredient count: {}",
+            tracker.get_safe_ingredient_count()
+        );
+    }
 
-    println!(
-        "Safe ingredient count: {}",
-        tracker.get_safe_ingredient_count()
-    );
-    println!("Canonical list: {}", tracker.get_canonical_list());
+    if args.part == 2 || args.report {
+        let assignment = tracker
+            .assign()
+            .unwrap_or_else(|error| panic!("Failed to assign allergens: {}", error));
+        if args.part == 2 {
+            println!(
+                "Canonical list: {}",
+                AllergenTracker::get_canonical_list(&assignment)
+            );
+        }
+        if args.report {
+            for proof in tracker.prove(&assignment) {
+                println!("{proof}");
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    // use test::Bencher;
+    use test::Bencher;
+
+    use super::{AllergenTracker, AssignmentError};
+
+    const EXAMPLE: [&str; 4] = [
+        "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)",
+        "trh fvjkl sbzzf mxmxvkd (contains dairy)",
+        "sqjhc fvjkl (contains soy)",
+        "sqjhc mxmxvkd sbzzf (contains fish)",
+    ];
+
+    fn track(foods: &[&str]) -> AllergenTracker {
+        let mut tracker = AllergenTracker::new();
+        for food in foods {
+            tracker.add_food(food);
+        }
+        tracker
+    }
+
+    #[test]
+    fn example() {
+        let tracker = track(&EXAMPLE);
+        assert_eq!(tracker.get_safe_ingredient_count(), 5);
+        let assignment = tracker.assign().ok().unwrap();
+        assert_eq!(
+            AllergenTracker::get_canonical_list(&assignment),
+            "mxmxvkd,sqjhc,fvjkl"
+        );
+    }
+
+    #[test]
+    fn report() {
+        let tracker = track(&EXAMPLE);
+        let assignment = tracker.assign().ok().unwrap();
+        let proofs: Vec<_> = tracker
+            .prove(&assignment)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            proofs,
+            [
+                "dairy: mxmxvkd (listed in foods 1, 2)",
+                "fish: sqjhc (listed in foods 1, 4; mxmxvkd has dairy)",
+                "soy: fvjkl (listed in foods 3; sqjhc has fish)",
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        // Used to spin forever, since no allergen ever narrows to a single ingredient
+        let tracker = track(&["a b c (contains eggs, nuts)", "a b d (contains nuts, eggs)"]);
+        assert_eq!(tracker.get_safe_ingredient_count(), 2);
+        match tracker.assign() {
+            Err(AssignmentError::Ambiguous {
+                allergen,
+                ingredients,
+            }) => {
+                assert_eq!(allergen, "eggs");
+                assert_eq!(ingredients, ["a", "b"]);
+            }
+            _ => panic!("Expected an ambiguous assignment"),
+        }
+    }
+
+    #[test]
+    fn impossible() {
+        // Three allergens, but only two ingredients they could be in
+        let tracker = track(&[
+            "a b c (contains eggs, nuts, soy)",
+            "a b d (contains soy, eggs, nuts)",
+        ]);
+        match tracker.assign() {
+            Err(AssignmentError::Impossible(allergens)) => assert!(allergens.is_empty()),
+            _ => panic!("Expected an impossible assignment"),
+        }
+
+        let tracker = track(&["a b (contains eggs)", "c d (contains eggs)"]);
+        match tracker.assign() {
+            Err(AssignmentError::Impossible(allergens)) => assert_eq!(allergens, ["eggs"]),
+            _ => panic!("Expected an impossible assignment"),
+        }
+    }
+
+    #[bench]
+    fn bench_example(bencher: &mut Bencher) {
+        let tracker = track(&EXAMPLE);
+        bencher.iter(|| tracker.assign().ok());
+    }
 }
@@ -4,13 +4,34 @@
 extern crate test;
 
 use std::{
-    collections::{hash_map::DefaultHasher, HashSet, VecDeque},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    fmt,
+    fs::File,
     hash::{Hash, Hasher},
+    io::{BufWriter, Write},
 };
 
-use clap::{crate_name, App, Arg};
+use clap::Parser;
 use common::LineReader;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// File to write a log of every round and sub-game of the recursive game to
+    #[arg(long)]
+    log: Option<String>,
+
+    /// Print statistics about the sub-games of the recursive game
+    #[arg(long)]
+    stats: bool,
+}
+
 fn compute_score(deck: &VecDeque<u8>) -> usize {
     deck.iter()
         .enumerate()
@@ -39,73 +60,185 @@ fn play_basic_game(mut player1: VecDeque<u8>, mut player2: VecDeque<u8>) -> usiz
     }
 }
 
-fn play_recursive_game(
-    mut player1: VecDeque<u8>,
-    mut player2: VecDeque<u8>,
-    needs_score: bool,
-) -> (i8, usize) {
-    let mut previous_rounds = HashSet::new();
-
-    loop {
-        let hash = {
-            let mut hasher = DefaultHasher::new();
-            player1.hash(&mut hasher);
-            player2.hash(&mut hasher);
-            hasher.finish()
-        };
-
-        if previous_rounds.contains(&hash) {
-            return (1, 0);
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Player {
+    One,
+    Two,
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::One => write!(f, "Player 1"),
+            Self::Two => write!(f, "Player 2"),
         }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Statistics {
+    // Sub-games actually played, not counting the top-level game
+    sub_games: usize,
+    // Sub-games whose outcome was already known from identical starting decks
+    remembered_sub_games: usize,
+    max_depth: usize,
+    rounds: usize,
+    // Games ended because a round repeated
+    repeated_rounds: usize,
+    // Sub-games ended early because player 1 held the highest card
+    highest_card_wins: usize,
+}
 
-        previous_rounds.insert(hash);
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Sub-games spawned: {} ({} remembered)",
+            self.sub_games + self.remembered_sub_games,
+            self.remembered_sub_games
+        )?;
+        writeln!(f, "Maximum depth: {}", self.max_depth)?;
+        writeln!(f, "Rounds played: {}", self.rounds)?;
+        writeln!(f, "Repeated rounds: {}", self.repeated_rounds)?;
+        write!(f, "Highest card wins: {}", self.highest_card_wins)
+    }
+}
+
+struct RecursiveCombat<W: Write> {
+    // Winners of sub-games, keyed by their starting decks
+    outcomes: HashMap<(Vec<u8>, Vec<u8>), Player>,
+    statistics: Statistics,
+    log: Option<W>,
+}
 
-        if !needs_score && player1.iter().max() > player2.iter().max() {
-            return (1, 0);
+impl<W: Write> RecursiveCombat<W> {
+    fn new(log: Option<W>) -> Self {
+        Self {
+            outcomes: HashMap::new(),
+            statistics: Statistics::default(),
+            log,
         }
+    }
 
-        let card1 = player1.pop_front().unwrap();
-        let card2 = player2.pop_front().unwrap();
+    fn log(&mut self, depth: usize, message: fmt::Arguments) {
+        if let Some(log) = &mut self.log {
+            writeln!(log, "{:indent$}{}", "", message, indent = depth * 2)
+                .expect("Failed to write to log");
+        }
+    }
 
-        let winner = if player1.len() >= card1 as usize && player2.len() >= card2 as usize {
-            let (winner, _) = play_recursive_game(
-                player1.iter().take(card1 as usize).copied().collect(),
-                player2.iter().take(card2 as usize).copied().collect(),
-                false,
-            );
-            winner
-        } else if card1 > card2 {
-            1
-        } else {
-            2
-        };
+    // Plays the top-level game, returning the winner and their score
+    fn play(&mut self, player1: VecDeque<u8>, player2: VecDeque<u8>) -> (Player, usize) {
+        let (winner, deck) = self.play_game(player1, player2, 0);
+        (winner, compute_score(&deck))
+    }
+
+    // Returns the winner and their deck at the end of the game
+    fn play_game(
+        &mut self,
+        mut player1: VecDeque<u8>,
+        mut player2: VecDeque<u8>,
+        depth: usize,
+    ) -> (Player, VecDeque<u8>) {
+        self.statistics.max_depth = self.statistics.max_depth.max(depth);
+        self.log(
+            depth,
+            format_args!("Game at depth {depth}: {player1:?} vs {player2:?}"),
+        );
+
+        // Only the top-level game needs to be played out to get a score. In a sub-game, player 1
+        // can never lose the highest card, so either wins outright or wins when a round repeats.
+        if depth > 0 && player1.iter().max() > player2.iter().max() {
+            self.statistics.highest_card_wins += 1;
+            self.log(depth, format_args!("Player 1 holds the highest card"));
+            return (Player::One, player1);
+        }
 
-        match winner {
-            1 => {
-                player1.push_back(card1);
-                player1.push_back(card2);
+        let mut previous_rounds = HashSet::new();
+        let mut round = 0;
+
+        loop {
+            let hash = {
+                let mut hasher = DefaultHasher::new();
+                player1.hash(&mut hasher);
+                player2.hash(&mut hasher);
+                hasher.finish()
+            };
+
+            if !previous_rounds.insert(hash) {
+                self.statistics.repeated_rounds += 1;
+                self.log(
+                    depth,
+                    format_args!("Round repeated, Player 1 wins the game"),
+                );
+                return (Player::One, player1);
             }
-            2 => {
-                player2.push_back(card2);
-                player2.push_back(card1);
+
+            round += 1;
+            self.statistics.rounds += 1;
+            self.log(
+                depth,
+                format_args!("Round {round}: {player1:?} vs {player2:?}"),
+            );
+
+            let card1 = player1.pop_front().unwrap();
+            let card2 = player2.pop_front().unwrap();
+
+            let winner = if player1.len() >= card1 as usize && player2.len() >= card2 as usize {
+                let deck1: Vec<_> = player1.iter().take(card1 as usize).copied().collect();
+                let deck2: Vec<_> = player2.iter().take(card2 as usize).copied().collect();
+                let key = (deck1, deck2);
+                if let Some(winner) = self.outcomes.get(&key).copied() {
+                    self.statistics.remembered_sub_games += 1;
+                    self.log(
+                        depth + 1,
+                        format_args!("Game at depth {}: {:?} vs {:?}", depth + 1, key.0, key.1),
+                    );
+                    self.log(depth + 1, format_args!("Already played, won by {winner}"));
+                    winner
+                } else {
+                    self.statistics.sub_games += 1;
+                    let (winner, _) = self.play_game(
+                        key.0.iter().copied().collect(),
+                        key.1.iter().copied().collect(),
+                        depth + 1,
+                    );
+                    self.outcomes.insert(key, winner);
+                    winner
+                }
+            } else if card1 > card2 {
+                Player::One
+            } else {
+                Player::Two
+            };
+
+            match winner {
+                Player::One => {
+                    player1.push_back(card1);
+                    player1.push_back(card2);
+                }
+                Player::Two => {
+                    player2.push_back(card2);
+                    player2.push_back(card1);
+                }
             }
-            _ => panic!("Unexpected winner {}", winner),
-        };
+            self.log(depth, format_args!("{winner} wins round {round}"));
 
-        if player1.is_empty() {
-            return (2, compute_score(&player2));
-        } else if player2.is_empty() {
-            return (1, compute_score(&player1));
+            if player1.is_empty() {
+                self.log(depth, format_args!("Player 2 wins the game"));
+                return (Player::Two, player2);
+            } else if player2.is_empty() {
+                self.log(depth, format_args!("Player 1 wins the game"));
+                return (Player::One, player1);
+            }
         }
     }
 }
 
 fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<FILE>"))
-        .get_matches();
+    let args = Args::parse();
 
-    let mut reader = LineReader::new(args.value_of("FILE").unwrap());
+    let mut reader = LineReader::new(args.filename.as_deref());
 
     let mut player1 = VecDeque::new();
     reader.read_with(|line| {
@@ -116,7 +249,7 @@ fn main() {
         player1.push_back(
             line.parse::<u8>()
                 .unwrap_or_else(|_| panic!("Failed to parse {}", line)),
-        )
+        );
     });
 
     let mut player2 = VecDeque::new();
@@ -128,19 +261,101 @@ fn main() {
         player2.push_back(
             line.parse::<u8>()
                 .unwrap_or_else(|_| panic!("Failed to parse {}", line)),
-        )
+        );
     });
 
-    println!(
-        "Basic game score: {}",
-        play_basic_game(player1.clone(), player2.clone())
-    );
-
-    let (_winner, score) = play_recursive_game(player1, player2, true);
-    println!("Recursive game score: {}", score);
+    if args.part == 1 {
+        println!("Basic game score: {}", play_basic_game(player1, player2));
+    } else {
+        let log = args.log.as_ref().map(|filename| {
+            BufWriter::new(
+                File::create(filename)
+                    .unwrap_or_else(|_| panic!("Failed to create log {}", filename)),
+            )
+        });
+        let mut game = RecursiveCombat::new(log);
+        let (_winner, score) = game.play(player1, player2);
+        println!("Recursive game score: {score}");
+        if args.stats {
+            println!("{}", game.statistics);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    // use test::Bencher;
+    use std::collections::VecDeque;
+
+    use test::Bencher;
+
+    use super::{play_basic_game, Player, RecursiveCombat, Statistics};
+
+    fn example() -> (VecDeque<u8>, VecDeque<u8>) {
+        (
+            [9, 2, 6, 3, 1].iter().copied().collect(),
+            [5, 8, 4, 7, 10].iter().copied().collect(),
+        )
+    }
+
+    #[test]
+    fn basic_example() {
+        let (player1, player2) = example();
+        assert_eq!(play_basic_game(player1, player2), 306);
+    }
+
+    #[test]
+    fn recursive_example() {
+        let (player1, player2) = example();
+        let mut game = RecursiveCombat::new(None::<Vec<u8>>);
+        assert_eq!(game.play(player1, player2), (Player::Two, 291));
+    }
+
+    #[test]
+    fn repeated_round() {
+        let mut game = RecursiveCombat::new(None::<Vec<u8>>);
+        let (winner, _) = game.play(
+            [43, 19].iter().copied().collect(),
+            [2, 29, 14].iter().copied().collect(),
+        );
+        assert_eq!(winner, Player::One);
+        assert_eq!(game.statistics.repeated_rounds, 1);
+    }
+
+    #[test]
+    fn log_and_statistics() {
+        let (player1, player2) = example();
+        let mut game = RecursiveCombat::new(Some(Vec::new()));
+        game.play(player1, player2);
+
+        let log = String::from_utf8(game.log.take().unwrap()).unwrap();
+        let lines: Vec<_> = log.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Game at depth 0: [9, 2, 6, 3, 1] vs [5, 8, 4, 7, 10]"
+        );
+        assert_eq!(lines[1], "Round 1: [9, 2, 6, 3, 1] vs [5, 8, 4, 7, 10]");
+        assert_eq!(lines[2], "Player 1 wins round 1");
+        assert!(lines.contains(&"  Game at depth 1: [8, 1] vs [3, 4, 10, 9, 7, 5]"));
+        assert_eq!(lines[lines.len() - 1], "Player 2 wins the game");
+
+        assert_eq!(
+            game.statistics,
+            Statistics {
+                sub_games: 3,
+                remembered_sub_games: 1,
+                max_depth: 2,
+                rounds: 28,
+                repeated_rounds: 0,
+                highest_card_wins: 0,
+            }
+        );
+    }
+
+    #[bench]
+    fn bench_recursive_example(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let (player1, player2) = example();
+            RecursiveCombat::new(None::<Vec<u8>>).play(player1, player2)
+        });
+    }
 }
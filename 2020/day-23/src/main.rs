@@ -3,94 +3,221 @@
 
 extern crate test;
 
-use clap::{crate_name, App, Arg};
+use std::{convert::TryFrom, io::BufRead};
 
-fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<INPUT>"))
-        .arg(Arg::from_usage("<STEPS>"))
-        .get_matches();
-
-    let mut next_cup = Vec::new();
-    next_cup.resize(1_000_001, 0);
-
-    let mut max = 0;
-    let mut head = 0;
-    let mut tail = 0;
-    for value in args.value_of("INPUT").unwrap().chars().map(|character| {
-        String::from(character)
-            .parse::<u32>()
-            .expect("Failed to parse cup as u8")
-    }) {
-        max = max.max(value);
-        if head == 0 {
-            head = value;
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Number of moves to make with just the labeled cups in part 1
+    #[arg(long, default_value_t = 100)]
+    moves: usize,
+
+    /// Number of cups once the labeled cups are extended in part 2
+    #[arg(long, default_value_t = 1_000_000)]
+    extended_cups: u32,
+
+    /// Number of moves to make with the extended cups in part 2
+    #[arg(long, default_value_t = 10_000_000)]
+    extended_moves: usize,
+}
+
+fn parse_labels(labels: &str) -> Vec<u32> {
+    let cups: Vec<u32> = labels
+        .chars()
+        .map(|character| {
+            character
+                .to_digit(10)
+                .unwrap_or_else(|| panic!("Failed to parse cup label [{}]", character))
+        })
+        .collect();
+
+    // The labels have to be exactly the cups from 1 up to however many there are
+    let mut sorted = cups.clone();
+    sorted.sort_unstable();
+    assert!(
+        sorted
+            .iter()
+            .zip(1..)
+            .all(|(cup, expected)| *cup == expected),
+        "Expected each cup from 1 to {} exactly once in [{}]",
+        cups.len(),
+        labels
+    );
+
+    cups
+}
+
+// A circle of cups stored as the label of the cup after each one, indexed by label. Labels start
+// at 1, so index 0 is unused.
+struct CupCircle {
+    next_cup: Vec<u32>,
+    current: u32,
+}
+
+impl CupCircle {
+    // Places the labeled cups, followed by increasing labels until there are cup_count cups
+    fn new(labels: &[u32], cup_count: u32) -> Self {
+        let label_count = u32::try_from(labels.len()).expect("Failed to fit label count in u32");
+        assert!(label_count > 0, "Expected at least one cup label");
+        assert!(
+            cup_count >= label_count,
+            "Expected at least {} cups but was asked for {}",
+            label_count,
+            cup_count
+        );
+
+        let order = labels.iter().copied().chain(label_count + 1..=cup_count);
+        let mut next_cup = vec![0; cup_count as usize + 1];
+        // Start from the last cup so the loop also closes the circle
+        let mut tail = if cup_count > label_count {
+            cup_count
+        } else {
+            labels[labels.len() - 1]
+        };
+        for cup in order {
+            next_cup[tail as usize] = cup;
+            tail = cup;
         }
-        if tail != 0 {
-            next_cup[tail as usize] = value;
+
+        Self {
+            next_cup,
+            current: labels[0],
         }
-        tail = value;
     }
 
-    // let cup_count = max;
-
-    let cup_count = 1_000_000;
-    for value in max + 1..=cup_count {
-        next_cup[tail as usize] = value;
-        tail = value;
+    fn cup_count(&self) -> u32 {
+        u32::try_from(self.next_cup.len() - 1).expect("Failed to fit cup count in u32")
     }
 
-    // Complete the circular list
-    next_cup[tail as usize] = head;
+    fn next(&self, cup: u32) -> u32 {
+        self.next_cup[cup as usize]
+    }
 
-    let steps: usize = args.value_of("STEPS").unwrap().parse().unwrap();
+    fn play(&mut self, moves: usize) {
+        let cup_count = self.cup_count();
+        if cup_count < 5 {
+            // There has to be a destination left after picking up three cups
+            assert_eq!(moves, 0, "Need at least 5 cups to make a move");
+            return;
+        }
 
-    let mut current = head;
-    for _ in 0..steps {
-        let mut pick_cursor = current;
-        let mut picked = [0; 3];
-        for pick in &mut picked {
-            pick_cursor = next_cup[pick_cursor as usize];
-            *pick = pick_cursor;
+        for _ in 0..moves {
+            let first = self.next(self.current);
+            let second = self.next(first);
+            let third = self.next(second);
+            self.next_cup[self.current as usize] = self.next(third);
+
+            let mut destination = self.current;
+            loop {
+                destination = if destination == 1 {
+                    cup_count
+                } else {
+                    destination - 1
+                };
+                if destination != first && destination != second && destination != third {
+                    break;
+                }
+            }
+
+            self.next_cup[third as usize] = self.next(destination);
+            self.next_cup[destination as usize] = first;
+
+            self.current = self.next(self.current);
         }
-        next_cup[current as usize] = next_cup[pick_cursor as usize];
+    }
 
-        let mut destination = (current + cup_count - 2) % cup_count + 1;
-        while picked.iter().any(|value| *value == destination) {
-            destination = (destination + cup_count - 2) % cup_count + 1
+    // The labels of every cup after cup 1, in order
+    fn labels_after_one(&self) -> String {
+        let mut labels = String::new();
+        let mut cup = self.next(1);
+        while cup != 1 {
+            labels.push_str(&cup.to_string());
+            cup = self.next(cup);
         }
+        labels
+    }
 
-        let destination_next = next_cup[destination as usize];
-        next_cup[destination as usize] = picked[0];
-        next_cup[picked[picked.len() - 1] as usize] = destination_next;
+    // The product of the two cups after cup 1
+    fn product_after_one(&self) -> u64 {
+        let first = self.next(1);
+        u64::from(first) * u64::from(self.next(first))
+    }
+}
 
-        current = next_cup[current as usize];
+fn main() {
+    let args = Args::parse();
+
+    let mut labels = String::new();
+    open_input(args.filename.as_deref())
+        .read_line(&mut labels)
+        .expect("Failed to read cup labels");
+    let labels = parse_labels(labels.trim());
+
+    if args.part != Some(2) {
+        let mut circle = CupCircle::new(&labels, u32::try_from(labels.len()).unwrap());
+        circle.play(args.moves);
+        println!("Labels: {}", circle.labels_after_one());
     }
+    if args.part != Some(1) {
+        let mut circle = CupCircle::new(&labels, args.extended_cups);
+        circle.play(args.extended_moves);
+        println!("Product: {}", circle.product_after_one());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::Bencher;
+
+    use super::{parse_labels, CupCircle};
 
-    while current != 1 {
-        current = next_cup[current as usize];
+    fn play(cup_count: u32, moves: usize) -> CupCircle {
+        let mut circle = CupCircle::new(&parse_labels("389125467"), cup_count);
+        circle.play(moves);
+        circle
     }
 
-    current = next_cup[current as usize];
+    #[test]
+    fn example_10_moves() {
+        assert_eq!(play(9, 10).labels_after_one(), "92658374");
+    }
 
-    /*
-    for _ in 0..cup_count - 1 {
-        print!("{}", current);
-        current = next_cup[current];
+    #[test]
+    fn example_100_moves() {
+        assert_eq!(play(9, 100).labels_after_one(), "67384529");
     }
-    println!();
-    */
 
-    let mut product = 1;
-    for _ in 0..2 {
-        product *= u64::from(current);
-        current = next_cup[current as usize];
+    #[test]
+    fn example_10_million_moves() {
+        assert_eq!(
+            play(1_000_000, 10_000_000).product_after_one(),
+            149_245_887_792
+        );
     }
 
-    println!("Product: {}", product);
-}
+    #[test]
+    fn extended_circle() {
+        let circle = CupCircle::new(&parse_labels("312"), 5);
+        assert_eq!(circle.labels_after_one(), "2453");
+    }
 
-#[cfg(test)]
-mod tests {
-    // use test::Bencher;
+    #[test]
+    #[should_panic(expected = "Expected each cup from 1 to 3 exactly once")]
+    fn missing_label() {
+        parse_labels("124");
+    }
+
+    #[bench]
+    fn bench_example_million_moves(bencher: &mut Bencher) {
+        bencher.iter(|| play(1_000_000, 1_000_000).product_after_one());
+    }
 }
@@ -6,9 +6,23 @@ extern crate test;
 use std::convert::TryInto;
 
 use bit_set::BitSet;
-use clap::{crate_name, App, Arg};
+use clap::Parser;
 use common::LineReader;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Number of days to evolve the floor for
+    #[arg(short, long, default_value_t = 100)]
+    days: u32,
+}
+
 enum Direction {
     East,
     Southeast,
@@ -43,7 +57,7 @@ impl<'a> DirectionIterator<'a> {
     }
 }
 
-impl<'a> Iterator for DirectionIterator<'a> {
+impl Iterator for DirectionIterator<'_> {
     type Item = Direction;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -198,13 +212,11 @@ fn evolve_tiles(black_tiles: &mut BitSet) {
 }
 
 fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<FILE>"))
-        .get_matches();
+    let args = Args::parse();
 
     let mut black_tiles = BitSet::new();
 
-    let mut reader = LineReader::new(args.value_of("FILE").unwrap());
+    let mut reader = LineReader::new(args.filename.as_deref());
     reader.read_with(|line| {
         let coordinate = get_coordinate(line);
         if !black_tiles.remove(coordinate.get_address() as usize) {
@@ -212,14 +224,20 @@ fn main() {
         }
     });
 
-    println!("{} tiles remain flipped", black_tiles.len());
+    if args.part == 1 {
+        println!("{} tiles remain flipped", black_tiles.len());
+        return;
+    }
 
-    let days = 100;
-    for _day in 1..=days {
+    for _day in 1..=args.days {
         evolve_tiles(&mut black_tiles);
     }
 
-    println!("After {} days, {} tiles are black", days, black_tiles.len());
+    println!(
+        "After {} days, {} tiles are black",
+        args.days,
+        black_tiles.len()
+    );
 }
 
 #[cfg(test)]
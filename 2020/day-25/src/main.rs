@@ -3,12 +3,21 @@
 
 extern crate test;
 
-use clap::{crate_name, App, Arg};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 struct Transformer {
     subject: u64,
     value: u64,
-    loop_count: u32
+    loop_count: u32,
 }
 
 impl Transformer {
@@ -22,7 +31,7 @@ impl Transformer {
 
     fn run_loop(&mut self) {
         self.value *= self.subject;
-        self.value %= 20201227;
+        self.value %= 20_201_227;
         self.loop_count += 1;
     }
 
@@ -36,13 +45,20 @@ impl Transformer {
 }
 
 fn main() {
-    let args = App::new(crate_name!())
-        .arg(Arg::from_usage("<CARD>"))
-        .arg(Arg::from_usage("<ROOM>"))
-        .get_matches();
+    let args = Args::parse();
 
-    let card_public_key: u64 = args.value_of("CARD").unwrap().parse().expect("Failed to parse card public key as u64");
-    let room_public_key: u64 = args.value_of("ROOM").unwrap().parse().expect("Failed to parse room public key as u64");
+    let mut lines = open_input(args.filename.as_deref()).lines();
+    let mut read_key = || -> u64 {
+        lines
+            .next()
+            .expect("Missing public key")
+            .expect("Failed to read line")
+            .trim()
+            .parse()
+            .expect("Failed to parse public key as u64")
+    };
+    let card_public_key = read_key();
+    let room_public_key = read_key();
 
     let mut card_transformer = Transformer::new(7);
     let mut room_transformer = Transformer::new(7);
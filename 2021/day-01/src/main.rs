@@ -1,9 +1,17 @@
-use std::{
-    collections::VecDeque,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use std::{collections::VecDeque, io::BufRead, iter::Iterator};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn count_increases<I: Iterator<Item = i32>>(measurements: I) -> usize {
     measurements
@@ -66,18 +74,25 @@ impl<I: Iterator<Item = i32>> SumIterator<I> {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let measurements = reader
+    let args = Args::parse();
+
+    let measurements: Vec<_> = open_input(args.filename.as_deref())
         .lines()
-        .into_iter()
-        .map(|line| line.unwrap().parse::<i32>().unwrap());
-
-    // println!("Increases: {}", count_increases(measurements));
-    println!(
-        "Window increases: {}",
-        count_increases(SumIterator::new(measurements, 3))
-    )
+        .map(|line| line.unwrap().parse::<i32>().unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Increases: {}",
+            count_increases(measurements.iter().copied())
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Window increases: {}",
+            count_increases(SumIterator::new(measurements.into_iter(), 3))
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn naive_position(
     (distance, depth, _aim): (i32, i32, i32),
@@ -46,12 +56,25 @@ fn compute_position<I: Iterator<Item = String>>(commands: I, use_aim: bool) -> i
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    println!(
-        "Position: {}",
-        compute_position(reader.lines().map(|line| line.unwrap()), true)
-    );
+    let args = Args::parse();
+
+    let commands: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Position: {}",
+            compute_position(commands.iter().cloned(), false)
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Position with aim: {}",
+            compute_position(commands.into_iter(), true)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn calculate_power_consumption<I: Iterator<Item = String>>(lines: I) -> u32 {
     let mut counters = Vec::new();
@@ -69,12 +79,25 @@ fn calculate_life_support_rating<I: Iterator<Item = String>>(lines: I) -> u32 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    println!(
-        "Life support rating: {}",
-        calculate_life_support_rating(reader.lines().into_iter().map(|line| line.unwrap()))
-    );
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Power consumption: {}",
+            calculate_power_consumption(lines.iter().cloned())
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Life support rating: {}",
+            calculate_life_support_rating(lines.into_iter())
+        );
+    }
 }
 
 #[cfg(test)]
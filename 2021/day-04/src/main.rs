@@ -1,10 +1,18 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashSet, io::BufRead};
 
 use bit_set::BitSet;
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Debug)]
 struct Board {
@@ -55,32 +63,11 @@ impl Board {
     }
 }
 
-fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().map(|line| line.unwrap());
-
-    let calls = lines.next().unwrap();
-    let calls = calls.split(',').map(|c| c.parse::<u8>().unwrap());
-    lines.next();
-
-    let mut boards = Vec::new();
-    while let Some(board) = Board::from_lines(&mut lines) {
-        boards.push(board)
-    }
-
-    // for called in calls {
-    //     for board in &mut boards {
-    //         let line_completed = board.mark_number(called);
-    //         if line_completed {
-    //             println!("Final score: {}", board.get_unmarked_sum() * called as u16);
-    //             return;
-    //         }
-    //     }
-    // }
+// The score of each board when it wins, in the order they win
+fn winning_scores<I: Iterator<Item = u8>>(boards: &mut [Board], calls: I) -> Vec<u16> {
     let mut completed_boards = HashSet::new();
+    let mut scores = Vec::new();
     for called in calls {
-        let num_boards = boards.len();
         for (index, board) in &mut boards.iter_mut().enumerate() {
             if completed_boards.contains(&index) {
                 continue;
@@ -89,12 +76,36 @@ fn main() {
             let line_completed = board.mark_number(called);
             if line_completed {
                 completed_boards.insert(index);
-                if completed_boards.len() == num_boards {
-                    println!("Final score: {}", board.get_unmarked_sum() * called as u16);
-                }
+                scores.push(board.get_unmarked_sum() * called as u16);
             }
         }
     }
+    scores
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap());
+
+    let calls = lines.next().unwrap();
+    let calls = calls.split(',').map(|c| c.parse::<u8>().unwrap());
+    lines.next();
+
+    let mut boards = Vec::new();
+    while let Some(board) = Board::from_lines(&mut lines) {
+        boards.push(board)
+    }
+
+    let scores = winning_scores(&mut boards, calls);
+    if args.part != Some(2) {
+        println!("Final score: {}", scores.first().unwrap());
+    }
+    if args.part != Some(1) {
+        println!("Final score: {}", scores.last().unwrap());
+    }
 }
 
 #[cfg(test)]
@@ -197,4 +208,17 @@ mod test {
 
         unreachable!()
     }
+
+    #[test]
+    fn test_winning_scores() {
+        let mut lines = get_boards().into_iter();
+        let mut boards = Vec::new();
+        while let Some(board) = Board::from_lines(&mut lines) {
+            boards.push(board);
+        }
+
+        let scores = winning_scores(&mut boards, get_calls().into_iter());
+        assert_eq!(scores.first(), Some(&4512));
+        assert_eq!(scores.last(), Some(&1924));
+    }
 }
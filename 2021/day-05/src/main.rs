@@ -1,11 +1,19 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashMap, io::BufRead};
 
+use clap::Parser;
+use common::open_input;
 use nalgebra::{point, Point2, Vector2};
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 fn get_direction_vector(p0: Point2<i16>, p1: Point2<i16>) -> Vector2<i16> {
     let mut vector = p1 - p0;
     vector.x = vector.x.clamp(-1, 1);
@@ -13,7 +21,7 @@ fn get_direction_vector(p0: Point2<i16>, p1: Point2<i16>) -> Vector2<i16> {
     vector
 }
 
-fn count_overlaps<I: Iterator<Item = String>>(lines: I) -> usize {
+fn count_overlaps<I: Iterator<Item = String>>(lines: I, include_diagonals: bool) -> usize {
     lines
         .map(|line| {
             let mut split = line.split(" -> ").map(|position| {
@@ -24,7 +32,7 @@ fn count_overlaps<I: Iterator<Item = String>>(lines: I) -> usize {
             });
             (split.next().unwrap(), split.next().unwrap())
         })
-        // .filter(|(p0, p1)| p0.x == p1.x || p0.y == p1.y)
+        .filter(|(p0, p1)| include_diagonals || p0.x == p1.x || p0.y == p1.y)
         .fold(HashMap::new(), |mut map, line| {
             let (mut start, end) = line;
             let direction_vector = get_direction_vector(start, end);
@@ -41,12 +49,19 @@ fn count_overlaps<I: Iterator<Item = String>>(lines: I) -> usize {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    println!(
-        "Overlaps: {}",
-        count_overlaps(reader.lines().map(|line| line.unwrap()))
-    );
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!("Overlaps: {}", count_overlaps(lines.iter().cloned(), false));
+    }
+    if args.part != Some(1) {
+        println!("Overlaps: {}", count_overlaps(lines.into_iter(), true));
+    }
 }
 
 #[cfg(test)]
@@ -112,6 +127,11 @@ mod test {
 
     #[test]
     fn test_rectilinear_vents() {
-        assert_eq!(count_overlaps(get_sample_input().into_iter()), 5);
+        assert_eq!(count_overlaps(get_sample_input().into_iter(), false), 5);
+    }
+
+    #[test]
+    fn test_all_vents() {
+        assert_eq!(count_overlaps(get_sample_input().into_iter(), true), 12);
     }
 }
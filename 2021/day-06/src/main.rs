@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 const GESTATION_PERIOD: i32 = 7;
 const ADOLESCENCE_DELAY: i32 = 2;
@@ -27,23 +37,33 @@ fn count_descendents(cache: &mut Vec<Option<usize>>, days: i32) -> usize {
     descendants
 }
 
-const DAYS: i32 = 256;
+fn count_fish(cache: &mut Vec<Option<usize>>, phases: &[i32], days: i32) -> usize {
+    phases
+        .iter()
+        .map(|phase| 1 + count_descendents(cache, days - phase))
+        .sum()
+}
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+
+    let phases: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .next()
+        .unwrap()
+        .unwrap()
+        .split(',')
+        .map(|phase| phase.parse::<i32>().unwrap())
+        .collect();
+
+    // The cache is indexed by days remaining, so it carries over between parts
     let mut cache = Vec::new();
-    println!(
-        "Fish: {}",
-        reader
-            .lines()
-            .next()
-            .unwrap()
-            .unwrap()
-            .split(',')
-            .map(|phase| 1 + count_descendents(&mut cache, DAYS - phase.parse::<i32>().unwrap()))
-            .sum::<usize>()
-    )
+    if args.part != Some(2) {
+        println!("Fish: {}", count_fish(&mut cache, &phases, 80));
+    }
+    if args.part != Some(1) {
+        println!("Fish: {}", count_fish(&mut cache, &phases, 256));
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn get_cumulative_sums(slice: &[i32]) -> Vec<i32> {
     let mut sum = 0;
@@ -105,9 +115,9 @@ fn find_minimal_fuel(positions: &[i32], use_triangle_fuel_consumption: bool) ->
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let mut positions: Vec<_> = reader
+    let args = Args::parse();
+
+    let mut positions: Vec<_> = open_input(args.filename.as_deref())
         .lines()
         .next()
         .unwrap()
@@ -116,10 +126,18 @@ fn main() {
         .map(|position| position.parse::<i32>().unwrap())
         .collect();
     positions.sort_unstable();
-    println!(
-        "Minimal fuel: {}",
-        find_minimal_fuel(positions.as_ref(), true)
-    );
+    if args.part != Some(2) {
+        println!(
+            "Minimal fuel: {}",
+            find_minimal_fuel(positions.as_ref(), false)
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Minimal fuel: {}",
+            find_minimal_fuel(positions.as_ref(), true)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn bits_from_letters(letters: &str) -> u8 {
     let mut bits = 0;
@@ -107,22 +117,28 @@ fn get_output_value(line: &str) -> i32 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    // println!(
-    //     "Easy digits: {}",
-    //     reader
-    //         .lines()
-    //         .map(|line| count_easy_digits(&line.unwrap()))
-    //         .sum::<usize>()
-    // );
-    println!(
-        "Sum: {}",
-        reader
-            .lines()
-            .map(|line| get_output_value(&line.unwrap()))
-            .sum::<i32>()
-    );
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Easy digits: {}",
+            lines
+                .iter()
+                .map(|line| count_easy_digits(line))
+                .sum::<usize>()
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Sum: {}",
+            lines.iter().map(|line| get_output_value(line)).sum::<i32>()
+        );
+    }
 }
 
 #[cfg(test)]
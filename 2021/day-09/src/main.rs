@@ -1,10 +1,22 @@
 use std::{
     collections::{HashSet, VecDeque},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
 };
 
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 fn get_low_points(lines: &[Vec<u8>]) -> Vec<(usize, usize)> {
     lines
         .iter()
@@ -101,16 +113,20 @@ fn get_basin_size_product(lines: &[Vec<u8>]) -> usize {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let lines: Vec<_> = reader
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
         .lines()
         .map(|l| l.unwrap())
         .into_iter()
         .map(|s| s.into_bytes())
         .collect();
-    // println!("Risk level: {}", get_low_point_risk_level(&lines));
-    println!("Largest basin product: {}", get_basin_size_product(&lines));
+    if args.part != Some(2) {
+        println!("Risk level: {}", get_low_point_risk_level(&lines));
+    }
+    if args.part != Some(1) {
+        println!("Largest basin product: {}", get_basin_size_product(&lines));
+    }
 }
 
 #[cfg(test)]
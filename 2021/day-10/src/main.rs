@@ -1,8 +1,17 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashMap, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Debug, Eq, PartialEq)]
 enum ParseStatus {
@@ -64,16 +73,25 @@ fn get_incomplete_score<I: Iterator<Item = String>>(lines: I) -> i64 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    // println!(
-    //     "Corrupted score: {}",
-    //     get_corrupted_score(reader.lines().map(|l| l.unwrap()))
-    // );
-    println!(
-        "Incomplete score: {}",
-        get_incomplete_score(reader.lines().map(|l| l.unwrap()))
-    );
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|l| l.unwrap())
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Corrupted score: {}",
+            get_corrupted_score(lines.iter().cloned())
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Incomplete score: {}",
+            get_incomplete_score(lines.into_iter())
+        );
+    }
 }
 
 #[cfg(test)]
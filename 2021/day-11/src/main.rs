@@ -1,7 +1,17 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn flash_cell(lines: &mut [Vec<u8>], row: usize, column: usize) {
     if lines[row][column] != 10 {
@@ -63,28 +73,34 @@ fn run_generation(lines: &mut [Vec<u8>]) -> i32 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let mut lines: Vec<Vec<u8>> = reader
+    let args = Args::parse();
+
+    let lines: Vec<Vec<u8>> = open_input(args.filename.as_deref())
         .lines()
         .map(|line| line.unwrap())
         .into_iter()
         .map(|line| line.into_bytes().into_iter().map(|b| b - b'0').collect())
         .collect();
 
-    // let mut flashes = 0;
-    // for _ in 0..100 {
-    //     flashes += run_generation(&mut lines);
-    // }
-    // println!("Flashes: {}", flashes)
-
-    let mut step = 1;
-    loop {
-        if run_generation(&mut lines) == 100 {
-            println!("Step {}", step);
-            break;
+    if args.part != Some(2) {
+        let mut lines = lines.clone();
+        let mut flashes = 0;
+        for _ in 0..100 {
+            flashes += run_generation(&mut lines);
+        }
+        println!("Flashes: {}", flashes)
+    }
+
+    if args.part != Some(1) {
+        let mut lines = lines;
+        let mut step = 1;
+        loop {
+            if run_generation(&mut lines) == 100 {
+                println!("Step {}", step);
+                break;
+            }
+            step += 1;
         }
-        step += 1;
     }
 }
 
@@ -1,11 +1,20 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashMap, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 const END: u8 = 12;
 const START: u8 = END - 1;
@@ -137,10 +146,19 @@ fn count_paths(neighbors: &TinyMap, allow_duplicates: bool) -> usize {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let neighbors = parse_neighbors(reader.lines().map(|l| l.unwrap()));
-    println!("Paths: {}", count_paths(&neighbors, true))
+    let args = Args::parse();
+
+    let neighbors = parse_neighbors(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|l| l.unwrap()),
+    );
+    if args.part != Some(2) {
+        println!("Paths: {}", count_paths(&neighbors, false))
+    }
+    if args.part != Some(1) {
+        println!("Paths: {}", count_paths(&neighbors, true))
+    }
 }
 
 #[cfg(test)]
@@ -236,9 +254,10 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             let neighbors = parse_neighbors(lines.clone().into_iter());
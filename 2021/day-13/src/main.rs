@@ -1,8 +1,17 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashSet, io::BufRead};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Debug, Eq, PartialEq)]
 enum Command {
@@ -92,11 +101,22 @@ fn print_dots(coordinates: &mut [(u16, u16)], commands: &[Command]) {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let (mut coordinates, commands) = parse_input(reader.lines().map(|line| line.unwrap()));
-    // println!("Dots: {}", count_dots(&mut coordinates, &commands[0..1]));
-    print_dots(&mut coordinates, &commands);
+    let args = Args::parse();
+
+    let (mut coordinates, commands) = parse_input(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|line| line.unwrap()),
+    );
+    if args.part != Some(2) {
+        println!(
+            "Dots: {}",
+            get_unique_dots(&mut coordinates.clone(), &commands[0..1]).len()
+        );
+    }
+    if args.part != Some(1) {
+        print_dots(&mut coordinates, &commands);
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,20 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    mem::swap,
-};
+use std::{collections::HashMap, io::BufRead, mem::swap};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct Token(u8);
@@ -129,14 +137,25 @@ fn get_difference(
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let (mut template, rules, last_character) =
-        parse_input(reader.lines().map(|line| line.unwrap()));
-    println!(
-        "Difference: {}",
-        get_difference(&rules, &mut template, last_character, 40)
-    )
+    let args = Args::parse();
+
+    let (template, rules, last_character) = parse_input(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|line| line.unwrap()),
+    );
+    if args.part != Some(2) {
+        println!(
+            "Difference: {}",
+            get_difference(&rules, &mut template.clone(), last_character, 10)
+        )
+    }
+    if args.part != Some(1) {
+        println!(
+            "Difference: {}",
+            get_difference(&rules, &mut template.clone(), last_character, 40)
+        )
+    }
 }
 
 #[cfg(test)]
@@ -191,9 +210,10 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             let (mut template, rules, last_character) = parse_input(lines.clone().into_iter());
@@ -3,10 +3,22 @@ extern crate test;
 
 use std::{
     collections::{BinaryHeap, VecDeque},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
 };
 
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 fn parse_input<I: Iterator<Item = String>>(lines: I) -> Vec<Vec<u8>> {
     lines
         .map(|line| line.as_bytes().iter().map(|b| b - b'0').collect())
@@ -62,6 +74,9 @@ fn propagate_from_neighbors(risk_to_enter: &[Vec<u8>], lowest_risk: &mut [Vec<u1
 
     for row in 0..risk_to_enter.len() {
         for column in 0..risk_to_enter.len() {
+            // Check every neighbor, since the first improvement found isn't necessarily the best
+            let mut improved = false;
+
             // Above
             if row > 0
                 && (lowest_risk[row - 1][column] + risk_to_enter[row][column] as u16)
@@ -69,8 +84,7 @@ fn propagate_from_neighbors(risk_to_enter: &[Vec<u8>], lowest_risk: &mut [Vec<u1
             {
                 lowest_risk[row][column] =
                     lowest_risk[row - 1][column] + risk_to_enter[row][column] as u16;
-                updates.push_back((row, column));
-                continue;
+                improved = true;
             }
 
             // Left
@@ -80,8 +94,7 @@ fn propagate_from_neighbors(risk_to_enter: &[Vec<u8>], lowest_risk: &mut [Vec<u1
             {
                 lowest_risk[row][column] =
                     lowest_risk[row][column - 1] + risk_to_enter[row][column] as u16;
-                updates.push_back((row, column));
-                continue;
+                improved = true;
             }
 
             // Right
@@ -91,8 +104,7 @@ fn propagate_from_neighbors(risk_to_enter: &[Vec<u8>], lowest_risk: &mut [Vec<u1
             {
                 lowest_risk[row][column] =
                     lowest_risk[row][column + 1] + risk_to_enter[row][column] as u16;
-                updates.push_back((row, column));
-                continue;
+                improved = true;
             }
 
             // Below
@@ -102,8 +114,11 @@ fn propagate_from_neighbors(risk_to_enter: &[Vec<u8>], lowest_risk: &mut [Vec<u1
             {
                 lowest_risk[row][column] =
                     lowest_risk[row + 1][column] + risk_to_enter[row][column] as u16;
+                improved = true;
+            }
+
+            if improved {
                 updates.push_back((row, column));
-                continue;
             }
         }
     }
@@ -297,19 +312,28 @@ fn get_lowest_risk_a_star(risk_to_enter: &[Vec<u8>]) -> u16 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let risk_to_enter = parse_input(reader.lines().map(|line| line.unwrap()));
-    // println!("Lowest risk: {}", get_lowest_risk(&risk_to_enter));
-    println!(
-        "Lowest risk: {}",
-        get_lowest_risk(&(expand_map(&risk_to_enter)))
+    let args = Args::parse();
+
+    let risk_to_enter = parse_input(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|line| line.unwrap()),
     );
+    if args.part != Some(2) {
+        println!("Lowest risk: {}", get_lowest_risk(&risk_to_enter));
+    }
+    if args.part != Some(1) {
+        println!(
+            "Lowest risk: {}",
+            get_lowest_risk(&(expand_map(&risk_to_enter)))
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use test::Bencher;
 
     fn get_example() -> [String; 10] {
@@ -386,11 +410,28 @@ mod tests {
         assert_eq!(get_lowest_risk_a_star(&expand_map(&risk_to_enter)), 315);
     }
 
+    fn map_strategy() -> impl Strategy<Value = Vec<Vec<u8>>> {
+        (1usize..16)
+            .prop_flat_map(|size| prop::collection::vec(prop::collection::vec(1u8..=9, size), size))
+    }
+
+    proptest! {
+        // The relaxation search and A* should always find the same lowest risk
+        #[test]
+        fn test_lowest_risk_matches_a_star(risk_to_enter in map_strategy()) {
+            prop_assert_eq!(
+                get_lowest_risk(&risk_to_enter),
+                get_lowest_risk_a_star(&risk_to_enter)
+            );
+        }
+    }
+
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             let risk_to_enter = parse_input(lines.clone().into_iter());
@@ -400,9 +441,10 @@ mod tests {
 
     #[bench]
     fn bench_input_a_star(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             let risk_to_enter = parse_input(lines.clone().into_iter());
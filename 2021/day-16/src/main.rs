@@ -1,10 +1,20 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 use bitvec::prelude::*;
 
@@ -196,20 +206,20 @@ impl Packet {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    // println!(
-    //     "Version sum: {}",
-    //     Packet::parse_from_binary(&convert_to_binary(reader.lines().next().unwrap().unwrap()))
-    //         .0
-    //         .get_version_sum()
-    // );
-    println!(
-        "Value: {}",
-        Packet::parse_from_binary(&convert_to_binary(reader.lines().next().unwrap().unwrap()))
-            .0
-            .get_value()
-    );
+    let args = Args::parse();
+
+    let message = open_input(args.filename.as_deref())
+        .lines()
+        .next()
+        .unwrap()
+        .unwrap();
+    let packet = Packet::parse_from_binary(&convert_to_binary(message)).0;
+    if args.part != Some(2) {
+        println!("Version sum: {}", packet.get_version_sum());
+    }
+    if args.part != Some(1) {
+        println!("Value: {}", packet.get_value());
+    }
 }
 
 #[cfg(test)]
@@ -377,9 +387,11 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let input = reader.lines().next().unwrap().unwrap();
+        let input = open_input(Some("input.txt"))
+            .lines()
+            .next()
+            .unwrap()
+            .unwrap();
 
         b.iter(|| {
             assert_eq!(
@@ -3,9 +3,38 @@ extern crate test;
 
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    io::BufRead,
     ops::RangeInclusive,
 };
 
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+fn parse_range(range: &str) -> RangeInclusive<i32> {
+    let (start, end) = range[2..].split_once("..").unwrap();
+    start.parse().unwrap()..=end.parse().unwrap()
+}
+
+// Parses "target area: x=20..30, y=-10..-5" into its x and y ranges
+fn parse_target(line: &str) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+    let (x, y) = line
+        .trim()
+        .trim_start_matches("target area: ")
+        .split_once(", ")
+        .unwrap();
+    (parse_range(x), parse_range(y))
+}
+
 fn get_possible_values(
     target_x: RangeInclusive<i32>,
     target_y: RangeInclusive<i32>,
@@ -94,11 +123,34 @@ fn get_possible_values(
     possible_values
 }
 
+// The probe rises until its vertical velocity runs out, so the highest point comes from the fastest
+// upward launch that still hits the target
+fn get_highest_y(possible_values: &HashSet<(i32, i32)>) -> i32 {
+    let vy = possible_values.iter().map(|(_, vy)| *vy).max().unwrap();
+    if vy > 0 {
+        vy * (vy + 1) / 2
+    } else {
+        0
+    }
+}
+
 fn main() {
-    println!(
-        "Possibilities: {}",
-        get_possible_values(241..=273, -97..=-63).len()
-    );
+    let args = Args::parse();
+
+    let line = open_input(args.filename.as_deref())
+        .lines()
+        .next()
+        .unwrap()
+        .unwrap();
+    let (target_x, target_y) = parse_target(&line);
+    let possible_values = get_possible_values(target_x, target_y);
+
+    if args.part != Some(2) {
+        println!("Highest y: {}", get_highest_y(&possible_values));
+    }
+    if args.part != Some(1) {
+        println!("Possibilities: {}", possible_values.len());
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +163,19 @@ mod tests {
         assert_eq!(get_possible_values(20..=30, -10..=-5).len(), 112);
     }
 
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("target area: x=20..30, y=-10..-5"),
+            (20..=30, -10..=-5)
+        );
+    }
+
+    #[test]
+    fn test_highest_y() {
+        assert_eq!(get_highest_y(&get_possible_values(20..=30, -10..=-5)), 45);
+    }
+
     #[bench]
     fn bench_input(b: &mut Bencher) {
         b.iter(|| {
@@ -4,11 +4,23 @@ extern crate test;
 use std::{
     cell::RefCell,
     fmt::Display,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     rc::{Rc, Weak},
 };
 
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 enum Contents {
     Regular(i32),
     Pair(Rc<RefCell<Node>>, Rc<RefCell<Node>>),
@@ -307,16 +319,21 @@ fn get_maximum_magnitude(numbers: &[String]) -> i32 {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    // println!(
-    //     "Magnitude: {}",
-    //     reduce_list(reader.lines().map(|line| line.unwrap()))
-    //         .borrow()
-    //         .get_magnitude()
-    // )
-    let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
-    println!("Maximum magnitude: {}", get_maximum_magnitude(&lines));
+    let args = Args::parse();
+
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| line.unwrap())
+        .collect();
+    if args.part != Some(2) {
+        println!(
+            "Magnitude: {}",
+            reduce_list(lines.iter().cloned()).borrow().get_magnitude()
+        );
+    }
+    if args.part != Some(1) {
+        println!("Maximum magnitude: {}", get_maximum_magnitude(&lines));
+    }
 }
 
 #[cfg(test)]
@@ -475,9 +492,10 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             assert_eq!(get_maximum_magnitude(&lines), 4638);
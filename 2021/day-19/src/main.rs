@@ -1,14 +1,22 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::HashSet, io::BufRead};
 
+use clap::Parser;
+use common::open_input;
 use nalgebra::{point, Point3};
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy)]
 enum Orientation {
     PositiveZUpPositiveY,
@@ -281,29 +289,39 @@ fn resolve_scanners(scanners: &mut Vec<Scanner>) {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let mut scanners = parse_scanners(reader.lines().map(|line| line.unwrap()));
-    println!("Found {} scanners", scanners.len());
+    let args = Args::parse();
+
+    let mut scanners = parse_scanners(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|line| line.unwrap()),
+    );
+    eprintln!("Found {} scanners", scanners.len());
     resolve_scanners(&mut scanners);
-    // let unique: HashSet<_> = scanners
-    //     .iter()
-    //     .flat_map(|scanner| scanner.absolute_beacons.iter())
-    //     .collect();
-    // println!("Found {} unique points", unique.len());
-    let mut max_distance = 0;
-    for i in 0..scanners.len() {
-        let left_position = scanners[i].absolute_position;
-        for scanner in scanners.iter().skip(i + 1) {
-            let right_position = scanner.absolute_position;
-            max_distance = max_distance.max(
-                (left_position.x - right_position.x).abs()
-                    + (left_position.y - right_position.y).abs()
-                    + (left_position.z - right_position.z).abs(),
-            )
+
+    if args.part != Some(2) {
+        let unique: HashSet<_> = scanners
+            .iter()
+            .flat_map(|scanner| scanner.absolute_beacons.iter())
+            .collect();
+        println!("Found {} unique points", unique.len());
+    }
+
+    if args.part != Some(1) {
+        let mut max_distance = 0;
+        for i in 0..scanners.len() {
+            let left_position = scanners[i].absolute_position;
+            for scanner in scanners.iter().skip(i + 1) {
+                let right_position = scanner.absolute_position;
+                max_distance = max_distance.max(
+                    (left_position.x - right_position.x).abs()
+                        + (left_position.y - right_position.y).abs()
+                        + (left_position.z - right_position.z).abs(),
+                )
+            }
         }
+        println!("Max distance: {}", max_distance);
     }
-    println!("Max distance: {}", max_distance);
 }
 
 #[cfg(test)]
@@ -1,12 +1,20 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    collections::VecDeque,
-    fs::File,
-    io::{BufRead, BufReader},
-    mem::swap,
-};
+use std::{collections::VecDeque, io::BufRead, mem::swap};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn parse_input<I: Iterator<Item = String>>(mut lines: I) -> ([u8; 512], VecDeque<VecDeque<u8>>) {
     let algorithm: [u8; 512] = lines.next().unwrap().as_bytes().try_into().unwrap();
@@ -135,10 +143,22 @@ fn run_iterations(
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let (algorithm, pixels) = parse_input(reader.lines().map(|line| line.unwrap()));
-    println!("Lit pixels: {}", run_iterations(&algorithm, pixels, 50));
+    let args = Args::parse();
+
+    let (algorithm, pixels) = parse_input(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(|line| line.unwrap()),
+    );
+    if args.part != Some(2) {
+        println!(
+            "Lit pixels: {}",
+            run_iterations(&algorithm, pixels.clone(), 2)
+        );
+    }
+    if args.part != Some(1) {
+        println!("Lit pixels: {}", run_iterations(&algorithm, pixels, 50));
+    }
 }
 
 #[cfg(test)]
@@ -186,9 +206,10 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let lines: Vec<_> = reader.lines().map(|line| line.unwrap()).collect();
+        let lines: Vec<_> = open_input(Some("input.txt"))
+            .lines()
+            .map(|line| line.unwrap())
+            .collect();
 
         b.iter(|| {
             let (algorithm, pixels) = parse_input(lines.clone().into_iter());
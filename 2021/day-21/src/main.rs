@@ -1,4 +1,17 @@
-use std::{collections::HashMap, mem::swap};
+use std::{collections::HashMap, io::BufRead, mem::swap};
+
+use clap::Parser;
+use common::open_input;
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn roll_die(die: &mut i32) -> i32 {
     let roll = *die;
@@ -6,11 +19,10 @@ fn roll_die(die: &mut i32) -> i32 {
     roll
 }
 
-fn play_game() -> i32 {
+fn play_game(mut positions: [i32; 2]) -> i32 {
     let mut die = 1;
     let mut die_rolls = 0;
 
-    let mut positions = [10, 7];
     let mut scores = [0, 0];
 
     let mut current_player = 0;
@@ -35,12 +47,12 @@ struct Universe {
     score: [u8; 2],
 }
 
-fn simulate_multiverse() -> [usize; 2] {
+fn simulate_multiverse(position: [u8; 2]) -> [usize; 2] {
     let mut wins = [0usize; 2];
 
     let mut initial = HashMap::from([(
         Universe {
-            position: [10, 7],
+            position,
             score: [0, 0],
         },
         1,
@@ -75,7 +87,45 @@ fn simulate_multiverse() -> [usize; 2] {
     wins
 }
 
+// Parses "Player 1 starting position: 4"
+fn parse_position(line: &str) -> u8 {
+    line.rsplit_once(": ").unwrap().1.trim().parse().unwrap()
+}
+
 fn main() {
-    println!("One game: {}", play_game());
-    println!("Multiverse: {:?}", simulate_multiverse());
+    let args = Args::parse();
+
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(|line| parse_position(&line.unwrap()));
+    let positions = [lines.next().unwrap(), lines.next().unwrap()];
+
+    if args.part != Some(2) {
+        println!("One game: {}", play_game(positions.map(i32::from)));
+    }
+    if args.part != Some(1) {
+        println!(
+            "Multiverse: {}",
+            simulate_multiverse(positions).into_iter().max().unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_position() {
+        assert_eq!(parse_position("Player 2 starting position: 10"), 10);
+    }
+
+    #[test]
+    fn test_sample() {
+        assert_eq!(play_game([4, 8]), 739785);
+        assert_eq!(
+            simulate_multiverse([4, 8]).into_iter().max().unwrap(),
+            444356092776315
+        );
+    }
 }
@@ -3,15 +3,20 @@
 extern crate test;
 
 use bitvec::prelude::*;
+use clap::Parser;
+use common::open_input;
 
-use std::{
-    cell::RefCell,
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-    ops::Range,
-    rc::Rc,
-};
+use std::{cell::RefCell, collections::HashSet, io::BufRead, ops::Range, rc::Rc};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 trait Intersection {
     fn intersection(&self, other: &Self) -> Option<Self>
@@ -215,13 +220,13 @@ impl PartialEq for Node {
 
 impl Eq for Node {}
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum Command {
     Off,
     On,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct Step {
     command: Command,
     x: Range<i32>,
@@ -414,27 +419,38 @@ fn run_2(steps: &[Step]) -> usize {
     volume
 }
 
+// Whether the step lies within the -50..=50 initialization region. Step ranges are exclusive.
+fn is_initialization_step(step: &Step) -> bool {
+    [&step.x, &step.y, &step.z]
+        .into_iter()
+        .all(|range| range.start >= -50 && range.end <= 51)
+}
+
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let steps = Step::parse_from_lines(reader.lines().map(std::result::Result::unwrap));
-    // let steps: Vec<_> = steps
-    //     .into_iter()
-    //     .filter(|step| {
-    //         step.x.start >= -50
-    //             && step.x.end <= 50
-    //             && step.y.start >= -50
-    //             && step.y.end <= 50
-    //             && step.z.start >= -50
-    //             && step.z.end <= 50
-    //     })
-    //     .collect();
-    println!("{}", run_2(&steps));
+    let args = Args::parse();
+
+    let steps = Step::parse_from_lines(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(std::result::Result::unwrap),
+    );
+    if args.part != Some(2) {
+        let steps: Vec<_> = steps
+            .iter()
+            .filter(|step| is_initialization_step(step))
+            .cloned()
+            .collect();
+        println!("{}", run_2(&steps));
+    }
+    if args.part != Some(1) {
+        println!("{}", run_2(&steps));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use test::Bencher;
 
     fn get_basic_example() -> [String; 4] {
@@ -593,6 +609,41 @@ mod tests {
         assert_eq!(run_steps(&steps), 590784);
     }
 
+    #[test]
+    fn test_initialization_steps() {
+        let steps: Vec<_> = Step::parse_from_lines(get_larger_example().into_iter())
+            .into_iter()
+            .filter(is_initialization_step)
+            .collect();
+        assert_eq!(run_2(&steps), 590_784);
+    }
+
+    fn range_strategy() -> impl Strategy<Value = Range<i32>> {
+        (-16..16, 1..12).prop_map(|(start, length)| start..start + length)
+    }
+
+    fn step_strategy() -> impl Strategy<Value = Step> {
+        (
+            any::<bool>(),
+            range_strategy(),
+            range_strategy(),
+            range_strategy(),
+        )
+            .prop_map(|(on, x, y, z)| {
+                let command = if on { Command::On } else { Command::Off };
+                Step::new(command, x, y, z)
+            })
+    }
+
+    proptest! {
+        // The octree and the coordinate-compressed bitmap should always agree on the lit volume
+        #[test]
+        fn test_run_steps_matches_run_2(steps in prop::collection::vec(step_strategy(), 1..8)) {
+            let volume = usize::try_from(run_steps(&steps)).unwrap();
+            prop_assert_eq!(volume, run_2(&steps));
+        }
+    }
+
     // #[bench]
     // fn bench_input(b: &mut Bencher) {
     //     let file = File::open("input.txt").unwrap();
@@ -7,10 +7,23 @@ extern crate test;
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashSet},
+    io::BufRead,
 };
 
+use clap::Parser;
+use common::open_input;
 use num_traits::FromPrimitive;
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, FromPrimitive, Hash, Ord, PartialEq, PartialOrd)]
 enum Position {
     Hallway00,
@@ -224,7 +237,7 @@ fn get_paths(adjacencies: &HashSet<(Position, Position)>) -> Vec<Vec<Position>>
         }
     }
 
-    println!("Generated {} paths", paths.len());
+    eprintln!("Generated {} paths", paths.len());
 
     paths
 }
@@ -490,12 +503,12 @@ fn organize_amphipods(configuration: Configuration) -> Cost {
         }
 
         if is_complete(configuration) {
-            println!("Found {:?} for {}", configuration, actual_cost);
+            eprintln!("Found {:?} for {}", configuration, actual_cost);
             return actual_cost;
         }
 
         if estimated_cost > max_cost {
-            println!(
+            eprintln!(
                 "max {} popped {} visited {} paths {} complete {} dest {} vdest {} config {} impure {}",
                 estimated_cost,
                 popped,
@@ -572,63 +585,61 @@ fn organize_amphipods(configuration: Configuration) -> Cost {
     0
 }
 
+// Reads the amphipods in each room from the diagram, as rows from the hallway down, each listing
+// rooms A to D
+fn parse_rows<I: Iterator<Item = String>>(lines: I) -> Vec<[u8; 4]> {
+    lines
+        .skip(2)
+        .map(|line| {
+            line.bytes()
+                .filter(u8::is_ascii_uppercase)
+                .collect::<Vec<_>>()
+        })
+        .filter(|letters| !letters.is_empty())
+        .map(|letters| letters.try_into().expect("Expected four amphipods per row"))
+        .collect()
+}
+
+// Places the amphipods from four rows of the diagram, top row first
+fn get_configuration(rows: [[u8; 4]; 4]) -> Configuration {
+    let rooms = [ROOM_A, ROOM_B, ROOM_C, ROOM_D];
+    let mut configuration = [Position::Hallway00; AMPHIPOD_COUNT];
+    let mut placed = [0usize; 4];
+    for (depth, row) in rows.iter().enumerate() {
+        for (room, letter) in rooms.iter().zip(row) {
+            let kind = usize::from(letter - b'A');
+            assert!(
+                placed[kind] < 4,
+                "Found too many {} amphipods",
+                *letter as char
+            );
+            configuration[kind * 4 + placed[kind]] = room[depth];
+            placed[kind] += 1;
+        }
+    }
+    configuration
+}
+
 fn main() {
-    // organize_amphipods([
-    //     Position::RoomASouth,
-    //     Position::RoomDSouth,
-    //     Position::RoomANorth,
-    //     Position::RoomCNorth,
-    //     Position::RoomBNorth,
-    //     Position::RoomCSouth,
-    //     Position::RoomBSouth,
-    //     Position::RoomDNorth,
-    // ]);
-    // organize_amphipods([
-    //     Position::RoomANorth,
-    //     Position::RoomCSouth,
-    //     Position::RoomASouth,
-    //     Position::RoomCNorth,
-    //     Position::RoomBSouth,
-    //     Position::RoomDSouth,
-    //     Position::RoomBNorth,
-    //     Position::RoomDNorth,
-    // ]);
-    // organize_amphipods([
-    //     Position::RoomD2,
-    //     Position::RoomC3,
-    //     Position::RoomA4,
-    //     Position::RoomD4,
-    //     Position::RoomA1,
-    //     Position::RoomC1,
-    //     Position::RoomC2,
-    //     Position::RoomB3,
-    //     Position::RoomB1,
-    //     Position::RoomB2,
-    //     Position::RoomD3,
-    //     Position::RoomC4,
-    //     Position::RoomD1,
-    //     Position::RoomA2,
-    //     Position::RoomA3,
-    //     Position::RoomB4,
-    // ]);
-    organize_amphipods([
-        Position::RoomA1,
-        Position::RoomC4,
-        Position::RoomD2,
-        Position::RoomC3,
-        Position::RoomA4,
-        Position::RoomC1,
-        Position::RoomC2,
-        Position::RoomB3,
-        Position::RoomB4,
-        Position::RoomD4,
-        Position::RoomB2,
-        Position::RoomD3,
-        Position::RoomB1,
-        Position::RoomD1,
-        Position::RoomA2,
-        Position::RoomA3,
-    ]);
+    let args = Args::parse();
+
+    let rows = parse_rows(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(std::result::Result::unwrap),
+    );
+    assert_eq!(rows.len(), 2, "Expected two rows of amphipods");
+
+    // The solver always uses rooms four deep, so in part 1 the bottom of each room is filled with
+    // amphipods that are already home and never have to move
+    if args.part != Some(2) {
+        let configuration = get_configuration([rows[0], rows[1], *b"ABCD", *b"ABCD"]);
+        println!("Energy: {}", organize_amphipods(configuration));
+    }
+    if args.part != Some(1) {
+        let configuration = get_configuration([rows[0], *b"DCBA", *b"DBAC", rows[1]]);
+        println!("Energy: {}", organize_amphipods(configuration));
+    }
 }
 
 #[cfg(test)]
@@ -636,8 +647,30 @@ mod tests {
     use super::*;
     use test::Bencher;
 
+    fn get_example() -> [String; 5] {
+        [
+            String::from("#############"),
+            String::from("#...........#"),
+            String::from("###B#C#B#D###"),
+            String::from("  #A#D#C#A#"),
+            String::from("  #########"),
+        ]
+    }
+
     #[test]
-    fn test() {}
+    fn test_parse_rows() {
+        assert_eq!(parse_rows(get_example().into_iter()), [*b"BCBD", *b"ADCA"]);
+    }
+
+    #[test]
+    fn test_get_configuration() {
+        let configuration = get_configuration([*b"BCBD", *b"ADCA", *b"ABCD", *b"ABCD"]);
+        assert_eq!(configuration[Amphipod::A1 as usize], Position::RoomA2);
+        assert_eq!(configuration[Amphipod::B1 as usize], Position::RoomA1);
+        assert_eq!(configuration[Amphipod::B2 as usize], Position::RoomC1);
+        assert!(!is_complete(configuration));
+        assert!(is_complete(get_configuration([*b"ABCD"; 4])));
+    }
 
     // #[bench]
     // fn bench_input(b: &mut Bencher) {
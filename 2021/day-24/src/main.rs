@@ -7,13 +7,23 @@ extern crate test;
 use std::{
     collections::VecDeque,
     fmt::Debug,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     ops::AddAssign,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
+use clap::Parser;
+use common::open_input;
 use itertools::join;
+use tracing::{info_span, trace};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+// There's no --part, since both parts are solved by hand from the constraints this prints
+#[derive(Parser)]
+struct Args {
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone)]
 enum Expression {
@@ -562,21 +572,33 @@ fn execute(
     instructions: &[Instruction],
 ) {
     for (index, instruction) in instructions.iter().enumerate() {
-        // println!("{} {:?}", index, instruction);
+        trace!("{} {:?}", index, instruction);
         if !instruction.execute(&mut register_file, constraints, &instructions[index + 1..]) {
             return;
         }
-        // println!("{:?} {:?}", constraints, register_file);
+        trace!("{:?} {:?}", constraints, register_file);
     }
     println!("{:?} {:?}", constraints, register_file);
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
-    let instructions =
-        Instruction::parse_from_lines(reader.lines().map(std::result::Result::unwrap));
-    execute(RegisterFile::new(), &Vec::new(), &instructions);
+    let args = Args::parse();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let parse = info_span!("parse").entered();
+    let instructions = Instruction::parse_from_lines(
+        open_input(args.filename.as_deref())
+            .lines()
+            .map(std::result::Result::unwrap),
+    );
+    parse.exit();
+
+    info_span!("execute").in_scope(|| execute(RegisterFile::new(), &Vec::new(), &instructions));
 }
 
 #[cfg(test)]
@@ -2,10 +2,17 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::io::BufRead;
+
+use clap::Parser;
+use common::open_input;
+
+// There's no --part, since the last day only has one puzzle
+#[derive(Parser)]
+struct Args {
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn step_east(grid: &mut [Vec<u8>]) -> bool {
     let mut changed = false;
@@ -80,12 +87,12 @@ fn count_until_stop(grid: &mut [Vec<u8>]) -> usize {
 }
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+
     println!(
         "Steps: {}",
         count_until_stop(
-            &mut reader
+            &mut open_input(args.filename.as_deref())
                 .lines()
                 .map(|line| line.unwrap().into_bytes())
                 .collect::<Vec<_>>(),
@@ -112,9 +119,7 @@ mod tests {
 
     #[bench]
     fn bench_input(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
-        let grid = &mut reader
+        let grid = &mut open_input(Some("input.txt"))
             .lines()
             .map(|line| line.unwrap().into_bytes())
             .collect::<Vec<_>>();
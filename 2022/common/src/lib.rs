@@ -0,0 +1,16 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+// Opens the named file, or stdin if there is no filename or the filename is "-"
+pub fn open_input(filename: Option<&str>) -> Box<dyn BufRead> {
+    match filename {
+        Some(filename) if filename != "-" => {
+            let file =
+                File::open(filename).unwrap_or_else(|_| panic!("Failed to open file {filename}"));
+            Box::new(BufReader::new(file))
+        }
+        _ => Box::new(io::stdin().lock()),
+    }
+}
@@ -1,13 +1,21 @@
 #![warn(clippy::pedantic)]
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn main() {
-    let file = File::open("input.txt").unwrap();
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
     let mut sum = 0;
     let mut top = vec![0, 0, 0];
     for line in reader.lines().map(std::result::Result::unwrap) {
@@ -22,8 +30,12 @@ fn main() {
         }
     }
 
-    let max = top[2];
-    println!("max: {max}");
-    let top_sum: i32 = top.iter().sum();
-    println!("top: {top_sum}");
+    if args.part != Some(2) {
+        let max = top[2];
+        println!("max: {max}");
+    }
+    if args.part != Some(1) {
+        let top_sum: i32 = top.iter().sum();
+        println!("top: {top_sum}");
+    }
 }
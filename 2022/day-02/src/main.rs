@@ -1,10 +1,19 @@
 #![warn(clippy::pedantic)]
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+// The first part reads the second column as my choice, the second part as the outcome
 enum Strategy {
     Choice,
     Outcome,
@@ -64,23 +73,9 @@ fn get_choice_for_outcome(opponent: Choice, outcome: Outcome) -> Choice {
     }
 }
 
-fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let strategy = match std::env::args()
-        .nth(2)
-        .expect("Strategy not found")
-        .as_str()
-    {
-        "choice" => Strategy::Choice,
-        "outcome" => Strategy::Outcome,
-        _ => unreachable!("Unknown strategy. Valid options are 'choice' and 'outcome'"),
-    };
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+fn get_total_score(lines: &[String], strategy: &Strategy) -> i32 {
     let mut total = 0;
-    for line in reader.lines().map(std::result::Result::unwrap) {
+    for line in lines {
         let mut choices = line.split(' ');
         let opponent = match choices.next().unwrap() {
             "A" => Choice::Rock,
@@ -115,5 +110,26 @@ fn main() {
         total += score;
     }
 
-    println!("Total score: {total}");
+    total
+}
+
+fn main() {
+    let args = Args::parse();
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap)
+        .collect();
+
+    if args.part != Some(2) {
+        println!(
+            "Total score: {}",
+            get_total_score(&lines, &Strategy::Choice)
+        );
+    }
+    if args.part != Some(1) {
+        println!(
+            "Total score: {}",
+            get_total_score(&lines, &Strategy::Outcome)
+        );
+    }
 }
@@ -1,10 +1,17 @@
 #![warn(clippy::pedantic)]
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashSet, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn get_item_priority(item: u8) -> u32 {
     if item >= 97 {
@@ -15,11 +22,8 @@ fn get_item_priority(item: u8) -> u32 {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
 
     let mut rucksack_sum = 0;
     let mut badge_sum = 0;
@@ -50,6 +54,10 @@ fn main() {
         }
     }
 
-    println!("Rucksack sum: {rucksack_sum}");
-    println!("Badge sum: {badge_sum}");
+    if args.part != Some(2) {
+        println!("Rucksack sum: {rucksack_sum}");
+    }
+    if args.part != Some(1) {
+        println!("Badge sum: {badge_sum}");
+    }
 }
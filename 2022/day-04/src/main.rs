@@ -1,11 +1,19 @@
 #![warn(clippy::pedantic)]
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-    ops::RangeInclusive,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator, ops::RangeInclusive};
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+// The first part counts pairs where one range encloses the other, the second any overlap
 enum Mode {
     Enclose,
     Overlap,
@@ -31,21 +39,9 @@ impl RangeOverlap for RangeInclusive<i32> {
     }
 }
 
-fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let mode = match std::env::args().nth(2).expect("Mode not found").as_str() {
-        "enclose" => Mode::Enclose,
-        "overlap" => Mode::Overlap,
-        _ => unimplemented!("Unknown mode. Valid options are 'enclose' and 'overlap'"),
-    };
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-
-    let match_count = reader
-        .lines()
-        .map(std::result::Result::unwrap)
+fn count_matches(lines: &[String], mode: &Mode) -> usize {
+    lines
+        .iter()
         .filter(|line| {
             let mut split = line.split(',');
             let left = parse_range(split.next().unwrap());
@@ -55,7 +51,20 @@ fn main() {
                 Mode::Overlap => left.overlaps(&right),
             }
         })
-        .count();
+        .count()
+}
 
-    println!("Found {match_count}");
+fn main() {
+    let args = Args::parse();
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap)
+        .collect();
+
+    if args.part != Some(2) {
+        println!("Found {}", count_matches(&lines, &Mode::Enclose));
+    }
+    if args.part != Some(1) {
+        println!("Found {}", count_matches(&lines, &Mode::Overlap));
+    }
 }
@@ -1,9 +1,17 @@
 #![warn(clippy::pedantic)]
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 type StackSlice = Vec<Option<char>>;
 
@@ -68,28 +76,31 @@ fn execute_command(command: &str, stacks: &mut [Stack], retain_order: bool) {
     stacks[destination].append(&mut moved);
 }
 
+fn get_message(mut stacks: Vec<Stack>, commands: &[String], retain_order: bool) -> String {
+    for command in commands {
+        execute_command(command.as_str(), &mut stacks, retain_order);
+    }
+
+    stacks.iter().map(|stack| stack.last().unwrap()).collect()
+}
+
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let retain_order = if let Some(retain) = std::env::args().nth(2) {
-        retain.as_str() == "retain"
-    } else {
-        false
-    };
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let slices = parse_stack_slices(&mut lines);
-    let mut stacks = slices_to_stacks(slices);
+    let stacks = slices_to_stacks(slices);
 
     lines.next(); // Skip blank line before commands
-    for line in lines {
-        execute_command(line.as_str(), &mut stacks, retain_order);
-    }
+    let commands: Vec<_> = lines.collect();
 
-    let message: String = stacks.iter().map(|stack| stack.last().unwrap()).collect();
-
-    println!("{message}");
+    // The first part moves crates one at a time, the second keeps the order of each move
+    if args.part != Some(2) {
+        println!("{}", get_message(stacks.clone(), &commands, false));
+    }
+    if args.part != Some(1) {
+        println!("{}", get_message(stacks, &commands, true));
+    }
 }
@@ -1,10 +1,17 @@
 #![warn(clippy::pedantic)]
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashSet, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn get_marker_position(buffer: &str, distinct_characters: usize) -> usize {
     buffer
@@ -20,21 +27,22 @@ fn get_marker_position(buffer: &str, distinct_characters: usize) -> usize {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
 
     for line in reader.lines().map(std::result::Result::unwrap) {
-        println!(
-            "First packet marker after character {}",
-            get_marker_position(&line, 4)
-        );
-        println!(
-            "First message marker after character {}",
-            get_marker_position(&line, 14)
-        );
+        if args.part != Some(2) {
+            println!(
+                "First packet marker after character {}",
+                get_marker_position(&line, 4)
+            );
+        }
+        if args.part != Some(1) {
+            println!(
+                "First message marker after character {}",
+                get_marker_position(&line, 14)
+            );
+        }
     }
 }
 
@@ -1,11 +1,23 @@
 #![warn(clippy::pedantic)]
+use clap::Parser;
+use common::open_input;
 use std::{
     cell::RefCell,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
     rc::{Rc, Weak},
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 struct Directory {
     name: String,
     parent: Weak<RefCell<Self>>,
@@ -124,28 +136,29 @@ fn parse_file_tree(lines: impl Iterator<Item = String>) -> Rc<RefCell<Directory>
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file = std::fs::File::open(&filename)
-        .unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
 
     let root = parse_file_tree(reader.lines().map(std::result::Result::unwrap));
 
     let mut directory_sizes = root.borrow().get_directory_sizes();
-    let total_capped_size: usize = directory_sizes
-        .iter()
-        .map(|(_name, size)| if *size <= 100_000 { *size } else { 0 })
-        .sum();
-    println!("Total capped size {total_capped_size}");
-
-    let to_free = root.borrow().get_total_size() - 40_000_000;
-    println!("Need to free {to_free}");
-
-    directory_sizes.sort_unstable_by_key(|(_name, size)| *size);
-    let (name, size) = directory_sizes
-        .iter()
-        .find(|(_name, size)| *size >= to_free)
-        .unwrap();
-    println!("Should free {name} to save {size}");
+    if args.part != Some(2) {
+        let total_capped_size: usize = directory_sizes
+            .iter()
+            .map(|(_name, size)| if *size <= 100_000 { *size } else { 0 })
+            .sum();
+        println!("Total capped size {total_capped_size}");
+    }
+
+    if args.part != Some(1) {
+        let to_free = root.borrow().get_total_size() - 40_000_000;
+        println!("Need to free {to_free}");
+
+        directory_sizes.sort_unstable_by_key(|(_name, size)| *size);
+        let (name, size) = directory_sizes
+            .iter()
+            .find(|(_name, size)| *size >= to_free)
+            .unwrap();
+        println!("Should free {name} to save {size}");
+    }
 }
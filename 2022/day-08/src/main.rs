@@ -2,11 +2,19 @@
 #![feature(test)]
 extern crate test;
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn parse_grid(lines: impl Iterator<Item = String>) -> Vec<Vec<u8>> {
     lines
@@ -95,19 +103,20 @@ fn max_scenic_score(grid: &[Vec<u8>]) -> usize {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
 
     let grid = parse_grid(reader.lines().map(std::result::Result::unwrap));
 
-    let visible_trees = count_visible_trees(&grid);
-    println!("{visible_trees} visible trees");
+    if args.part != Some(2) {
+        let visible_trees = count_visible_trees(&grid);
+        println!("{visible_trees} visible trees");
+    }
 
-    let scenic_score = max_scenic_score(&grid);
-    println!("Max scenic score {scenic_score}");
+    if args.part != Some(1) {
+        let scenic_score = max_scenic_score(&grid);
+        println!("Max scenic score {scenic_score}");
+    }
 }
 
 #[cfg(test)]
@@ -117,8 +126,7 @@ mod tests {
 
     #[bench]
     fn bench_parse_grid(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
+        let reader = open_input(Some("input.txt"));
         let lines: Vec<_> = reader.lines().map(std::result::Result::unwrap).collect();
 
         b.iter(|| {
@@ -129,8 +137,7 @@ mod tests {
 
     #[bench]
     fn bench_count_visible_trees(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
+        let reader = open_input(Some("input.txt"));
 
         let grid = parse_grid(reader.lines().map(std::result::Result::unwrap));
 
@@ -142,8 +149,7 @@ mod tests {
 
     #[bench]
     fn bench_max_scenic_score(b: &mut Bencher) {
-        let file = File::open("input.txt").unwrap();
-        let reader = BufReader::new(file);
+        let reader = open_input(Some("input.txt"));
 
         let grid = parse_grid(reader.lines().map(std::result::Result::unwrap));
 
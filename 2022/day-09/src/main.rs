@@ -1,11 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashSet, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct Position {
@@ -73,18 +80,19 @@ fn simulate_rope(lines: impl Iterator<Item = String>, rope_length: usize) -> usi
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let rope_length = std::env::args()
-        .nth(2)
-        .expect("Rope length not found")
-        .parse()
-        .unwrap();
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
-
-    let tail_positions = simulate_rope(lines, rope_length);
-    println!("{tail_positions} tail positions visited");
+    let args = Args::parse();
+    let lines: Vec<_> = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap)
+        .collect();
+
+    // The head is followed by one knot in the first part and nine in the second
+    if args.part != Some(2) {
+        let tail_positions = simulate_rope(lines.iter().cloned(), 1);
+        println!("{tail_positions} tail positions visited");
+    }
+    if args.part != Some(1) {
+        let tail_positions = simulate_rope(lines.into_iter(), 9);
+        println!("{tail_positions} tail positions visited");
+    }
 }
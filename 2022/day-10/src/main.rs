@@ -1,10 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 struct Cpu {
     cycle: i32,
@@ -69,20 +77,21 @@ fn run_program(lines: impl Iterator<Item = String>) -> (SignalStrength, Pixels)
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
+    let args = Args::parse();
+    let reader = open_input(args.filename.as_deref());
     let lines = reader.lines().map(std::result::Result::unwrap);
 
     let (signal_strength, pixels) = run_program(lines);
-    println!("Signal strength: {signal_strength}");
+    if args.part != Some(2) {
+        println!("Signal strength: {signal_strength}");
+    }
 
-    for (index, char) in pixels.chars().enumerate() {
-        print!("{char}");
-        if index % 40 == 39 {
-            println!();
+    if args.part != Some(1) {
+        for (index, char) in pixels.chars().enumerate() {
+            print!("{char}");
+            if index % 40 == 39 {
+                println!();
+            }
         }
     }
 }
@@ -1,13 +1,20 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
-
-#[derive(Debug)]
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashMap, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
+#[derive(Clone, Debug)]
 enum Operation {
     Multiply(usize),
     Add(usize),
@@ -47,7 +54,7 @@ impl Operation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Monkey {
     items: Vec<usize>,
     operation: Operation,
@@ -129,28 +136,11 @@ fn simulate_round(monkeys: &mut [Monkey], modulo: Option<usize>) -> Vec<(usize,
     inspection_counts
 }
 
-fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let part = std::env::args()
-        .nth(2)
-        .expect("Part not found. Expected 'part1' or 'part2'");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
-
-    let mut monkeys = parse_monkeys(lines);
-
-    let (modulo, round_count) = if part == "part1" {
-        (None, 20)
-    } else {
-        (
-            Some(monkeys.iter().map(|monkey| monkey.divisible_by).product()),
-            10_000,
-        )
-    };
-
+fn get_monkey_business(
+    mut monkeys: Vec<Monkey>,
+    modulo: Option<usize>,
+    round_count: usize,
+) -> usize {
     let mut inspection_counts = HashMap::new();
     for _ in 0..round_count {
         for (monkey, count) in simulate_round(&mut monkeys, modulo).drain(..) {
@@ -161,10 +151,28 @@ fn main() {
     let mut inspection_counts: Vec<_> = inspection_counts.drain().collect();
     inspection_counts.sort_unstable_by_key(|(_monkey, counts)| *counts);
     inspection_counts.reverse();
-    let monkey_business: usize = inspection_counts
+    inspection_counts
         .iter()
         .take(2)
         .map(|(_monkey, counts)| *counts)
-        .product();
-    println!("Monkey business: {monkey_business}");
+        .product()
+}
+
+fn main() {
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
+
+    let monkeys = parse_monkeys(lines);
+
+    if args.part != Some(2) {
+        let monkey_business = get_monkey_business(monkeys.clone(), None, 20);
+        println!("Monkey business: {monkey_business}");
+    }
+    if args.part != Some(1) {
+        let modulo = monkeys.iter().map(|monkey| monkey.divisible_by).product();
+        let monkey_business = get_monkey_business(monkeys, Some(modulo), 10_000);
+        println!("Monkey business: {monkey_business}");
+    }
 }
@@ -1,11 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::VecDeque,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::VecDeque, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 type Heightmap = Vec<Vec<u8>>;
 
@@ -100,35 +107,37 @@ fn get_all_shortest_paths(heightmap: &Heightmap, end: Location) -> Vec<Vec<usize
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let (heightmap, start, end) = parse_map(lines);
 
     let shortest_paths = get_all_shortest_paths(&heightmap, end);
 
-    println!(
-        "From current position: {}",
-        shortest_paths[start.row][start.column]
-    );
-
-    let heightmap_flattened = heightmap.iter().flat_map(|row| row.iter());
-    let best_complete_length = shortest_paths
-        .iter()
-        .flat_map(|row| row.iter())
-        .zip(heightmap_flattened)
-        .filter_map(|(shortest_path, height)| {
-            if *height == 0 {
-                Some(shortest_path)
-            } else {
-                None
-            }
-        })
-        .min()
-        .unwrap();
-    println!("From best position: {}", best_complete_length);
+    if args.part != Some(2) {
+        println!(
+            "From current position: {}",
+            shortest_paths[start.row][start.column]
+        );
+    }
+
+    if args.part != Some(1) {
+        let heightmap_flattened = heightmap.iter().flat_map(|row| row.iter());
+        let best_complete_length = shortest_paths
+            .iter()
+            .flat_map(|row| row.iter())
+            .zip(heightmap_flattened)
+            .filter_map(|(shortest_path, height)| {
+                if *height == 0 {
+                    Some(shortest_path)
+                } else {
+                    None
+                }
+            })
+            .min()
+            .unwrap();
+        println!("From best position: {}", best_complete_length);
+    }
 }
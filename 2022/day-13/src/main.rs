@@ -1,13 +1,24 @@
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use std::{
     cmp::Ordering,
     fmt::Debug,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::{Iterator, Peekable},
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum Packet {
     Integer(i32),
@@ -113,12 +124,10 @@ impl std::cmp::PartialOrd for Packet {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let mut index = 1;
     let mut index_sum = 0;
@@ -137,24 +146,27 @@ fn main() {
         packets.push(right);
     }
 
-    println!("Index sum: {index_sum}");
-
-    packets.push(Packet::new_divider(2));
-    packets.push(Packet::new_divider(6));
+    if args.part != Some(2) {
+        println!("Index sum: {index_sum}");
+    }
+    if args.part != Some(1) {
+        packets.push(Packet::new_divider(2));
+        packets.push(Packet::new_divider(6));
 
-    packets.sort_unstable();
+        packets.sort_unstable();
 
-    let divider_2_position = packets
-        .iter()
-        .position(|packet| *packet == Packet::new_divider(2))
-        .unwrap()
-        + 1;
+        let divider_2_position = packets
+            .iter()
+            .position(|packet| *packet == Packet::new_divider(2))
+            .unwrap()
+            + 1;
 
-    let divider_6_position = packets
-        .iter()
-        .position(|packet| *packet == Packet::new_divider(6))
-        .unwrap()
-        + 1;
+        let divider_6_position = packets
+            .iter()
+            .position(|packet| *packet == Packet::new_divider(6))
+            .unwrap()
+            + 1;
 
-    println!("Decoder key: {}", divider_2_position * divider_6_position);
+        println!("Decoder key: {}", divider_2_position * divider_6_position);
+    }
 }
@@ -1,11 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashSet, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 struct Location {
@@ -127,18 +134,20 @@ fn count_drops(mut cave: HashSet<Location>, void_depth: u16) -> (usize, usize) {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let cave = parse_cave(lines);
 
     let void_depth = cave.iter().map(|rock| rock.y).max().unwrap() + 2;
 
     let (drops_until_void, drops_until_full) = count_drops(cave, void_depth);
-    println!("{drops_until_void} drops until void");
-    println!("{drops_until_full} drops until full");
+    if args.part != Some(2) {
+        println!("{drops_until_void} drops until void");
+    }
+    if args.part != Some(1) {
+        println!("{drops_until_full} drops until full");
+    }
 }
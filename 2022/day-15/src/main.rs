@@ -1,11 +1,26 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-    ops::RangeInclusive,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator, ops::RangeInclusive};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Row to count impossible beacon positions in for part 1
+    #[arg(short, long, default_value_t = 2_000_000)]
+    row: i64,
+
+    /// Largest coordinate the distress beacon can have for part 2
+    #[arg(short, long, default_value_t = 4_000_000)]
+    clamp: i64,
+}
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct Location {
@@ -181,31 +196,25 @@ fn find_possible_position(sensor_beacon_pairs: &[(Location, Location)], max_row:
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let row = std::env::args()
-        .nth(2)
-        .expect("Row to check not found")
-        .parse()
-        .unwrap();
-    let clamp = std::env::args()
-        .nth(3)
-        .expect("Clamp value not found")
-        .parse()
-        .unwrap();
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let sensor_beacon_pairs = parse_sensor_beacon_pairs(lines);
 
-    let impossible_position_count = get_impossible_positions(&sensor_beacon_pairs, row, None).len();
-    println!("Impossible positions in row {row}: {impossible_position_count}");
+    if args.part != Some(2) {
+        let row = args.row;
+        let impossible_position_count =
+            get_impossible_positions(&sensor_beacon_pairs, row, None).len();
+        println!("Impossible positions in row {row}: {impossible_position_count}");
+    }
 
-    let possible_position = find_possible_position(&sensor_beacon_pairs, clamp);
-    println!(
-        "Tuning frequency: {}",
-        possible_position.x * 4_000_000 + possible_position.y
-    );
+    if args.part != Some(1) {
+        let possible_position = find_possible_position(&sensor_beacon_pairs, args.clamp);
+        println!(
+            "Tuning frequency: {}",
+            possible_position.x * 4_000_000 + possible_position.y
+        );
+    }
 }
@@ -1,13 +1,24 @@
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use itertools::Itertools;
 use std::{
     collections::{BinaryHeap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 type VertexIndex = usize;
 
 #[derive(Debug)]
@@ -246,41 +257,43 @@ fn compute_maximum_pressure(graph: &Graph, flowable_valves: &[FlowableValve]) ->
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let (graph, flowable_valves) = parse_graph(lines);
 
-    let maximum_pressure = compute_maximum_pressure(&graph, &flowable_valves);
-    println!("{maximum_pressure}");
+    if args.part != Some(2) {
+        let maximum_pressure = compute_maximum_pressure(&graph, &flowable_valves);
+        println!("{maximum_pressure}");
+    }
+    if args.part != Some(1) {
+        let mut elephant_maximum = 0;
+        for mine in flowable_valves.iter().powerset() {
+            if mine.is_empty() {
+                continue;
+            }
 
-    let mut elephant_maximum = 0;
-    for mine in flowable_valves.iter().powerset() {
-        if mine.is_empty() {
-            continue;
-        }
+            if mine.len() > flowable_valves.len() {
+                break;
+            }
 
-        if mine.len() > flowable_valves.len() {
-            break;
-        }
+            let mine: Vec<_> = mine.iter().map(|valve| (**valve).clone()).collect();
+            let elephants: Vec<_> = flowable_valves
+                .iter()
+                .filter(|valve| !mine.contains(valve))
+                .cloned()
+                .collect();
 
-        let mine: Vec<_> = mine.iter().map(|valve| (**valve).clone()).collect();
-        let elephants: Vec<_> = flowable_valves
-            .iter()
-            .filter(|valve| !mine.contains(valve))
-            .cloned()
-            .collect();
+            // println!("Checking {:?} {:?}", mine, elephants);
 
-        // println!("Checking {:?} {:?}", mine, elephants);
+            elephant_maximum = elephant_maximum.max(
+                compute_maximum_pressure(&graph, &mine)
+                    + compute_maximum_pressure(&graph, &elephants),
+            );
+        }
 
-        elephant_maximum = elephant_maximum.max(
-            compute_maximum_pressure(&graph, &mine) + compute_maximum_pressure(&graph, &elephants),
-        );
+        println!("{elephant_maximum}");
     }
-
-    println!("{elephant_maximum}");
 }
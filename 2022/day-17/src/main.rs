@@ -1,11 +1,21 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use std::{collections::HashMap, io::BufRead, iter::Iterator};
+
+use clap::Parser;
+use common::open_input;
+use tracing::{debug, info_span};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone, Copy, Debug, Default)]
 struct Position {
@@ -288,16 +298,48 @@ impl std::fmt::Display for Chamber {
     }
 }
 
-fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
+// Drops rocks into an empty chamber and returns the height of the tower they build
+fn get_tower_height(commands: &[char], shapes: &[Shape], rock_count: usize) -> usize {
+    let mut command_cycle = commands.iter().cycle();
+    let mut shape_cycle = shapes.iter().cycle();
+
+    let mut chamber = Chamber::new();
+    for _ in 0..rock_count {
+        let mut rock = Rock::new(
+            shape_cycle.next().unwrap(),
+            Position::new(2, chamber.get_top() + 3),
+        );
+
+        for command in &mut command_cycle {
+            match command {
+                '<' => rock.move_left(&chamber),
+                '>' => rock.move_right(&chamber),
+                _ => unimplemented!(),
+            }
+
+            if !rock.move_down(&chamber) {
+                chamber.place(rock);
+                break;
+            }
+        }
+    }
+
+    chamber.get_top()
+}
 
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().map(std::result::Result::unwrap);
+fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
     let commands: Vec<_> = lines.next().unwrap().chars().collect();
-    println!("found {} commands", commands.len());
-    let mut command_cycle = commands.iter().cycle();
+    debug!("found {} commands", commands.len());
 
     let shapes = [
         Shape::new_flat(),
@@ -306,94 +348,126 @@ fn main() {
         Shape::new_tall(),
         Shape::new_square(),
     ];
-    let mut shape_cycle = shapes.iter().cycle();
 
-    let mut chamber = Chamber::new();
+    if args.part != Some(2) {
+        println!("Total: {}", get_tower_height(&commands, &shapes, 2022));
+    }
+    if args.part != Some(1) {
+        let mut command_cycle = commands.iter().cycle();
+        let mut shape_cycle = shapes.iter().cycle();
 
-    let mut last_seen = HashMap::new();
+        let mut chamber = Chamber::new();
 
-    let mut possible_cycle = None;
+        let mut last_seen = HashMap::new();
 
-    let mut tower_heights = vec![0];
+        let mut possible_cycle = None;
 
-    for iteration in 0..2000 {
-        for _ in 0..commands.len() {
-            let mut rock = Rock::new(
-                shape_cycle.next().unwrap(),
-                Position::new(2, chamber.get_top() + 3),
-            );
+        let mut tower_heights = vec![0];
 
-            for command in &mut command_cycle {
-                match command {
-                    '<' => rock.move_left(&chamber),
-                    '>' => rock.move_right(&chamber),
-                    _ => unimplemented!(),
-                }
+        let cycle_search = info_span!("cycle_search").entered();
+        for iteration in 0..2000 {
+            for _ in 0..commands.len() {
+                let mut rock = Rock::new(
+                    shape_cycle.next().unwrap(),
+                    Position::new(2, chamber.get_top() + 3),
+                );
+
+                for command in &mut command_cycle {
+                    match command {
+                        '<' => rock.move_left(&chamber),
+                        '>' => rock.move_right(&chamber),
+                        _ => unimplemented!(),
+                    }
 
-                if !rock.move_down(&chamber) {
-                    chamber.place(rock);
-                    break;
+                    if !rock.move_down(&chamber) {
+                        chamber.place(rock);
+                        break;
+                    }
                 }
             }
-        }
 
-        let tower_height = chamber.get_top();
-        let difference = tower_height - tower_heights.last().unwrap();
-        let mut signature = chamber.get_signature();
-
-        signature.push(difference);
-        if last_seen.contains_key(&signature) {
-            let last_seen_iteration = *last_seen.get(&signature).unwrap();
-            let cycle_length = iteration - last_seen_iteration;
-            if let Some((possible_length, _)) = possible_cycle {
-                if possible_length == cycle_length {
-                    // Cycle confirmed, break
-                    break;
+            let tower_height = chamber.get_top();
+            let difference = tower_height - tower_heights.last().unwrap();
+            let mut signature = chamber.get_signature();
+
+            signature.push(difference);
+            if last_seen.contains_key(&signature) {
+                let last_seen_iteration = *last_seen.get(&signature).unwrap();
+                let cycle_length = iteration - last_seen_iteration;
+                if let Some((possible_length, _)) = possible_cycle {
+                    if possible_length == cycle_length {
+                        // Cycle confirmed, break
+                        break;
+                    }
                 }
+
+                possible_cycle = Some((cycle_length, last_seen_iteration));
+            } else {
+                possible_cycle = None;
             }
+            last_seen.insert(signature, iteration);
 
-            possible_cycle = Some((cycle_length, last_seen_iteration));
-        } else {
-            possible_cycle = None;
+            tower_heights.push(tower_height);
         }
-        last_seen.insert(signature, iteration);
+        cycle_search.exit();
 
-        tower_heights.push(tower_height);
-    }
-
-    if let Some((cycle_length, starting_iteration)) = possible_cycle {
-        println!(
-            "Cycle confirmed: {} iterations long, starting iteration {}",
-            cycle_length, starting_iteration
-        );
+        if let Some((cycle_length, starting_iteration)) = possible_cycle {
+            let _extrapolation = info_span!("extrapolation").entered();
+            debug!(
+                "Cycle confirmed: {} iterations long, starting iteration {}",
+                cycle_length, starting_iteration
+            );
 
-        let mut rocks = 1_000_000_000_000;
-        rocks -= commands.len() * starting_iteration;
-        println!("{} rocks are part of a cycle", rocks);
-
-        let rocks_in_cycle = cycle_length * commands.len();
-        let cycles = rocks / rocks_in_cycle;
-        let growth_per_cycle = tower_heights[starting_iteration + 1 + cycle_length]
-            - tower_heights[starting_iteration + 1];
-        let growth_in_cycles = growth_per_cycle * cycles;
-        println!("{} growth in cycles", growth_in_cycles);
-
-        // TODO: Generalize
-        println!(
-            "{} including pre-cycle iterations",
-            growth_in_cycles + tower_heights[1]
-        );
+            let mut rocks = 1_000_000_000_000;
+            rocks -= commands.len() * starting_iteration;
+            debug!("{} rocks are part of a cycle", rocks);
+
+            let rocks_in_cycle = cycle_length * commands.len();
+            let cycles = rocks / rocks_in_cycle;
+            let growth_per_cycle = tower_heights[starting_iteration + 1 + cycle_length]
+                - tower_heights[starting_iteration + 1];
+            let growth_in_cycles = growth_per_cycle * cycles;
+            debug!("{} growth in cycles", growth_in_cycles);
+
+            // TODO: Generalize
+            debug!(
+                "{} including pre-cycle iterations",
+                growth_in_cycles + tower_heights[1]
+            );
 
-        let rocks_in_cycle = cycle_length * commands.len();
-        rocks %= rocks_in_cycle;
-        println!("{} rocks after mod reduction", rocks);
+            let rocks_in_cycle = cycle_length * commands.len();
+            rocks %= rocks_in_cycle;
+            debug!("{} rocks after mod reduction", rocks);
+
+            let mut chamber = Chamber::new();
+            let mut command_cycle = commands.iter().cycle();
+            let mut shape_cycle = shapes.iter().cycle();
+
+            for _ in 0..starting_iteration {
+                for _ in 0..commands.len() {
+                    let mut rock = Rock::new(
+                        shape_cycle.next().unwrap(),
+                        Position::new(2, chamber.get_top() + 3),
+                    );
+
+                    for command in &mut command_cycle {
+                        match command {
+                            '<' => rock.move_left(&chamber),
+                            '>' => rock.move_right(&chamber),
+                            _ => unimplemented!(),
+                        }
+
+                        if !rock.move_down(&chamber) {
+                            chamber.place(rock);
+                            break;
+                        }
+                    }
+                }
+            }
 
-        let mut chamber = Chamber::new();
-        let mut command_cycle = commands.iter().cycle();
-        let mut shape_cycle = shapes.iter().cycle();
+            let after_cycles = chamber.get_top();
 
-        for _ in 0..starting_iteration {
-            for _ in 0..commands.len() {
+            for _ in 0..rocks {
                 let mut rock = Rock::new(
                     shape_cycle.next().unwrap(),
                     Position::new(2, chamber.get_top() + 3),
@@ -412,35 +486,13 @@ fn main() {
                     }
                 }
             }
-        }
 
-        let after_cycles = chamber.get_top();
+            debug!("Adding a final {}", chamber.get_top() - after_cycles);
 
-        for _ in 0..rocks {
-            let mut rock = Rock::new(
-                shape_cycle.next().unwrap(),
-                Position::new(2, chamber.get_top() + 3),
+            println!(
+                "Total: {}",
+                growth_in_cycles + tower_heights[1] + chamber.get_top() - after_cycles
             );
-
-            for command in &mut command_cycle {
-                match command {
-                    '<' => rock.move_left(&chamber),
-                    '>' => rock.move_right(&chamber),
-                    _ => unimplemented!(),
-                }
-
-                if !rock.move_down(&chamber) {
-                    chamber.place(rock);
-                    break;
-                }
-            }
         }
-
-        println!("Adding a final {}", chamber.get_top() - after_cycles);
-
-        println!(
-            "Total: {}",
-            growth_in_cycles + tower_heights[1] + chamber.get_top() - after_cycles
-        );
     }
 }
@@ -1,13 +1,24 @@
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use std::{
     collections::{HashSet, VecDeque},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
     ops::RangeInclusive,
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
     x: i32,
@@ -95,18 +106,20 @@ fn calculate_exterior_surface_area(points: &HashSet<Point>) -> usize {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let points: HashSet<_> = lines.map(|line| Point::parse(&line)).collect();
 
-    let surface_area: usize = calculate_surface_area(&points);
-    println!("Surface area: {surface_area}");
+    if args.part != Some(2) {
+        let surface_area: usize = calculate_surface_area(&points);
+        println!("Surface area: {surface_area}");
+    }
 
-    let external_surface_area = calculate_exterior_surface_area(&points);
-    println!("External surface area: {external_surface_area}");
+    if args.part != Some(1) {
+        let external_surface_area = calculate_exterior_surface_area(&points);
+        println!("External surface area: {external_surface_area}");
+    }
 }
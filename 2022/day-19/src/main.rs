@@ -1,11 +1,21 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use std::{collections::HashMap, io::BufRead, iter::Iterator};
+
+use clap::Parser;
+use common::open_input;
+use tracing::{debug, info_span, trace};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 #[derive(Clone, Copy)]
 enum Resource {
@@ -151,8 +161,8 @@ impl Cache {
         } else {
             self.misses += 1;
             if self.misses % 100_000 == 0 {
-                println!("{inventory:?} {production:?} {time_remaining}");
-                self.print_stats();
+                trace!("{inventory:?} {production:?} {time_remaining}");
+                self.log_stats();
             }
         }
 
@@ -170,8 +180,8 @@ impl Cache {
             .insert((inventory, production, time_remaining), geodes);
     }
 
-    fn print_stats(&self) {
-        println!(
+    fn log_stats(&self) {
+        debug!(
             "Hits: {} ({:.2}%) Misses: {}",
             self.hits,
             100f32 * self.hits as f32 / (self.hits as f32 + self.misses as f32),
@@ -292,50 +302,62 @@ fn count_geodes(
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
 
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let blueprints: Vec<_> = lines.map(Blueprint::parse).collect();
 
-    let quality_sum: usize = blueprints
-        .iter()
-        .map(|blueprint| {
-            println!("Processing {}", blueprint.id);
-            let mut cache = Cache::new();
-            let quality_level = blueprint.id
-                * count_geodes(
+    if args.part != Some(2) {
+        let part_1 = info_span!("part_1").entered();
+        let quality_sum: usize = blueprints
+            .iter()
+            .map(|blueprint| {
+                let _span = info_span!("blueprint", id = blueprint.id).entered();
+                let mut cache = Cache::new();
+                let quality_level = blueprint.id
+                    * count_geodes(
+                        &mut cache,
+                        blueprint,
+                        Resources::default(),
+                        Resources::one(Resource::Ore),
+                        24,
+                    );
+                cache.log_stats();
+                quality_level
+            })
+            .sum();
+        part_1.exit();
+        println!("Quality sum: {quality_sum}");
+    }
+
+    if args.part != Some(1) {
+        let part_2 = info_span!("part_2").entered();
+        let geode_product: usize = blueprints
+            .iter()
+            .take(3)
+            .map(|blueprint| {
+                let _span = info_span!("blueprint", id = blueprint.id).entered();
+                let mut cache = Cache::new();
+                let geodes = count_geodes(
                     &mut cache,
                     blueprint,
                     Resources::default(),
                     Resources::one(Resource::Ore),
-                    24,
+                    32,
                 );
-            cache.print_stats();
-            quality_level
-        })
-        .sum();
-    println!("Quality sum: {quality_sum}");
-
-    let geode_product: usize = blueprints
-        .iter()
-        .take(3)
-        .map(|blueprint| {
-            println!("Processing {}", blueprint.id);
-            let mut cache = Cache::new();
-            let geodes = count_geodes(
-                &mut cache,
-                blueprint,
-                Resources::default(),
-                Resources::one(Resource::Ore),
-                32,
-            );
-            cache.print_stats();
-            geodes
-        })
-        .product();
-    println!("Geodes: {geode_product}");
+                cache.log_stats();
+                geodes
+            })
+            .product();
+        part_2.exit();
+        println!("Geodes: {geode_product}");
+    }
 }
@@ -1,10 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 struct Node {
     previous: usize,
@@ -118,34 +126,36 @@ impl Ring {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
     let values: Vec<_> = lines.map(|line| line.parse().unwrap()).collect();
 
-    let mut unencrypted_ring = Ring::new(&values);
-    for index in 0..unencrypted_ring.len() {
-        unencrypted_ring.move_node(index);
-    }
+    if args.part != Some(2) {
+        let mut unencrypted_ring = Ring::new(&values);
+        for index in 0..unencrypted_ring.len() {
+            unencrypted_ring.move_node(index);
+        }
 
-    println!(
-        "Unencrypted grove coordinates: {}",
-        unencrypted_ring.get_grove_coordinates()
-    );
+        println!(
+            "Unencrypted grove coordinates: {}",
+            unencrypted_ring.get_grove_coordinates()
+        );
+    }
 
-    let encrypted_values: Vec<_> = values.iter().map(|value| value * 811_589_153).collect();
-    let mut encrypted_ring = Ring::new(&encrypted_values);
-    for _ in 0..10 {
-        for index in 0..encrypted_ring.len() {
-            encrypted_ring.move_node(index);
+    if args.part != Some(1) {
+        let encrypted_values: Vec<_> = values.iter().map(|value| value * 811_589_153).collect();
+        let mut encrypted_ring = Ring::new(&encrypted_values);
+        for _ in 0..10 {
+            for index in 0..encrypted_ring.len() {
+                encrypted_ring.move_node(index);
+            }
         }
-    }
 
-    println!(
-        "Encrypted grove coordinates: {}",
-        encrypted_ring.get_grove_coordinates()
-    );
+        println!(
+            "Encrypted grove coordinates: {}",
+            encrypted_ring.get_grove_coordinates()
+        );
+    }
 }
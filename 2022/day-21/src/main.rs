@@ -1,11 +1,18 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{collections::HashMap, io::BufRead, iter::Iterator};
+
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 type Value = u64;
 
@@ -95,49 +102,51 @@ fn get_next_power_of_10(value: Value) -> Value {
 type GetValues = dyn Fn(&Monkeys) -> (Value, Value);
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let mut monkeys = parse_monkeys(lines);
     let root_value = compute_value(&String::from("root"), &monkeys);
-    println!("Root value: {root_value}");
-
-    let (lhs, rhs) = if let Operation::Addition(lhs, rhs) = &monkeys[&String::from("root")] {
-        (lhs.clone(), rhs.clone())
-    } else {
-        unreachable!()
-    };
-
-    let left_value = compute_value(&lhs, &monkeys);
-    let right_value = compute_value(&rhs, &monkeys);
-    let get_values: Box<GetValues> = if left_value < right_value {
-        Box::new(move |monkeys| (compute_value(&lhs, monkeys), compute_value(&rhs, monkeys)))
-    } else {
-        Box::new(move |monkeys| (compute_value(&rhs, monkeys), compute_value(&lhs, monkeys)))
-    };
-
-    let humn_value = compute_value(&String::from("humn"), &monkeys);
-    let mut step = get_next_power_of_10(root_value);
-    let mut additional = step;
-    loop {
-        monkeys.insert(
-            String::from("humn"),
-            Operation::Constant(humn_value + additional),
-        );
-        let (lesser, greater) = get_values(&monkeys);
-        match lesser.cmp(&greater) {
-            std::cmp::Ordering::Less => additional += step,
-            std::cmp::Ordering::Greater => {
-                additional -= step;
-                step /= 10;
-            }
-            std::cmp::Ordering::Equal => break,
-        };
+    if args.part != Some(2) {
+        println!("Root value: {root_value}");
     }
 
-    println!("humn: {}", humn_value + additional);
+    if args.part != Some(1) {
+        let (lhs, rhs) = if let Operation::Addition(lhs, rhs) = &monkeys[&String::from("root")] {
+            (lhs.clone(), rhs.clone())
+        } else {
+            unreachable!()
+        };
+
+        let left_value = compute_value(&lhs, &monkeys);
+        let right_value = compute_value(&rhs, &monkeys);
+        let get_values: Box<GetValues> = if left_value < right_value {
+            Box::new(move |monkeys| (compute_value(&lhs, monkeys), compute_value(&rhs, monkeys)))
+        } else {
+            Box::new(move |monkeys| (compute_value(&rhs, monkeys), compute_value(&lhs, monkeys)))
+        };
+
+        let humn_value = compute_value(&String::from("humn"), &monkeys);
+        let mut step = get_next_power_of_10(root_value);
+        let mut additional = step;
+        loop {
+            monkeys.insert(
+                String::from("humn"),
+                Operation::Constant(humn_value + additional),
+            );
+            let (lesser, greater) = get_values(&monkeys);
+            match lesser.cmp(&greater) {
+                std::cmp::Ordering::Less => additional += step,
+                std::cmp::Ordering::Greater => {
+                    additional -= step;
+                    step /= 10;
+                }
+                std::cmp::Ordering::Equal => break,
+            };
+        }
+
+        println!("humn: {}", humn_value + additional);
+    }
 }
@@ -1,13 +1,28 @@
 #![feature(iter_intersperse)]
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+
+    /// Length of each edge of the cube for part 2
+    #[arg(short, long, default_value_t = 50)]
+    face_dimension: usize,
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Turn {
     Right = 1,
@@ -525,27 +540,24 @@ fn run_commands(commands: &[Command], board: &[Vec<u8>], mut wrap_cache: impl Wr
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-    let face_dimension = std::env::args()
-        .nth(2)
-        .expect("Face dimension not found")
-        .parse()
-        .unwrap();
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let mut lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let board = parse_board(&mut lines);
     let commands = parse_commands(&lines.next().unwrap());
 
-    run_commands(&commands, &board, FlatWrapCache::new());
+    if args.part != Some(2) {
+        run_commands(&commands, &board, FlatWrapCache::new());
+    }
 
-    let oriented_faces = get_oriented_faces(&board, face_dimension);
-    run_commands(
-        &commands,
-        &board,
-        CubeWrapCache::new(oriented_faces, face_dimension),
-    );
+    if args.part != Some(1) {
+        let oriented_faces = get_oriented_faces(&board, args.face_dimension);
+        run_commands(
+            &commands,
+            &board,
+            CubeWrapCache::new(oriented_faces, args.face_dimension),
+        );
+    }
 }
@@ -1,13 +1,24 @@
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
     ops::RangeInclusive,
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Direction {
     North,
@@ -157,12 +168,10 @@ fn resolve_proposals(elves: &mut HashSet<Position>, proposals: Proposals) {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
     let mut elves = parse_elves(lines);
 
     let mut direction_order = vec![
@@ -178,24 +187,28 @@ fn main() {
         direction_order.rotate_left(1);
     }
 
-    let (row_bounds, column_bounds) = get_bounds(&elves);
-    let empty_tiles = (row_bounds.end() - row_bounds.start() + 1)
-        * (column_bounds.end() - column_bounds.start() + 1)
-        - i32::try_from(elves.len()).unwrap();
+    if args.part != Some(2) {
+        let (row_bounds, column_bounds) = get_bounds(&elves);
+        let empty_tiles = (row_bounds.end() - row_bounds.start() + 1)
+            * (column_bounds.end() - column_bounds.start() + 1)
+            - i32::try_from(elves.len()).unwrap();
 
-    println!("Empty tiles: {empty_tiles}");
+        println!("Empty tiles: {empty_tiles}");
+    }
 
-    let mut iterations = 11;
-    loop {
-        let proposals = get_proposals(&elves, &direction_order);
-        if proposals.is_empty() {
-            break;
+    if args.part != Some(1) {
+        let mut iterations = 11;
+        loop {
+            let proposals = get_proposals(&elves, &direction_order);
+            if proposals.is_empty() {
+                break;
+            }
+
+            resolve_proposals(&mut elves, proposals);
+            direction_order.rotate_left(1);
+            iterations += 1;
         }
 
-        resolve_proposals(&mut elves, proposals);
-        direction_order.rotate_left(1);
-        iterations += 1;
+        println!("Iterations: {iterations}");
     }
-
-    println!("Iterations: {iterations}");
 }
@@ -1,12 +1,23 @@
 #![warn(clippy::pedantic)]
 
+use clap::Parser;
+use common::open_input;
 use std::{
     collections::{BinaryHeap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     iter::Iterator,
 };
 
+#[derive(Parser)]
+struct Args {
+    /// Part of the problem to run, or both parts if omitted
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct Position {
     row: usize,
@@ -250,12 +261,10 @@ fn find_arrival_time(
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     let (blizzards, width, height) = parse_blizzards(lines);
     let mut vacancy_cache = VacancyCache::new(blizzards, width, height);
@@ -264,11 +273,15 @@ fn main() {
     let end = Position::new(height + 1, width - 1);
 
     let initial_time = find_arrival_time(&mut vacancy_cache, start, end, 0);
-    println!("Initial time: {initial_time}");
+    if args.part != Some(2) {
+        println!("Initial time: {initial_time}");
+    }
 
-    let return_time = find_arrival_time(&mut vacancy_cache, end, start, initial_time);
-    println!("Return time: {return_time}");
+    if args.part != Some(1) {
+        let return_time = find_arrival_time(&mut vacancy_cache, end, start, initial_time);
+        println!("Return time: {return_time}");
 
-    let final_time = find_arrival_time(&mut vacancy_cache, start, end, return_time);
-    println!("Final time: {final_time}");
+        let final_time = find_arrival_time(&mut vacancy_cache, start, end, return_time);
+        println!("Final time: {final_time}");
+    }
 }
@@ -1,10 +1,15 @@
 #![warn(clippy::pedantic)]
 
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    iter::Iterator,
-};
+use clap::Parser;
+use common::open_input;
+use std::{io::BufRead, iter::Iterator};
+
+// There's no --part, since the last day only has one puzzle
+#[derive(Parser)]
+struct Args {
+    /// File to open, or stdin if omitted
+    filename: Option<String>,
+}
 
 fn snafu_to_decimal(snafu: &str) -> i64 {
     let mut decimal = 0;
@@ -62,12 +67,10 @@ fn decimal_to_snafu(mut decimal: i64) -> String {
 }
 
 fn main() {
-    let filename = std::env::args().nth(1).expect("Filename not found");
-
-    let file =
-        File::open(&filename).unwrap_or_else(|_| panic!("Couldn't open {}", filename.as_str()));
-    let reader = BufReader::new(file);
-    let lines = reader.lines().map(std::result::Result::unwrap);
+    let args = Args::parse();
+    let lines = open_input(args.filename.as_deref())
+        .lines()
+        .map(std::result::Result::unwrap);
 
     println!(
         "SNAFU Sum: {}",
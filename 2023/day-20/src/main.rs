@@ -7,6 +7,9 @@ use std::{
     io::{BufRead, BufReader},
 };
 
+use tracing::{debug, info_span, trace};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
 trait Module: Debug {
     fn handle_inputs(&mut self, _inputs: Vec<String>) {}
     fn send_pulse(&mut self, high: bool, from: &str) -> Vec<(String, bool)>;
@@ -146,7 +149,7 @@ fn presses_to_rx(modules: &mut HashMap<String, Box<dyn Module>>) -> usize {
                 if name == "zp" {
                     let message = format!("{module:?}");
                     if message != last_message {
-                        println!("{presses} {message}");
+                        debug!("{presses} {message}");
                         last_message = message;
                     }
                 }
@@ -160,6 +163,13 @@ fn presses_to_rx(modules: &mut HashMap<String, Box<dyn Module>>) -> usize {
 }
 
 fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let parse = info_span!("parse").entered();
     let file = File::open("input.txt").unwrap();
     let reader = BufReader::new(file);
 
@@ -193,11 +203,13 @@ fn main() {
     }
 
     for module in &modules {
-        println!("{module:?}");
+        trace!("{module:?}");
     }
+    parse.exit();
 
     // let (high_pulses, low_pulses) = count_pulses(&mut modules, 1000);
     // println!("{high_pulses} {low_pulses} {}", high_pulses * low_pulses);
 
-    println!("{}", presses_to_rx(&mut modules));
+    let presses = info_span!("presses_to_rx").in_scope(|| presses_to_rx(&mut modules));
+    println!("{presses}");
 }
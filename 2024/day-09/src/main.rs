@@ -173,3 +173,79 @@ fn main() {
         println!("{}", defragment_chunks(files));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Straightforward block-by-block version of defragment_chunks, used as a reference
+    fn defragment_files_naive(blocks: &mut [u16]) {
+        let max_id = blocks.iter().copied().max().unwrap_or(0);
+        for id in (1..=max_id).rev() {
+            let Some(start) = blocks.iter().position(|b| *b == id) else {
+                continue;
+            };
+            let length = blocks[start..].iter().take_while(|b| **b == id).count();
+
+            let mut run = 0;
+            for index in 0..start {
+                if blocks[index] != 0 {
+                    run = 0;
+                    continue;
+                }
+
+                run += 1;
+                if run == length {
+                    let gap = index + 1 - length;
+                    blocks[gap..=index].fill(id);
+                    blocks[start..start + length].fill(0);
+                    break;
+                }
+            }
+        }
+    }
+
+    // A disk map alternates file and free space lengths, starting and ending with a file. Puzzle
+    // inputs never contain empty files, and defragment_chunks relies on that
+    fn map_strategy(file_lengths: std::ops::RangeInclusive<u8>) -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec((file_lengths, 0u8..=9), 1..64).prop_map(|pairs| {
+            let mut map: Vec<_> = pairs
+                .into_iter()
+                .flat_map(|(file, space)| [file, space])
+                .collect();
+            map.pop();
+            map
+        })
+    }
+
+    #[test]
+    fn test_example() {
+        let map: Vec<_> = b"2333133121414131402".iter().map(|b| b - b'0').collect();
+
+        let mut blocks = expand(&map);
+        defragment_blocks(&mut blocks);
+        assert_eq!(checksum(&blocks), 1928);
+
+        assert_eq!(defragment_chunks(get_files(&map)), 2858);
+    }
+
+    proptest! {
+        // Moving whole files should match moving them block by block
+        #[test]
+        fn test_defragment_chunks_matches_naive(map in map_strategy(1..=9)) {
+            let mut blocks = expand(&map);
+            defragment_files_naive(&mut blocks);
+            prop_assert_eq!(defragment_chunks(get_files(&map)), checksum(&blocks));
+        }
+
+        // When every file is a single block, moving files and moving blocks are the same thing
+        #[test]
+        fn test_defragment_blocks_matches_chunks(map in map_strategy(1..=1)) {
+            let mut blocks = expand(&map);
+            prop_assume!(blocks.contains(&0));
+            defragment_blocks(&mut blocks);
+            prop_assert_eq!(checksum(&blocks), defragment_chunks(get_files(&map)));
+        }
+    }
+}
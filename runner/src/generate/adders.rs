@@ -0,0 +1,343 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+const SWAPS: usize = 4;
+
+struct Gate {
+    a: String,
+    op: &'static str,
+    b: String,
+    out: String,
+}
+
+impl Gate {
+    fn new(a: &str, op: &'static str, b: &str, out: &str) -> Self {
+        Self {
+            a: a.to_owned(),
+            op,
+            b: b.to_owned(),
+            out: out.to_owned(),
+        }
+    }
+}
+
+fn random_name(rng: &mut StdRng, used: &mut HashSet<String>) -> String {
+    loop {
+        let name: String = (0..3)
+            .map(|_| char::from(rng.random_range(b'a'..=b'w')))
+            .collect();
+        if used.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+fn swap_outputs(gates: &mut [Gate], first: usize, second: usize) {
+    let out = std::mem::take(&mut gates[first].out);
+    gates[first].out = std::mem::replace(&mut gates[second].out, out);
+}
+
+fn is_acyclic(gates: &[Gate]) -> bool {
+    fn visit<'a>(
+        wire: &'a str,
+        producers: &HashMap<&'a str, &'a Gate>,
+        visiting: &mut HashSet<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> bool {
+        if done.contains(wire) {
+            return true;
+        }
+        let Some(gate) = producers.get(wire) else {
+            return true;
+        };
+        if !visiting.insert(wire) {
+            return false;
+        }
+
+        let acyclic =
+            visit(&gate.a, producers, visiting, done) && visit(&gate.b, producers, visiting, done);
+        visiting.remove(wire);
+        done.insert(wire);
+        acyclic
+    }
+
+    let producers: HashMap<_, _> = gates.iter().map(|gate| (gate.out.as_str(), gate)).collect();
+    let mut visiting = HashSet::new();
+    let mut done = HashSet::new();
+    gates
+        .iter()
+        .all(|gate| visit(&gate.out, &producers, &mut visiting, &mut done))
+}
+
+// Positions within a bit of the x AND y and sum AND carry gates. Both only feed that bit's OR
+// gate, so swapping their outputs leaves the adder working.
+const INTERCHANGEABLE: (usize, usize) = (2, 3);
+
+// Builds a working ripple-carry adder, returning its gates and the indices of each bit's gates
+fn build_adder(rng: &mut StdRng, size: usize) -> (Vec<Gate>, Vec<Vec<usize>>) {
+    let mut used = HashSet::new();
+    let mut gates = Vec::new();
+    // Indices into gates for each bit, so that swaps stay local to a single bit
+    let mut bits = Vec::new();
+
+    let mut carry = random_name(rng, &mut used);
+    gates.push(Gate::new("x00", "XOR", "y00", "z00"));
+    gates.push(Gate::new("x00", "AND", "y00", &carry));
+    bits.push(vec![0, 1]);
+
+    for bit in 1..size {
+        let x = format!("x{bit:02}");
+        let y = format!("y{bit:02}");
+        let sum = random_name(rng, &mut used);
+        let generated = random_name(rng, &mut used);
+        let propagated = random_name(rng, &mut used);
+        let carry_out = if bit + 1 == size {
+            format!("z{size:02}")
+        } else {
+            random_name(rng, &mut used)
+        };
+
+        let start = gates.len();
+        gates.push(Gate::new(&x, "XOR", &y, &sum));
+        gates.push(Gate::new(&sum, "XOR", &carry, &format!("z{bit:02}")));
+        gates.push(Gate::new(&x, "AND", &y, &generated));
+        gates.push(Gate::new(&sum, "AND", &carry, &propagated));
+        gates.push(Gate::new(&generated, "OR", &propagated, &carry_out));
+        bits.push((start..gates.len()).collect());
+
+        carry = carry_out;
+    }
+
+    (gates, bits)
+}
+
+// Swaps the outputs of one pair of gates in each of SWAPS different bits, returning the pairs.
+// Every bit has some pair that breaks the adder without creating a loop, so this only fails if
+// there aren't enough bits.
+fn swap_pairs(
+    rng: &mut StdRng,
+    gates: &mut [Gate],
+    bits: &[Vec<usize>],
+) -> Result<Vec<(usize, usize)>, String> {
+    let mut candidates: Vec<_> = (1..bits.len()).collect();
+    candidates.shuffle(rng);
+
+    let mut swaps = Vec::new();
+    for bit in candidates {
+        if swaps.len() == SWAPS {
+            break;
+        }
+
+        let gate_count = bits[bit].len();
+        let mut pairs: Vec<_> = (0..gate_count)
+            .flat_map(|first| (first + 1..gate_count).map(move |second| (first, second)))
+            .filter(|pair| *pair != INTERCHANGEABLE)
+            .map(|(first, second)| (bits[bit][first], bits[bit][second]))
+            .collect();
+        pairs.shuffle(rng);
+
+        for (first, second) in pairs {
+            swap_outputs(gates, first, second);
+            if is_acyclic(gates) {
+                swaps.push((first, second));
+                break;
+            }
+
+            // Undo the swap and try another pair
+            swap_outputs(gates, first, second);
+        }
+    }
+
+    if swaps.len() == SWAPS {
+        Ok(swaps)
+    } else {
+        Err(format!(
+            "Only found {} of {SWAPS} swaps that keep the gates acyclic",
+            swaps.len()
+        ))
+    }
+}
+
+// Adders are the ripple-carry adders from 2024 day 24, built from the usual two XORs, two ANDs and
+// an OR per bit, with four pairs of gate outputs swapped inside distinct bits. Each swap breaks
+// the adder without creating a loop. Size is the number of input bits, with at least one more bit
+// than there are swaps.
+pub fn generate(rng: &mut StdRng, size: usize) -> Result<String, String> {
+    let size = size.clamp(SWAPS + 1, 99);
+
+    let (mut gates, bits) = build_adder(rng, size);
+    swap_pairs(rng, &mut gates, &bits)?;
+
+    let mut output = String::new();
+    for input in ['x', 'y'] {
+        for bit in 0..size {
+            writeln!(
+                output,
+                "{input}{bit:02}: {}",
+                u8::from(rng.random_bool(0.5))
+            )
+            .unwrap();
+        }
+    }
+    output.push('\n');
+
+    gates.shuffle(rng);
+    for gate in &gates {
+        let (a, b) = if rng.random_bool(0.5) {
+            (&gate.a, &gate.b)
+        } else {
+            (&gate.b, &gate.a)
+        };
+        writeln!(output, "{a} {} {b} -> {}", gate.op, gate.out).unwrap();
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn parse_gates(text: &str) -> Vec<Gate> {
+        text.lines()
+            .map(|line| {
+                let (inputs, out) = line.split_once(" -> ").unwrap();
+                let mut split = inputs.split(' ');
+                let a = split.next().unwrap();
+                let op = match split.next().unwrap() {
+                    "AND" => "AND",
+                    "OR" => "OR",
+                    "XOR" => "XOR",
+                    op => panic!("Unexpected operation {op}"),
+                };
+                let b = split.next().unwrap();
+                Gate::new(a, op, b, out)
+            })
+            .collect()
+    }
+
+    // Adds x and y with the gates, or returns None if some z wire is never set
+    fn add(gates: &[Gate], size: usize, x: u64, y: u64) -> Option<u64> {
+        let mut wires = HashMap::new();
+        for bit in 0..size {
+            wires.insert(format!("x{bit:02}"), x >> bit & 1 == 1);
+            wires.insert(format!("y{bit:02}"), y >> bit & 1 == 1);
+        }
+
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for gate in gates {
+                if wires.contains_key(&gate.out) {
+                    continue;
+                }
+                if let (Some(a), Some(b)) = (wires.get(&gate.a), wires.get(&gate.b)) {
+                    let value = match gate.op {
+                        "AND" => a & b,
+                        "OR" => a | b,
+                        _ => a ^ b,
+                    };
+                    wires.insert(gate.out.clone(), value);
+                    progress = true;
+                }
+            }
+        }
+
+        (0..=size).rev().try_fold(0, |sum, bit| {
+            let value = wires.get(&format!("z{bit:02}"))?;
+            Some(sum << 1 | u64::from(*value))
+        })
+    }
+
+    fn key(gate: &Gate) -> (&str, &str, &str) {
+        let (a, b) = if gate.a < gate.b {
+            (&gate.a, &gate.b)
+        } else {
+            (&gate.b, &gate.a)
+        };
+        (a, gate.op, b)
+    }
+
+    #[test]
+    fn test_same_seed() {
+        let first = generate(&mut StdRng::seed_from_u64(7), 10).unwrap();
+        assert_eq!(first, generate(&mut StdRng::seed_from_u64(7), 10).unwrap());
+        assert_ne!(first, generate(&mut StdRng::seed_from_u64(8), 10).unwrap());
+    }
+
+    #[test]
+    fn test_parses() {
+        for (size, expected) in [(1, SWAPS + 1), (10, 10), (45, 45)] {
+            let output = generate(&mut StdRng::seed_from_u64(0), size).unwrap();
+            let (values, gates) = output.split_once("\n\n").unwrap();
+
+            let values: Vec<_> = values.lines().collect();
+            assert_eq!(values.len(), 2 * expected);
+            for value in values {
+                let (wire, value) = value.split_once(": ").unwrap();
+                assert!(wire.starts_with('x') || wire.starts_with('y'));
+                assert!(value == "0" || value == "1");
+            }
+
+            let gates = parse_gates(gates);
+            assert_eq!(gates.len(), 5 * expected - 3);
+            let outputs: HashSet<_> = gates.iter().map(|gate| gate.out.as_str()).collect();
+            assert_eq!(outputs.len(), gates.len());
+            for bit in 0..=expected {
+                assert!(outputs.contains(format!("z{bit:02}").as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_four_swaps() {
+        const SIZE: usize = 5;
+
+        for seed in 0..20 {
+            let output = generate(&mut StdRng::seed_from_u64(seed), SIZE).unwrap();
+            let gates = parse_gates(output.split_once("\n\n").unwrap().1);
+
+            // Generating starts by building the same working adder from the same seed
+            let (adder, _) = build_adder(&mut StdRng::seed_from_u64(seed), SIZE);
+            let expected: HashMap<_, _> = adder
+                .iter()
+                .map(|gate| (key(gate), gate.out.as_str()))
+                .collect();
+
+            let swapped: HashMap<_, _> = gates
+                .iter()
+                .map(|gate| (expected[&key(gate)], gate.out.as_str()))
+                .filter(|(expected, out)| expected != out)
+                .collect();
+            assert_eq!(swapped.len(), 2 * SWAPS, "Seed {seed}");
+            for (expected, out) in &swapped {
+                assert_eq!(swapped[out], *expected, "Seed {seed}");
+            }
+
+            // Each swap on its own breaks the adder for some input
+            for (first, second) in swapped.iter().filter(|(first, second)| first < second) {
+                let mut broken: Vec<_> = adder
+                    .iter()
+                    .map(|gate| Gate::new(&gate.a, gate.op, &gate.b, &gate.out))
+                    .collect();
+                let first = broken.iter().position(|gate| gate.out == *first).unwrap();
+                let second = broken.iter().position(|gate| gate.out == *second).unwrap();
+                swap_outputs(&mut broken, first, second);
+
+                assert!(
+                    (0..1 << SIZE).any(|x| {
+                        (0..1 << SIZE).any(|y| add(&broken, SIZE, x, y) != Some(x + y))
+                    }),
+                    "Seed {seed}"
+                );
+            }
+            assert_eq!(add(&adder, SIZE, 21, 14), Some(35));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fmt::Write, ops::RangeInclusive};
+
+use rand::{rngs::StdRng, Rng};
+
+const INITIALIZATION_STEPS: usize = 20;
+
+fn random_range(
+    rng: &mut StdRng,
+    starts: RangeInclusive<i32>,
+    lengths: RangeInclusive<i32>,
+    limit: i32,
+) -> (i32, i32) {
+    let start = rng.random_range(starts);
+    let end = (start + rng.random_range(lengths)).min(limit);
+    (start, end)
+}
+
+// Steps are the cuboid reboot steps from 2021 day 22, with the first twenty inside the -50..50
+// initialization region like the real input. Size is the total number of steps.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut output = String::new();
+
+    for step in 0..size {
+        let (starts, lengths, limit) = if step < INITIALIZATION_STEPS {
+            (-50..=40, 5..=50, 50)
+        } else {
+            (-100_000..=90_000, 1_000..=50_000, 100_000)
+        };
+
+        // Start with a lit cuboid so that the first step isn't a no-op
+        let command = if step == 0 || rng.random_bool(0.6) {
+            "on"
+        } else {
+            "off"
+        };
+        let (x_start, x_end) = random_range(rng, starts.clone(), lengths.clone(), limit);
+        let (y_start, y_end) = random_range(rng, starts.clone(), lengths.clone(), limit);
+        let (z_start, z_end) = random_range(rng, starts, lengths, limit);
+        writeln!(
+            output,
+            "{command} x={x_start}..{x_end},y={y_start}..{y_end},z={z_start}..{z_end}"
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    // Parses "on x=1..2,y=3..4,z=5..6" into whether it's on and the range on each axis
+    fn parse_step(line: &str) -> (bool, Vec<(i32, i32)>) {
+        let (command, ranges) = line.split_once(' ').unwrap();
+        let ranges = ranges
+            .split(',')
+            .zip(["x=", "y=", "z="])
+            .map(|(range, prefix)| {
+                let (start, end) = range
+                    .strip_prefix(prefix)
+                    .unwrap()
+                    .split_once("..")
+                    .unwrap();
+                (start.parse().unwrap(), end.parse().unwrap())
+            })
+            .collect();
+        (command == "on", ranges)
+    }
+
+    #[test]
+    fn test_same_seed() {
+        let first = generate(&mut StdRng::seed_from_u64(7), 50);
+        assert_eq!(first, generate(&mut StdRng::seed_from_u64(7), 50));
+        assert_ne!(first, generate(&mut StdRng::seed_from_u64(8), 50));
+    }
+
+    #[test]
+    fn test_steps() {
+        let output = generate(&mut StdRng::seed_from_u64(0), 100);
+        let steps: Vec<_> = output.lines().map(parse_step).collect();
+        assert_eq!(steps.len(), 100);
+        assert!(steps[0].0);
+
+        for (index, (_, ranges)) in steps.iter().enumerate() {
+            assert_eq!(ranges.len(), 3);
+            for (start, end) in ranges {
+                assert!(start <= end, "Step {index} has range {start}..{end}");
+                if index < INITIALIZATION_STEPS {
+                    assert!(-50 <= *start && *end <= 50);
+                }
+            }
+        }
+    }
+}
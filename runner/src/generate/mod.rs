@@ -0,0 +1,53 @@
+//! Seeded generators for random but structurally valid puzzle inputs, for stress testing and
+//! benchmarking solvers on inputs other than our own
+
+mod adders;
+mod cuboids;
+mod valves;
+mod workflows;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+struct Generator {
+    year: u16,
+    day: u8,
+    // Roughly the size of a real puzzle input, in the generator's own units
+    default_size: usize,
+    generate: fn(&mut StdRng, usize) -> Result<String, String>,
+}
+
+const GENERATORS: [Generator; 4] = [
+    Generator {
+        year: 2021,
+        day: 22,
+        default_size: 420,
+        generate: |rng, size| Ok(cuboids::generate(rng, size)),
+    },
+    Generator {
+        year: 2022,
+        day: 16,
+        default_size: 60,
+        generate: |rng, size| Ok(valves::generate(rng, size)),
+    },
+    Generator {
+        year: 2023,
+        day: 19,
+        default_size: 550,
+        generate: |rng, size| Ok(workflows::generate(rng, size)),
+    },
+    Generator {
+        year: 2024,
+        day: 24,
+        default_size: 45,
+        generate: adders::generate,
+    },
+];
+
+pub fn generate(year: u16, day: u8, seed: u64, size: Option<usize>) -> Result<String, String> {
+    let generator = GENERATORS
+        .iter()
+        .find(|generator| generator.year == year && generator.day == day)
+        .ok_or_else(|| format!("No generator for {year} day {day}"))?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    (generator.generate)(&mut rng, size.unwrap_or(generator.default_size))
+}
@@ -0,0 +1,127 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+// Valves are the tunnel graph from 2022 day 16: a connected, undirected graph starting from a
+// zero-flow valve AA, where about a quarter of the other valves have a nonzero flow rate. Size is
+// the number of valves.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let size = size.clamp(2, 26 * 26);
+
+    let mut names: Vec<_> = (b'A'..=b'Z')
+        .flat_map(|first| (b'A'..=b'Z').map(move |second| [first, second]))
+        .filter(|name| name != b"AA")
+        .map(|name| String::from_utf8(name.to_vec()).unwrap())
+        .collect();
+    names.shuffle(rng);
+    names.truncate(size - 1);
+    names.insert(0, String::from("AA"));
+
+    // A random spanning tree keeps the graph connected, and extra edges add some cycles
+    let mut tunnels = vec![BTreeSet::new(); size];
+    for valve in 1..size {
+        let other = rng.random_range(0..valve);
+        tunnels[valve].insert(other);
+        tunnels[other].insert(valve);
+    }
+    for _ in 0..size / 2 {
+        let valve = rng.random_range(0..size);
+        let other = rng.random_range(0..size);
+        if valve != other {
+            tunnels[valve].insert(other);
+            tunnels[other].insert(valve);
+        }
+    }
+
+    let mut order: Vec<_> = (0..size).collect();
+    order.shuffle(rng);
+
+    let mut output = String::new();
+    for valve in order {
+        let flow_rate = if valve != 0 && rng.random_bool(0.25) {
+            rng.random_range(3..=25)
+        } else {
+            0
+        };
+
+        let neighbors: Vec<_> = tunnels[valve]
+            .iter()
+            .map(|neighbor| names[*neighbor].as_str())
+            .collect();
+        let tunnels = if neighbors.len() == 1 {
+            "tunnel leads to valve"
+        } else {
+            "tunnels lead to valves"
+        };
+        writeln!(
+            output,
+            "Valve {} has flow rate={flow_rate}; {tunnels} {}",
+            names[valve],
+            neighbors.join(", ")
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    // Parses each valve into its flow rate and the valves its tunnels lead to
+    fn parse_valves(output: &str) -> HashMap<String, (u32, Vec<String>)> {
+        output
+            .lines()
+            .map(|line| {
+                let (valve, tunnels) = line.split_once("; ").unwrap();
+                let (name, flow_rate) = valve
+                    .strip_prefix("Valve ")
+                    .unwrap()
+                    .split_once(" has flow rate=")
+                    .unwrap();
+                let neighbors = tunnels
+                    .strip_prefix("tunnels lead to valves ")
+                    .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))
+                    .unwrap()
+                    .split(", ")
+                    .map(String::from)
+                    .collect();
+                (name.to_owned(), (flow_rate.parse().unwrap(), neighbors))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed() {
+        let first = generate(&mut StdRng::seed_from_u64(7), 30);
+        assert_eq!(first, generate(&mut StdRng::seed_from_u64(7), 30));
+        assert_ne!(first, generate(&mut StdRng::seed_from_u64(8), 30));
+    }
+
+    #[test]
+    fn test_connected_from_aa() {
+        for size in [1, 2, 60] {
+            let valves = parse_valves(&generate(&mut StdRng::seed_from_u64(0), size));
+            assert_eq!(valves.len(), size.max(2));
+            assert_eq!(valves["AA"].0, 0);
+
+            let mut reached = HashSet::from(["AA"]);
+            let mut queue = VecDeque::from(["AA"]);
+            while let Some(valve) = queue.pop_front() {
+                for neighbor in &valves[valve].1 {
+                    // Tunnels go both ways
+                    assert!(valves[neighbor.as_str()].1.iter().any(|back| back == valve));
+                    if reached.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), valves.len());
+        }
+    }
+}
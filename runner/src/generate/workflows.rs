@@ -0,0 +1,145 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Write,
+};
+
+use rand::{
+    rngs::StdRng,
+    seq::{IndexedRandom, SliceRandom},
+    Rng,
+};
+
+const PARTS: usize = 200;
+
+fn random_name(rng: &mut StdRng, used: &mut HashSet<String>) -> String {
+    loop {
+        let length = rng.random_range(2..=3);
+        let name: String = (0..length)
+            .map(|_| char::from(rng.random_range(b'a'..=b'z')))
+            .collect();
+        if used.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+// Workflows are the part sorting system from 2023 day 19: a tree of workflows rooted at "in", so
+// every part is eventually accepted or rejected, followed by a list of random parts. Size is the
+// number of workflows.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut used = HashSet::from([String::from("in"), String::from("A"), String::from("R")]);
+    let mut remaining = size.saturating_sub(1);
+    let mut pending = VecDeque::from([String::from("in")]);
+
+    let mut workflows = Vec::new();
+    while let Some(name) = pending.pop_front() {
+        let slots = rng.random_range(2..=4);
+        let mut rules = Vec::new();
+        for slot in 0..slots {
+            // Make sure the tree keeps growing until we've created all of the workflows
+            let must_branch = pending.is_empty() && slot + 1 == slots;
+            let target = if remaining > 0 && (must_branch || rng.random_bool(0.6)) {
+                remaining -= 1;
+                let child = random_name(rng, &mut used);
+                pending.push_back(child.clone());
+                child
+            } else {
+                String::from(if rng.random_bool(0.5) { "A" } else { "R" })
+            };
+
+            if slot + 1 == slots {
+                rules.push(target);
+            } else {
+                let category = ['x', 'm', 'a', 's'].choose(rng).unwrap();
+                let comparison = if rng.random_bool(0.5) { '<' } else { '>' };
+                let value = rng.random_range(1..=4000);
+                rules.push(format!("{category}{comparison}{value}:{target}"));
+            }
+        }
+        workflows.push(format!("{name}{{{}}}", rules.join(",")));
+    }
+    workflows.shuffle(rng);
+
+    let mut output = workflows.join("\n");
+    output.push_str("\n\n");
+    for _ in 0..PARTS {
+        let [x, m, a, s]: [u16; 4] = std::array::from_fn(|_| rng.random_range(1..=4000));
+        writeln!(output, "{{x={x},m={m},a={a},s={s}}}").unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    // Parses each workflow into the targets of its rules, checking each condition on the way
+    fn parse_workflows(text: &str) -> Vec<(String, Vec<String>)> {
+        text.lines()
+            .map(|line| {
+                let (name, rules) = line.strip_suffix('}').unwrap().split_once('{').unwrap();
+                let rules: Vec<_> = rules.split(',').collect();
+                let (last, conditional) = rules.split_last().unwrap();
+                let mut targets: Vec<_> = conditional
+                    .iter()
+                    .map(|rule| {
+                        let (condition, target) = rule.split_once(':').unwrap();
+                        assert!("xmas".contains(&condition[..1]));
+                        assert!("<>".contains(&condition[1..2]));
+                        condition[2..].parse::<u16>().unwrap();
+                        String::from(target)
+                    })
+                    .collect();
+                targets.push(String::from(*last));
+                (name.to_owned(), targets)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed() {
+        let first = generate(&mut StdRng::seed_from_u64(7), 40);
+        assert_eq!(first, generate(&mut StdRng::seed_from_u64(7), 40));
+        assert_ne!(first, generate(&mut StdRng::seed_from_u64(8), 40));
+    }
+
+    #[test]
+    fn test_tree_from_in() {
+        let output = generate(&mut StdRng::seed_from_u64(0), 100);
+        let (workflows, parts) = output.split_once("\n\n").unwrap();
+
+        let workflows = parse_workflows(workflows);
+        assert_eq!(workflows.len(), 100);
+        assert_eq!(workflows.iter().filter(|(name, _)| name == "in").count(), 1);
+        let targets: HashMap<_, _> = workflows.iter().cloned().collect();
+        assert_eq!(targets.len(), workflows.len());
+
+        // Every workflow is reached from in exactly once, so parts can't loop
+        let mut reached = HashSet::from([String::from("in")]);
+        let mut queue = VecDeque::from([String::from("in")]);
+        while let Some(name) = queue.pop_front() {
+            for target in &targets[&name] {
+                if target != "A" && target != "R" {
+                    assert!(reached.insert(target.clone()));
+                    queue.push_back(target.clone());
+                }
+            }
+        }
+        assert_eq!(reached.len(), workflows.len());
+
+        let parts: Vec<_> = parts.lines().collect();
+        assert_eq!(parts.len(), PARTS);
+        for part in parts {
+            let ratings = part.strip_prefix('{').unwrap().strip_suffix('}').unwrap();
+            for (rating, category) in ratings.split(',').zip(["x=", "m=", "a=", "s="]) {
+                let value: u16 = rating.strip_prefix(category).unwrap().parse().unwrap();
+                assert!((1..=4000).contains(&value));
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+#![warn(clippy::pedantic)]
+
+mod generate;
+mod report;
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Build and run the solver for a single day
+    Run(RunArgs),
+
+    /// Print a random puzzle input for a single day
+    Gen(GenArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Solver output as-is
+    Text,
+    /// One JSON record per part, with the time the whole solver run took
+    Json,
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    /// Year of the puzzle
+    year: u16,
+
+    /// Day of the puzzle
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Input file to pass to the solver
+    filename: Option<PathBuf>,
+
+    /// Part of the problem to run
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Print solver diagnostics to stderr (-v for debug, -vv for trace)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Print how long each phase of the solver took to stderr
+    #[arg(short, long)]
+    timings: bool,
+
+    /// Rustup toolchain to build the solver with (e.g. nightly)
+    #[arg(long)]
+    toolchain: Option<String>,
+
+    /// How to print the answers
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(clap::Args)]
+struct GenArgs {
+    /// Year of the puzzle
+    year: u16,
+
+    /// Day of the puzzle
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Seed for the random number generator
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Size of the input, in units that depend on the puzzle (defaults to about a real input)
+    #[arg(long)]
+    size: Option<usize>,
+}
+
+impl RunArgs {
+    // Solvers read their filter from RUST_LOG. Phase spans are emitted at the info level, so
+    // timings come along with any verbosity, but can also be requested on their own.
+    fn log_filter(&self) -> &'static str {
+        match (self.verbose, self.timings) {
+            (0, false) => "off",
+            (0, true) => "info",
+            (1, _) => "debug",
+            _ => "trace",
+        }
+    }
+}
+
+fn repository_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()
+}
+
+fn cargo(toolchain: Option<&str>) -> Command {
+    let mut command = Command::new("cargo");
+    if let Some(toolchain) = toolchain {
+        command.arg(format!("+{toolchain}"));
+    }
+    command
+}
+
+fn build_solver(args: &RunArgs, directory: &Path) -> PathBuf {
+    let target_directory = repository_root().join("target").join(args.year.to_string());
+
+    let status = cargo(args.toolchain.as_deref())
+        .args(["build", "--release", "--quiet", "--manifest-path"])
+        .arg(directory.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_directory)
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Failed to build {}", directory.display());
+
+    target_directory
+        .join("release")
+        .join(format!("day-{:02}", args.day))
+}
+
+// Solvers for these years parse their arguments with clap, so they can be asked for --help
+// without solving anything. The rest ignore their arguments or take the first as a filename.
+const CLAP_YEARS: [u16; 5] = [2017, 2020, 2021, 2022, 2024];
+
+// Not every clap-based solver takes --part, so ask the built solver whether it lists the flag
+fn accepts_part(year: u16, binary: &Path) -> bool {
+    CLAP_YEARS.contains(&year)
+        && Command::new(binary)
+            .arg("--help")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("--part"))
+}
+
+fn run(args: &RunArgs) {
+    let directory = repository_root()
+        .join(args.year.to_string())
+        .join(format!("day-{:02}", args.day));
+    assert!(
+        directory.join("Cargo.toml").exists(),
+        "No solver for {} day {}",
+        args.year,
+        args.day
+    );
+
+    let binary = build_solver(args, &directory);
+
+    // Solvers that hard-code input.txt expect to run from their own directory
+    let mut command = Command::new(&binary);
+    command
+        .current_dir(&directory)
+        .env("RUST_LOG", args.log_filter());
+    if let Some(filename) = &args.filename {
+        command.arg(std::path::absolute(filename).expect("Failed to resolve input path"));
+    }
+    if let Some(part) = args.part {
+        assert!(
+            accepts_part(args.year, &binary),
+            "{} day {} doesn't take --part, so run it without one to get every part it solves",
+            args.year,
+            args.day
+        );
+        command.arg("--part").arg(part.to_string());
+    }
+
+    match args.format {
+        Format::Text => {
+            let status = command.status().expect("Failed to run solver");
+            assert!(status.success(), "Solver exited with {status}");
+        }
+        Format::Json => {
+            let start = Instant::now();
+            let output = command
+                .stdin(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .output()
+                .expect("Failed to run solver");
+            let run_elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+            assert!(
+                output.status.success(),
+                "Solver exited with {}",
+                output.status
+            );
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let records =
+                report::parse_answers(args.year, args.day, args.part, &stdout, run_elapsed_ms)
+                    .unwrap_or_else(|error| panic!("{error}"));
+            for record in records {
+                println!("{}", serde_json::to_string(&record).unwrap());
+            }
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    match args.command {
+        Commands::Run(args) => run(&args),
+        Commands::Gen(args) => {
+            let input = generate::generate(args.year, args.day, args.seed, args.size)
+                .unwrap_or_else(|error| panic!("{error}"));
+            print!("{input}");
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    // How long the whole solver run took, which covers every part it solved, so records from the
+    // same run share it
+    pub run_elapsed_ms: f64,
+}
+
+// Solvers label their answers inconsistently ("Encryption key: 123", "Window increases: 7", or
+// just "7"), so the answer is whatever follows the first label on the line
+fn strip_label(line: &str) -> &str {
+    line.split_once(": ")
+        .map_or(line, |(_, answer)| answer)
+        .trim()
+}
+
+// Each non-empty line of solver output is part of an answer. When a part was requested, every
+// line belongs to it, since some solvers print their answer over several lines (letter art) or
+// print extra detail. Otherwise solvers print their answers one line per part, in order, and
+// anything past two lines can't be matched to a part.
+pub fn parse_answers(
+    year: u16,
+    day: u8,
+    part: Option<u8>,
+    stdout: &str,
+    run_elapsed_ms: f64,
+) -> Result<Vec<Record>, String> {
+    let answers: Vec<_> = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(strip_label)
+        .collect();
+
+    let record = |part, answer| Record {
+        year,
+        day,
+        part,
+        answer,
+        run_elapsed_ms,
+    };
+
+    match part {
+        Some(_) if answers.is_empty() => Ok(Vec::new()),
+        Some(part) => Ok(vec![record(part, answers.join("\n"))]),
+        None if answers.len() > 2 => Err(format!(
+            "Solver printed {} answer lines, but puzzles only have two parts (pass --part to keep \
+             them together)",
+            answers.len()
+        )),
+        None => Ok(answers
+            .into_iter()
+            .zip(1u8..)
+            .map(|(answer, part)| record(part, answer.to_owned()))
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_label() {
+        assert_eq!(strip_label("Encryption key: 6011069"), "6011069");
+        assert_eq!(strip_label("1514285714288"), "1514285714288");
+        assert_eq!(strip_label("Total: 1,2,3"), "1,2,3");
+        assert_eq!(
+            strip_label("Line 3: accepted by [range]"),
+            "accepted by [range]"
+        );
+        assert_eq!(strip_label("Shortest: a: b"), "a: b");
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let records =
+            parse_answers(2021, 21, None, "One game: 739785\nMultiverse: 444\n", 1.5).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].part, 1);
+        assert_eq!(records[0].answer, "739785");
+        assert_eq!(records[1].part, 2);
+        assert_eq!(records[1].answer, "444");
+
+        let records = parse_answers(2024, 9, Some(2), "2858\n", 1.5).unwrap();
+        assert_eq!(
+            records,
+            [Record {
+                year: 2024,
+                day: 9,
+                part: 2,
+                answer: String::from("2858"),
+                run_elapsed_ms: 1.5,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_line_answers() {
+        let records = parse_answers(2020, 10, Some(2), "Arrangements: 8\n0, 1, 4\n", 1.5).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].part, 2);
+        assert_eq!(records[0].answer, "8\n0, 1, 4");
+
+        assert!(parse_answers(2022, 10, None, "13140\n##..\n#..#\n", 1.5).is_err());
+        assert!(parse_answers(2022, 10, Some(1), "", 1.5)
+            .unwrap()
+            .is_empty());
+    }
+}